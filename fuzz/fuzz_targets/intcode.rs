@@ -0,0 +1,15 @@
+// Differential fuzzing of the intcode computer, run with
+// `cargo fuzz run intcode` from this directory.
+
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate aoc_rs;
+
+use aoc_rs::y2019::computer;
+
+fuzz_target!(|data: &[u8]| {
+    if let Err(msg) = computer::fuzz::fuzz_one(data) {
+        panic!("{}", msg);
+    }
+});
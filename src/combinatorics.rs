@@ -0,0 +1,223 @@
+// Lazy iterators over the ways of arranging or picking elements, for the
+// puzzles which try every possibility. Each item is a freshly allocated
+// `Vec` of clones of the elements.
+
+// every ordering of the elements, using Heap's algorithm: each permutation
+// only swaps two elements of the previous one
+pub fn permutations<T: Clone>(items: &[T]) -> Permutations<T> {
+    Permutations {
+        items: items.to_vec(),
+        counters: vec![0; items.len()],
+        i: 0,
+        started: false,
+    }
+}
+
+// every ordering of every `k` elements
+pub fn k_permutations<T: Clone>(items: &[T], k: usize) -> impl Iterator<Item = Vec<T>> {
+    combinations(items, k).flat_map(|c| permutations(&c))
+}
+
+// every way of picking `k` elements, keeping their order, in lexicographic
+// order of their positions
+pub fn combinations<T: Clone>(items: &[T], k: usize) -> Combinations<T> {
+    Combinations {
+        items: items.to_vec(),
+        indices: (0..k).collect(),
+        done: k > items.len(),
+    }
+}
+
+// every subset, from the smallest to the largest
+pub fn power_set<T: Clone>(items: &[T]) -> impl Iterator<Item = Vec<T>> {
+    let items = items.to_vec();
+    (0..=items.len()).flat_map(move |k| combinations(&items, k))
+}
+
+// every way of picking one element in each set, the last set changing first
+pub fn cartesian_product<T: Clone>(sets: &[Vec<T>]) -> CartesianProduct<T> {
+    CartesianProduct {
+        sets: sets.to_vec(),
+        indices: vec![0; sets.len()],
+        done: sets.iter().any(|s| s.is_empty()),
+    }
+}
+
+pub struct Permutations<T> {
+    items: Vec<T>,
+    // the state of the recursive version of the algorithm, unrolled
+    counters: Vec<usize>,
+    i: usize,
+    started: bool,
+}
+
+impl<T: Clone> Iterator for Permutations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        if !self.started {
+            self.started = true;
+            return Some(self.items.clone());
+        }
+        while self.i < self.items.len() {
+            if self.counters[self.i] < self.i {
+                if self.i.is_multiple_of(2) {
+                    self.items.swap(0, self.i);
+                } else {
+                    self.items.swap(self.counters[self.i], self.i);
+                }
+                self.counters[self.i] += 1;
+                self.i = 0;
+                return Some(self.items.clone());
+            }
+            self.counters[self.i] = 0;
+            self.i += 1;
+        }
+        None
+    }
+}
+
+pub struct Combinations<T> {
+    items: Vec<T>,
+    indices: Vec<usize>,
+    done: bool,
+}
+
+impl<T: Clone> Iterator for Combinations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        if self.done {
+            return None;
+        }
+        let result = self
+            .indices
+            .iter()
+            .map(|&i| self.items[i].clone())
+            .collect();
+
+        // move the rightmost index which can still move, and put the
+        // following ones right after it
+        let (n, k) = (self.items.len(), self.indices.len());
+        match (0..k).rev().find(|&i| self.indices[i] != i + n - k) {
+            Some(i) => {
+                self.indices[i] += 1;
+                for j in i + 1..k {
+                    self.indices[j] = self.indices[j - 1] + 1;
+                }
+            }
+            None => self.done = true,
+        }
+        Some(result)
+    }
+}
+
+pub struct CartesianProduct<T> {
+    sets: Vec<Vec<T>>,
+    indices: Vec<usize>,
+    done: bool,
+}
+
+impl<T: Clone> Iterator for CartesianProduct<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        if self.done {
+            return None;
+        }
+        let result = self
+            .indices
+            .iter()
+            .zip(self.sets.iter())
+            .map(|(&i, s)| s[i].clone())
+            .collect();
+
+        // count like an odometer
+        self.done = true;
+        for i in (0..self.sets.len()).rev() {
+            self.indices[i] += 1;
+            if self.indices[i] < self.sets[i].len() {
+                self.done = false;
+                break;
+            }
+            self.indices[i] = 0;
+        }
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_permutations() {
+        assert_eq!(
+            permutations(&[1, 2, 3]).collect::<Vec<_>>(),
+            vec![
+                vec![1, 2, 3],
+                vec![2, 1, 3],
+                vec![3, 1, 2],
+                vec![1, 3, 2],
+                vec![2, 3, 1],
+                vec![3, 2, 1],
+            ]
+        );
+        for n in 0..7 {
+            let items: Vec<usize> = (0..n).collect();
+            let all: HashSet<Vec<usize>> = permutations(&items).collect();
+            assert_eq!(all.len(), (1..=n).product::<usize>());
+        }
+        assert_eq!(k_permutations(&['a', 'b', 'c', 'd'], 2).count(), 12);
+    }
+
+    #[test]
+    fn test_combinations() {
+        assert_eq!(
+            combinations(&[1, 2, 3, 4], 2).collect::<Vec<_>>(),
+            vec![
+                vec![1, 2],
+                vec![1, 3],
+                vec![1, 4],
+                vec![2, 3],
+                vec![2, 4],
+                vec![3, 4],
+            ]
+        );
+        assert_eq!(combinations(&[1, 2], 0).collect::<Vec<_>>(), vec![vec![]]);
+        assert_eq!(combinations(&[1, 2], 3).count(), 0);
+        assert_eq!(combinations(&(0..10).collect::<Vec<_>>(), 4).count(), 210);
+    }
+
+    #[test]
+    fn test_power_set() {
+        assert_eq!(
+            power_set(&['a', 'b', 'c']).collect::<Vec<_>>(),
+            vec![
+                vec![],
+                vec!['a'],
+                vec!['b'],
+                vec!['c'],
+                vec!['a', 'b'],
+                vec!['a', 'c'],
+                vec!['b', 'c'],
+                vec!['a', 'b', 'c'],
+            ]
+        );
+        assert_eq!(power_set::<u8>(&[]).count(), 1);
+    }
+
+    #[test]
+    fn test_cartesian_product() {
+        assert_eq!(
+            cartesian_product(&[vec![0, 1], vec![5], vec![7, 8]]).collect::<Vec<_>>(),
+            vec![vec![0, 5, 7], vec![0, 5, 8], vec![1, 5, 7], vec![1, 5, 8]]
+        );
+        assert_eq!(cartesian_product(&[vec![0, 1], vec![]]).count(), 0);
+        assert_eq!(
+            cartesian_product::<u8>(&[]).collect::<Vec<_>>(),
+            vec![vec![]]
+        );
+    }
+}
@@ -0,0 +1,123 @@
+// Counting how many times each value appears, like Python's `Counter`.
+
+use std::collections::hash_map;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::iter::FromIterator;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counter<T: Eq + Hash> {
+    counts: HashMap<T, usize>,
+}
+
+impl<T: Eq + Hash> Counter<T> {
+    pub fn new() -> Counter<T> {
+        Counter {
+            counts: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, value: T) {
+        self.add_n(value, 1);
+    }
+
+    pub fn add_n(&mut self, value: T, n: usize) {
+        if n > 0 {
+            *self.counts.entry(value).or_insert(0) += n;
+        }
+    }
+
+    // 0 for values never seen
+    pub fn get(&self, value: &T) -> usize {
+        self.counts.get(value).cloned().unwrap_or(0)
+    }
+
+    // number of values, counting the repeats
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    // in no particular order
+    pub fn iter(&self) -> hash_map::Iter<'_, T, usize> {
+        self.counts.iter()
+    }
+
+    pub fn merge(&mut self, other: Counter<T>) {
+        for (value, n) in other.counts {
+            self.add_n(value, n);
+        }
+    }
+
+    // how many values appear once, how many twice...
+    pub fn counts_of_counts(&self) -> Counter<usize> {
+        self.counts.values().cloned().collect()
+    }
+}
+
+impl<T: Eq + Hash + Ord + Clone> Counter<T> {
+    // the most frequent first, ties in increasing order of the values
+    pub fn most_common(&self) -> Vec<(T, usize)> {
+        let mut result: Vec<(T, usize)> =
+            self.counts.iter().map(|(v, n)| (v.clone(), *n)).collect();
+        result.sort_by(|(v1, n1), (v2, n2)| n2.cmp(n1).then_with(|| v1.cmp(v2)));
+        result
+    }
+
+    pub fn top(&self, k: usize) -> Vec<(T, usize)> {
+        let mut result = self.most_common();
+        result.truncate(k);
+        result
+    }
+}
+
+impl<T: Eq + Hash> Default for Counter<T> {
+    fn default() -> Counter<T> {
+        Counter::new()
+    }
+}
+
+impl<T: Eq + Hash> Extend<T> for Counter<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, values: I) {
+        for value in values {
+            self.add(value);
+        }
+    }
+}
+
+impl<T: Eq + Hash> FromIterator<T> for Counter<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Counter<T> {
+        let mut counter = Counter::new();
+        counter.extend(values);
+        counter
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_counter() {
+        let mut letters: Counter<char> = "abracadabra".chars().collect();
+        assert_eq!(letters.get(&'a'), 5);
+        assert_eq!(letters.get(&'z'), 0);
+        assert_eq!(letters.iter().count(), 5);
+        assert_eq!(letters.total(), 11);
+        assert_eq!(
+            letters.most_common(),
+            vec![('a', 5), ('b', 2), ('r', 2), ('c', 1), ('d', 1)]
+        );
+        assert_eq!(letters.top(2), vec![('a', 5), ('b', 2)]);
+        assert_eq!(
+            letters.counts_of_counts().most_common(),
+            vec![(1, 2), (2, 2), (5, 1)]
+        );
+
+        letters.merge("cz".chars().collect());
+        letters.add_n('z', 0);
+        assert_eq!(letters.top(3), vec![('a', 5), ('b', 2), ('c', 2)]);
+        assert_eq!(letters.get(&'z'), 1);
+        assert_eq!(letters.total(), 13);
+        assert_eq!(Counter::<u8>::default().iter().next(), None);
+    }
+}
@@ -0,0 +1,306 @@
+// Finding where a sequence starts repeating.
+//
+// `floyd` and `brent` work on sequences where each value only depends on the
+// previous one, `x, f(x), f(f(x))...`, in constant memory. They never return
+// if the sequence doesn't cycle, which can't happen when `f` only takes a
+// finite number of values. `first_repeat` works on any sequence but
+// remembers every value.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+// the values at `start` and `start + length` are the same, and that's the
+// earliest such pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+// the first value seen twice, and when
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repeat<T> {
+    pub value: T,
+    pub first: usize,
+    pub second: usize,
+}
+
+impl<T> Repeat<T> {
+    // only meaningful when each value depends on the previous one, which is
+    // what `floyd` and `brent` are checked against
+    #[cfg(test)]
+    pub fn cycle(&self) -> Cycle {
+        Cycle {
+            start: self.first,
+            length: self.second - self.first,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CycleError {
+    Empty,
+    // the sums keep drifting away
+    NoRepeat,
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CycleError::Empty => write!(f, "no value to add"),
+            CycleError::NoRepeat => write!(f, "no sum is ever reached twice"),
+        }
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+pub fn floyd<T: Clone + PartialEq, F: Fn(&T) -> T>(x0: &T, f: F) -> Cycle {
+    // the hare goes twice as fast: they meet somewhere in the cycle, at a
+    // multiple of its length
+    let mut tortoise = f(x0);
+    let mut hare = f(&f(x0));
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&f(&hare));
+    }
+
+    // keeping that distance, they meet again at the start of the cycle
+    let mut start = 0;
+    tortoise = x0.clone();
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = f(&tortoise);
+    while tortoise != hare {
+        hare = f(&hare);
+        length += 1;
+    }
+    Cycle { start, length }
+}
+
+pub fn brent<T: Clone + PartialEq, F: Fn(&T) -> T>(x0: &T, f: F) -> Cycle {
+    // the tortoise teleports to the hare at every power of two, until the
+    // hare finds it again: the distance is then the length of the cycle
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = x0.clone();
+    let mut hare = f(x0);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = f(&hare);
+        length += 1;
+    }
+
+    // with the hare one length ahead, they meet at the start of the cycle
+    let mut start = 0;
+    tortoise = x0.clone();
+    hare = x0.clone();
+    for _ in 0..length {
+        hare = f(&hare);
+    }
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        start += 1;
+    }
+    Cycle { start, length }
+}
+
+// None if the sequence ends without repeating itself
+pub fn first_repeat<T, I>(values: I) -> Option<Repeat<T>>
+where
+    T: Clone + Eq + Hash,
+    I: IntoIterator<Item = T>,
+{
+    let mut seen = HashMap::new();
+    for (i, value) in values.into_iter().enumerate() {
+        match seen.entry(value) {
+            Entry::Occupied(e) => {
+                return Some(Repeat {
+                    value: e.key().clone(),
+                    first: *e.get(),
+                    second: i,
+                })
+            }
+            Entry::Vacant(e) => {
+                e.insert(i);
+            }
+        }
+    }
+    None
+}
+
+// The first repeat in `0, d0, d0 + d1...`, adding the deltas over and over.
+//
+// With `s` the sums over the first pass and `total` the sum of all the
+// deltas, the value at `k * n + j` is `s[j] + k * total`. Unless it repeats
+// during the first pass, a sum `s[i]` comes back when some `s[j]` catches up
+// with it after `k` passes: `s[j] + k * total == s[i]`. So only the sums
+// equal modulo `total` matter, and each one can only be caught up by the
+// closest one behind it.
+pub fn first_repeated_sum(deltas: &[i64]) -> Result<Repeat<i64>, CycleError> {
+    if deltas.is_empty() {
+        return Err(CycleError::Empty);
+    }
+    let n = deltas.len();
+    let sums: Vec<i64> = deltas
+        .iter()
+        .scan(0, |sum, d| {
+            let before = *sum;
+            *sum += d;
+            Some(before)
+        })
+        .collect();
+    let total: i64 = deltas.iter().sum();
+
+    // when the deltas add up to 0, the second pass is the same as the first
+    // one, and it starts with a repeat of 0
+    let first_pass = if total == 0 { n + 1 } else { n };
+    if let Some(repeat) = first_repeat(sums.iter().cloned().chain(Some(0)).take(first_pass)) {
+        return Ok(repeat);
+    }
+
+    // sums in order, grouped by remainder, in the direction of the drift
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|&i| (sums[i].rem_euclid(total), sums[i] * total.signum()));
+    order
+        .windows(2)
+        .filter(|w| sums[w[0]].rem_euclid(total) == sums[w[1]].rem_euclid(total))
+        .map(|w| {
+            let (j, i) = (w[0], w[1]);
+            let passes = ((sums[i] - sums[j]) / total) as usize;
+            Repeat {
+                value: sums[i],
+                first: i,
+                second: passes * n + j,
+            }
+        })
+        .min_by_key(|r| r.second)
+        .ok_or(CycleError::NoRepeat)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cycle_detection() {
+        let f = |x: &u64| (x * x + 1) % 255;
+        let hashed = first_repeat((0..).scan(3, |x, _| {
+            let current = *x;
+            *x = f(x);
+            Some(current)
+        }))
+        .unwrap()
+        .cycle();
+        assert_eq!(floyd(&3, f), hashed);
+        assert_eq!(brent(&3, f), hashed);
+
+        // a single loop back to the start
+        let f = |x: &u8| (x + 1) % 7;
+        let expected = Cycle {
+            start: 0,
+            length: 7,
+        };
+        assert_eq!(floyd(&0, f), expected);
+        assert_eq!(brent(&0, f), expected);
+        // a tail of 4 values before entering the cycle
+        let f = |x: &u8| if *x < 10 { x + 1 } else { 6 };
+        let expected = Cycle {
+            start: 4,
+            length: 5,
+        };
+        assert_eq!(floyd(&2, f), expected);
+        assert_eq!(brent(&2, f), expected);
+    }
+
+    #[test]
+    fn test_floyd_matches_brent() {
+        // pseudo random functions over small sets, checked against each
+        // other and against remembering every value
+        let mut seed: u64 = 7;
+        let mut random = move |n: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % n
+        };
+        for _ in 0..500 {
+            let n = 1 + random(40);
+            let table: Vec<usize> = (0..n).map(|_| random(n)).collect();
+            let f = |x: &usize| table[*x];
+            let x0 = random(n);
+            let hashed = first_repeat(std::iter::successors(Some(x0), |x| Some(f(x))))
+                .unwrap()
+                .cycle();
+            assert_eq!(floyd(&x0, f), brent(&x0, f), "{:?} from {}", table, x0);
+            assert_eq!(brent(&x0, f), hashed, "{:?} from {}", table, x0);
+        }
+    }
+
+    #[test]
+    fn test_first_repeat() {
+        assert_eq!(
+            first_repeat("abcdbe".chars()),
+            Some(Repeat {
+                value: 'b',
+                first: 1,
+                second: 4
+            })
+        );
+        assert_eq!(first_repeat("abc".chars()), None);
+    }
+
+    #[test]
+    fn test_first_repeated_sum() {
+        // the examples from 2018 day 1
+        let value = |deltas: &[i64]| first_repeated_sum(deltas).map(|r| r.value);
+        assert_eq!(value(&[1, -2, 3, 1]), Ok(2));
+        assert_eq!(value(&[1, -1]), Ok(0));
+        assert_eq!(value(&[3, 3, 4, -2, -4]), Ok(10));
+        assert_eq!(value(&[-6, 3, 8, 5, -6]), Ok(5));
+        assert_eq!(value(&[7, 7, -2, -7, -4]), Ok(14));
+
+        assert_eq!(value(&[]), Err(CycleError::Empty));
+        assert_eq!(value(&[1, 1]), Err(CycleError::NoRepeat));
+        assert_eq!(value(&[5, -2]), Err(CycleError::NoRepeat));
+        assert_eq!(value(&[0]), Ok(0));
+    }
+
+    #[test]
+    fn test_first_repeated_sum_matches_hash() {
+        // pseudo random deltas, checked against the plain simulation
+        let mut seed: u64 = 42;
+        let mut random = move || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as i64
+        };
+        for _ in 0..300 {
+            let n = 1 + random() % 8;
+            let deltas: Vec<i64> = (0..n).map(|_| random() % 21 - 10).collect();
+            let sums = deltas.iter().cycle().scan(0, |sum, d| {
+                let before = *sum;
+                *sum += d;
+                Some(before)
+            });
+            let expected = first_repeat(sums.take(10_000));
+            match first_repeated_sum(&deltas) {
+                Ok(repeat) => assert_eq!(Some(repeat), expected, "{:?}", deltas),
+                Err(_) => assert_eq!(expected, None, "{:?}", deltas),
+            }
+        }
+    }
+}
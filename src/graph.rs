@@ -0,0 +1,564 @@
+// Graphs whose nodes are named by arbitrary values, like the bodies of the
+// orbit maps. Names are interned: each distinct name gets a dense `NodeId`,
+// which is what all the algorithms work with. Edges are weighted, the
+// unweighted algorithms simply ignore the weights.
+
+use std::borrow::Borrow;
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+pub type NodeId = usize;
+
+#[derive(Debug, Clone)]
+pub struct Graph<N> {
+    directed: bool,
+    names: Vec<N>,
+    ids: HashMap<N, NodeId>,
+    // outgoing edges of each node, with their weight
+    edges: Vec<Vec<(NodeId, u64)>>,
+}
+
+impl<N: Clone + Eq + Hash> Graph<N> {
+    pub fn directed() -> Graph<N> {
+        Graph::new(true)
+    }
+
+    // every edge can be followed both ways
+    pub fn undirected() -> Graph<N> {
+        Graph::new(false)
+    }
+
+    fn new(directed: bool) -> Graph<N> {
+        Graph {
+            directed,
+            names: Vec::new(),
+            ids: HashMap::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    // id of the node with that name, which is added if needed
+    pub fn intern(&mut self, name: N) -> NodeId {
+        match self.ids.entry(name) {
+            Entry::Occupied(e) => *e.get(),
+            Entry::Vacant(e) => {
+                let id = self.names.len();
+                self.names.push(e.key().clone());
+                self.edges.push(Vec::new());
+                e.insert(id);
+                id
+            }
+        }
+    }
+
+    pub fn id<Q>(&self, name: &Q) -> Option<NodeId>
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.ids.get(name).cloned()
+    }
+
+    pub fn name(&self, id: NodeId) -> &N {
+        &self.names[id]
+    }
+
+    pub fn add_edge(&mut self, from: N, to: N) -> (NodeId, NodeId) {
+        self.add_weighted_edge(from, to, 1)
+    }
+
+    pub fn add_weighted_edge(&mut self, from: N, to: N, weight: u64) -> (NodeId, NodeId) {
+        let from = self.intern(from);
+        let to = self.intern(to);
+        self.edges[from].push((to, weight));
+        if !self.directed {
+            self.edges[to].push((from, weight));
+        }
+        (from, to)
+    }
+}
+
+impl<N> Graph<N> {
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    pub fn nodes(&self) -> std::ops::Range<NodeId> {
+        0..self.len()
+    }
+
+    // outgoing edges of the node, with their weight
+    pub fn edges(&self, id: NodeId) -> &[(NodeId, u64)] {
+        &self.edges[id]
+    }
+
+    pub fn neighbours<'a>(&'a self, id: NodeId) -> impl Iterator<Item = NodeId> + 'a {
+        self.edges[id].iter().map(|&(n, _)| n)
+    }
+
+    // nodes without any incoming edge
+    pub fn roots(&self) -> Vec<NodeId> {
+        let mut has_parent = vec![false; self.len()];
+        for n in self.edges.iter().flat_map(|e| e.iter()) {
+            has_parent[n.0] = true;
+        }
+        self.nodes().filter(|&id| !has_parent[id]).collect()
+    }
+
+    // number of edges to follow from `start` to reach each node, `None` for
+    // the unreachable ones
+    pub fn bfs(&self, start: NodeId) -> Vec<Option<usize>> {
+        let mut dists = vec![None; self.len()];
+        let mut queue = VecDeque::new();
+        dists[start] = Some(0);
+        queue.push_back(start);
+        while let Some(node) = queue.pop_front() {
+            let d = dists[node].unwrap();
+            for n in self.neighbours(node) {
+                if dists[n].is_none() {
+                    dists[n] = Some(d + 1);
+                    queue.push_back(n);
+                }
+            }
+        }
+        dists
+    }
+
+    // weight of the lightest path from `start` to each node
+    pub fn dijkstra(&self, start: NodeId) -> Vec<Option<u64>> {
+        let mut dists = vec![None; self.len()];
+        let mut heap = BinaryHeap::new();
+        dists[start] = Some(0);
+        heap.push(Reverse((0, start)));
+        while let Some(Reverse((cost, node))) = heap.pop() {
+            if dists[node].is_some_and(|d| cost > d) {
+                continue;
+            }
+            for &(n, weight) in self.edges(node) {
+                let next = cost + weight;
+                if dists[n].is_none_or(|d| next < d) {
+                    dists[n] = Some(next);
+                    heap.push(Reverse((next, n)));
+                }
+            }
+        }
+        dists
+    }
+
+    // lightest path from `start` to `goal` and its weight. The heuristic must
+    // never overestimate the remaining weight to the goal.
+    pub fn astar<H>(&self, start: NodeId, goal: NodeId, heuristic: H) -> Option<(u64, Vec<NodeId>)>
+    where
+        H: Fn(NodeId) -> u64,
+    {
+        let mut costs: Vec<Option<u64>> = vec![None; self.len()];
+        let mut came_from: Vec<Option<NodeId>> = vec![None; self.len()];
+        let mut heap = BinaryHeap::new();
+        costs[start] = Some(0);
+        heap.push(Reverse((heuristic(start), 0, start)));
+        while let Some(Reverse((_, cost, node))) = heap.pop() {
+            if node == goal {
+                let mut path = vec![goal];
+                while let Some(prev) = came_from[*path.last().unwrap()] {
+                    path.push(prev);
+                }
+                path.reverse();
+                return Some((cost, path));
+            }
+            if costs[node].is_some_and(|c| cost > c) {
+                continue;
+            }
+            for &(n, weight) in self.edges(node) {
+                let next = cost + weight;
+                if costs[n].is_none_or(|c| next < c) {
+                    costs[n] = Some(next);
+                    came_from[n] = Some(node);
+                    heap.push(Reverse((next + heuristic(n), next, n)));
+                }
+            }
+        }
+        None
+    }
+
+    pub fn shortest_path(&self, start: NodeId, goal: NodeId) -> Option<(u64, Vec<NodeId>)> {
+        self.astar(start, goal, |_| 0)
+    }
+
+    // an order where every node comes before the ones it points to, or a
+    // cycle if there is no such order
+    pub fn topological_sort(&self) -> Result<Vec<NodeId>, Vec<NodeId>> {
+        assert!(self.directed, "only directed graphs can be sorted");
+        let mut incoming = vec![0; self.len()];
+        for n in self.edges.iter().flat_map(|e| e.iter()) {
+            incoming[n.0] += 1;
+        }
+        let mut ready: VecDeque<NodeId> = self.nodes().filter(|&id| incoming[id] == 0).collect();
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(node) = ready.pop_front() {
+            order.push(node);
+            for n in self.neighbours(node) {
+                incoming[n] -= 1;
+                if incoming[n] == 0 {
+                    ready.push_back(n);
+                }
+            }
+        }
+        if order.len() == self.len() {
+            Ok(order)
+        } else {
+            Err(self.find_cycle().expect("no order but no cycle either"))
+        }
+    }
+
+    // the nodes of some cycle, in the order they are visited. For undirected
+    // graphs, going back and forth along a single edge isn't a cycle.
+    pub fn find_cycle(&self) -> Option<Vec<NodeId>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum State {
+            New,
+            InProgress,
+            Done,
+        }
+
+        let mut states = vec![State::New; self.len()];
+        let mut parents: Vec<Option<NodeId>> = vec![None; self.len()];
+        for root in self.nodes() {
+            if states[root] != State::New {
+                continue;
+            }
+            // depth first, with the index of the next edge to look at
+            let mut stack = vec![(root, 0)];
+            states[root] = State::InProgress;
+            while let Some(&mut (node, ref mut next_edge)) = stack.last_mut() {
+                let n = match self.edges[node].get(*next_edge) {
+                    Some(&(n, _)) => n,
+                    None => {
+                        states[node] = State::Done;
+                        stack.pop();
+                        continue;
+                    }
+                };
+                *next_edge += 1;
+                match states[n] {
+                    State::New => {
+                        states[n] = State::InProgress;
+                        parents[n] = Some(node);
+                        stack.push((n, 0));
+                    }
+                    State::InProgress if self.directed || parents[node] != Some(n) => {
+                        let mut cycle = vec![node];
+                        while *cycle.last().unwrap() != n {
+                            cycle.push(parents[*cycle.last().unwrap()].unwrap());
+                        }
+                        cycle.reverse();
+                        return Some(cycle);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        None
+    }
+}
+
+// The nodes reachable from a root, each one knowing its parent and depth.
+// Once built, questions about ancestors only climb the tree: they take time
+// proportional to the depth rather than to the size of the graph.
+#[derive(Debug, Clone)]
+pub struct Tree {
+    root: NodeId,
+    parents: Vec<Option<NodeId>>,
+    children: Vec<Vec<NodeId>>,
+    // `None` for the nodes which aren't reachable from the root
+    depths: Vec<Option<usize>>,
+}
+
+impl Tree {
+    // breadth first from the root: if several paths lead to a node, its
+    // parent is on one of the shortest ones
+    pub fn from_graph<N>(graph: &Graph<N>, root: NodeId) -> Tree {
+        let mut parents = vec![None; graph.len()];
+        let mut children = vec![Vec::new(); graph.len()];
+        let depths = graph.bfs(root);
+        for node in graph.nodes() {
+            // nodes out of reach of the root have no parent nor children
+            let depth = match depths[node] {
+                Some(d) => d,
+                None => continue,
+            };
+            for n in graph.neighbours(node) {
+                if parents[n].is_none() && n != root && depths[n] == Some(depth + 1) {
+                    parents[n] = Some(node);
+                    children[node].push(n);
+                }
+            }
+        }
+        Tree {
+            root,
+            parents,
+            children,
+            depths,
+        }
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.depths.get(id).is_some_and(|d| d.is_some())
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.parents.get(id).cloned().flatten()
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.children[id]
+    }
+
+    pub fn depth(&self, id: NodeId) -> Option<usize> {
+        self.depths.get(id).cloned().flatten()
+    }
+
+    // from the node up to the root, both included
+    pub fn ancestors<'a>(&'a self, id: NodeId) -> impl Iterator<Item = NodeId> + 'a {
+        let start = if self.contains(id) { Some(id) } else { None };
+        std::iter::successors(start, move |&n| self.parent(n))
+    }
+
+    pub fn lowest_common_ancestor(&self, a: NodeId, b: NodeId) -> Option<NodeId> {
+        let (mut a, mut b) = (a, b);
+        let (mut depth_a, mut depth_b) = (self.depth(a)?, self.depth(b)?);
+        while depth_a > depth_b {
+            a = self.parents[a]?;
+            depth_a -= 1;
+        }
+        while depth_b > depth_a {
+            b = self.parents[b]?;
+            depth_b -= 1;
+        }
+        while a != b {
+            a = self.parents[a]?;
+            b = self.parents[b]?;
+        }
+        Some(a)
+    }
+
+    // number of edges between the two nodes, going through their lowest
+    // common ancestor
+    pub fn distance(&self, a: NodeId, b: NodeId) -> Option<usize> {
+        let lca = self.lowest_common_ancestor(a, b)?;
+        Some(self.depths[a]? + self.depths[b]? - 2 * self.depths[lca]?)
+    }
+
+    // the nodes from `a` to `b`, both included
+    pub fn path(&self, a: NodeId, b: NodeId) -> Option<Vec<NodeId>> {
+        let lca = self.lowest_common_ancestor(a, b)?;
+        let mut path: Vec<NodeId> = self.ancestors(a).take_while(|&n| n != lca).collect();
+        path.push(lca);
+        let mut down: Vec<NodeId> = self.ancestors(b).take_while(|&n| n != lca).collect();
+        down.reverse();
+        path.extend(down);
+        Some(path)
+    }
+
+    // every node at most `k` edges away from `start`, moving up or down the
+    // tree, along with its distance
+    pub fn within(&self, start: NodeId, k: usize) -> Vec<(NodeId, usize)> {
+        if !self.contains(start) {
+            return Vec::new();
+        }
+        let mut result = vec![(start, 0)];
+        let mut seen = HashSet::new();
+        seen.insert(start);
+        let mut i = 0;
+        while i < result.len() {
+            let (node, d) = result[i];
+            i += 1;
+            if d == k {
+                continue;
+            }
+            let next = self
+                .parent(node)
+                .into_iter()
+                .chain(self.children[node].iter().cloned());
+            for n in next {
+                if seen.insert(n) {
+                    result.push((n, d + 1));
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn weighted() -> Graph<&'static str> {
+        let mut g = Graph::directed();
+        g.add_weighted_edge("a", "b", 7);
+        g.add_weighted_edge("a", "c", 2);
+        g.add_weighted_edge("c", "b", 3);
+        g.add_weighted_edge("b", "d", 1);
+        g.add_weighted_edge("c", "d", 8);
+        g.intern("lonely");
+        g
+    }
+
+    #[test]
+    fn test_interning() {
+        let mut g = Graph::undirected();
+        assert!(g.is_empty());
+        assert!(!g.is_directed());
+        let (a, b) = g.add_edge("a".to_string(), "b".to_string());
+        assert_eq!(g.intern("a".to_string()), a);
+        assert_eq!(g.id("b"), Some(b));
+        assert_eq!(g.id("c"), None);
+        assert_eq!(g.name(b), "b");
+        assert_eq!(g.neighbours(b).collect::<Vec<_>>(), vec![a]);
+        assert_eq!(g.len(), 2);
+        assert!(!g.is_empty());
+    }
+
+    #[test]
+    fn test_searches() {
+        let g = weighted();
+        let id = |name| g.id(name).unwrap();
+        let bfs = g.bfs(id("a"));
+        assert_eq!(bfs[id("d")], Some(2));
+        assert_eq!(bfs[id("lonely")], None);
+
+        let dists = g.dijkstra(id("a"));
+        assert_eq!(dists[id("b")], Some(5));
+        assert_eq!(dists[id("d")], Some(6));
+        assert_eq!(dists[id("lonely")], None);
+
+        let expected = Some((6, vec![id("a"), id("c"), id("b"), id("d")]));
+        assert_eq!(g.shortest_path(id("a"), id("d")), expected);
+        assert_eq!(
+            g.astar(id("a"), id("d"), |n| (n != id("d")) as u64),
+            expected
+        );
+        assert_eq!(g.shortest_path(id("d"), id("a")), None);
+        assert_eq!(g.shortest_path(id("a"), id("lonely")), None);
+    }
+
+    #[test]
+    fn test_topological_sort() {
+        let mut g = weighted();
+        let order = g.topological_sort().unwrap();
+        let position = |name| order.iter().position(|&n| n == g.id(name).unwrap());
+        assert!(position("a") < position("c"));
+        assert!(position("c") < position("b"));
+        assert!(position("b") < position("d"));
+        assert_eq!(g.find_cycle(), None);
+
+        g.add_edge("d", "c");
+        let cycle: Vec<&str> = g
+            .topological_sort()
+            .unwrap_err()
+            .into_iter()
+            .map(|n| *g.name(n))
+            .collect();
+        assert_eq!(cycle, vec!["b", "d", "c"]);
+    }
+
+    #[test]
+    fn test_undirected_cycle() {
+        let mut g = Graph::undirected();
+        g.add_edge(1, 2);
+        g.add_edge(2, 3);
+        assert_eq!(g.find_cycle(), None);
+        g.add_edge(3, 1);
+        assert_eq!(g.find_cycle().map(|c| c.len()), Some(3));
+    }
+
+    #[test]
+    fn test_lowest_common_ancestor() {
+        let mut g = Graph::directed();
+        for (parent, child) in &[("r", "a"), ("a", "b"), ("a", "c"), ("c", "d"), ("r", "e")] {
+            g.add_edge(*parent, *child);
+        }
+        g.intern("x");
+        let id = |name| g.id(name).unwrap();
+        let tree = Tree::from_graph(&g, id("r"));
+        let lca = |a, b| {
+            tree.lowest_common_ancestor(id(a), id(b))
+                .map(|n| *g.name(n))
+        };
+        assert_eq!(lca("b", "d"), Some("a"));
+        assert_eq!(lca("d", "c"), Some("c"));
+        assert_eq!(lca("e", "d"), Some("r"));
+        assert_eq!(lca("e", "x"), None);
+        assert_eq!(g.roots(), vec![id("r"), id("x")]);
+    }
+
+    #[test]
+    fn test_tree() {
+        let mut g = Graph::directed();
+        for (parent, child) in &[("r", "a"), ("a", "b"), ("a", "c"), ("c", "d"), ("r", "e")] {
+            g.add_edge(*parent, *child);
+        }
+        g.intern("x");
+        let id = |name| g.id(name).unwrap();
+        let tree = Tree::from_graph(&g, id("r"));
+        let names =
+            |nodes: Vec<NodeId>| -> Vec<&str> { nodes.into_iter().map(|n| *g.name(n)).collect() };
+
+        assert_eq!(tree.depth(id("d")), Some(3));
+        assert_eq!(tree.depth(id("x")), None);
+        assert_eq!(tree.parent(id("d")), Some(id("c")));
+        assert_eq!(tree.parent(id("r")), None);
+        assert_eq!(
+            names(tree.ancestors(id("d")).collect()),
+            vec!["d", "c", "a", "r"]
+        );
+        assert_eq!(tree.distance(id("b"), id("d")), Some(3));
+        assert_eq!(tree.distance(id("e"), id("e")), Some(0));
+        assert_eq!(tree.distance(id("e"), id("x")), None);
+        assert_eq!(
+            names(tree.path(id("b"), id("e")).unwrap()),
+            vec!["b", "a", "r", "e"]
+        );
+
+        let mut near: Vec<(&str, usize)> = tree
+            .within(id("c"), 2)
+            .into_iter()
+            .map(|(n, d)| (*g.name(n), d))
+            .collect();
+        near.sort();
+        assert_eq!(near, vec![("a", 1), ("b", 2), ("c", 0), ("d", 1), ("r", 2)]);
+    }
+
+    #[test]
+    fn test_tree_disconnected() {
+        let mut g = Graph::directed();
+        g.add_edge("r", "a");
+        g.add_edge("x", "y");
+        g.add_edge("y", "z");
+        let id = |name| g.id(name).unwrap();
+        let tree = Tree::from_graph(&g, id("r"));
+
+        for name in &["x", "y", "z"] {
+            assert!(!tree.contains(id(name)));
+            assert_eq!(tree.parent(id(name)), None);
+            assert!(tree.children(id(name)).is_empty());
+            assert_eq!(tree.ancestors(id(name)).count(), 0);
+        }
+        assert_eq!(tree.parent(id("a")), Some(id("r")));
+        assert_eq!(tree.distance(id("a"), id("z")), None);
+    }
+}
@@ -0,0 +1,471 @@
+// Points, directions and dense 2D grids, shared by the puzzles working on a
+// plane. The y axis points down, like the lines of the text the grids are
+// parsed from.
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub fn new(x: i32, y: i32) -> Point {
+        Point { x, y }
+    }
+
+    pub fn manhattan_distance(self, other: Point) -> i32 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    // quarter turns around the origin
+    pub fn rotate_left(self) -> Point {
+        Point::new(self.y, -self.x)
+    }
+
+    pub fn rotate_right(self) -> Point {
+        Point::new(-self.y, self.x)
+    }
+
+    pub fn neighbours4(self) -> [Point; 4] {
+        let mut result = [self; 4];
+        for (n, dir) in result.iter_mut().zip(Direction::ALL.iter()) {
+            *n = self + dir.delta();
+        }
+        result
+    }
+
+    // the 4 orthogonal neighbours, then the 4 diagonal ones
+    pub fn neighbours8(self) -> [Point; 8] {
+        let n = self.neighbours4();
+        [
+            n[0],
+            n[1],
+            n[2],
+            n[3],
+            self + Point::new(1, -1),
+            self + Point::new(1, 1),
+            self + Point::new(-1, 1),
+            self + Point::new(-1, -1),
+        ]
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Point) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<i32> for Point {
+    type Output = Point;
+
+    fn mul(self, k: i32) -> Point {
+        Point::new(self.x * k, self.y * k)
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    // clockwise, starting from up
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    // accepts both ULDR and arrows
+    pub fn from_char(c: char) -> Option<Direction> {
+        match c {
+            'U' | '^' => Some(Direction::Up),
+            'R' | '>' => Some(Direction::Right),
+            'D' | 'v' => Some(Direction::Down),
+            'L' | '<' => Some(Direction::Left),
+            _ => None,
+        }
+    }
+
+    pub fn delta(self) -> Point {
+        match self {
+            Direction::Up => Point::new(0, -1),
+            Direction::Right => Point::new(1, 0),
+            Direction::Down => Point::new(0, 1),
+            Direction::Left => Point::new(-1, 0),
+        }
+    }
+
+    pub fn turn_right(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn turn_left(self) -> Direction {
+        self.turn_right().turn_right().turn_right()
+    }
+
+    pub fn opposite(self) -> Direction {
+        self.turn_right().turn_right()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    WrongSize {
+        expected: usize,
+        found: usize,
+    },
+    RaggedLine {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    InvalidChar {
+        line: usize,
+        column: usize,
+        c: char,
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridError::WrongSize { expected, found } => {
+                write!(f, "expected {} cells but got {}", expected, found)
+            }
+            GridError::RaggedLine {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {} has {} characters instead of {}",
+                line, found, expected
+            ),
+            GridError::InvalidChar { line, column, c } => {
+                write!(f, "invalid character {:?} at {}:{}", c, line, column)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+// a rectangle of cells stored line by line, with (0, 0) at the top left
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Result<Grid<T>, GridError> {
+        if cells.len() != width * height {
+            return Err(GridError::WrongSize {
+                expected: width * height,
+                found: cells.len(),
+            });
+        }
+        Ok(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    pub fn from_fn<F: FnMut(Point) -> T>(width: usize, height: usize, f: F) -> Grid<T> {
+        let cells = points(width, height).map(f).collect();
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    // one line per row, each character converted by `f`. Trailing empty
+    // lines are ignored, 1-based positions are reported on errors.
+    pub fn parse<F>(text: &str, f: F) -> Result<Grid<T>, GridError>
+    where
+        F: Fn(char) -> Option<T>,
+    {
+        let lines: Vec<&str> = text.trim_end_matches('\n').lines().collect();
+        let width = lines.first().map_or(0, |l| l.chars().count());
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for (i, line) in lines.iter().enumerate() {
+            let found = line.chars().count();
+            if found != width {
+                return Err(GridError::RaggedLine {
+                    line: i + 1,
+                    expected: width,
+                    found,
+                });
+            }
+            for (j, c) in line.chars().enumerate() {
+                cells.push(f(c).ok_or(GridError::InvalidChar {
+                    line: i + 1,
+                    column: j + 1,
+                    c,
+                })?);
+            }
+        }
+        Ok(Grid {
+            width,
+            height: lines.len(),
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        p.x >= 0 && p.y >= 0 && (p.x as usize) < self.width && (p.y as usize) < self.height
+    }
+
+    fn idx(&self, p: Point) -> Option<usize> {
+        if self.contains(p) {
+            Some(p.y as usize * self.width + p.x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.idx(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        match self.idx(p) {
+            Some(i) => Some(&mut self.cells[i]),
+            None => None,
+        }
+    }
+
+    // all the points of the grid, line by line
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        points(self.width, self.height)
+    }
+
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = (Point, &'a T)> + 'a {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn rows(&self) -> std::slice::Chunks<'_, T> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    // orthogonal neighbours inside the grid
+    pub fn neighbours4<'a>(&'a self, p: Point) -> impl Iterator<Item = Point> + 'a {
+        p.neighbours4()
+            .to_vec()
+            .into_iter()
+            .filter(move |n| self.contains(*n))
+    }
+
+    // orthogonal and diagonal neighbours inside the grid
+    pub fn neighbours8<'a>(&'a self, p: Point) -> impl Iterator<Item = Point> + 'a {
+        p.neighbours8()
+            .to_vec()
+            .into_iter()
+            .filter(move |n| self.contains(*n))
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    // the reverse of `parse`, every line ends with a newline
+    pub fn render<F: Fn(&T) -> char>(&self, f: F) -> String {
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows().take(self.height) {
+            out.extend(row.iter().map(&f));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        match self.get(p) {
+            Some(x) => x,
+            None => panic!(
+                "{} is outside of the {}x{} grid",
+                p, self.width, self.height
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        match self.get_mut(p) {
+            Some(x) => x,
+            None => panic!("{} is outside of the {}x{} grid", p, width, height),
+        }
+    }
+}
+
+fn points(width: usize, height: usize) -> impl Iterator<Item = Point> {
+    (0..height).flat_map(move |y| (0..width).map(move |x| Point::new(x as i32, y as i32)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_point_ops() {
+        let p = Point::new(3, -4);
+        assert_eq!(p + Point::new(1, 1), Point::new(4, -3));
+        assert_eq!(p - Point::new(1, 1), Point::new(2, -5));
+        assert_eq!(p * 2, Point::new(6, -8));
+        assert_eq!(p.manhattan_distance(Point::ORIGIN), 7);
+        assert_eq!(p.rotate_right().rotate_left(), p);
+        assert_eq!(
+            p.rotate_right()
+                .rotate_right()
+                .rotate_right()
+                .rotate_right(),
+            p
+        );
+        assert_eq!(
+            Direction::Up.delta().rotate_right(),
+            Direction::Right.delta()
+        );
+        assert_eq!(Direction::Up.delta().rotate_left(), Direction::Left.delta());
+    }
+
+    #[test]
+    fn test_direction_turns() {
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+        assert_eq!(Direction::Left.opposite(), Direction::Right);
+        assert_eq!(Direction::from_char('v'), Some(Direction::Down));
+        assert_eq!(Direction::from_char('x'), None);
+    }
+
+    #[test]
+    fn test_grid_parse_render() {
+        let text = "#..\n.#.\n..#\n#.#\n";
+        let grid = Grid::parse(text, |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 4));
+        assert!(grid[Point::new(1, 1)]);
+        assert_eq!(grid.get(Point::new(1, 0)), Some(&false));
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        assert_eq!(grid.get(Point::new(0, -1)), None);
+        assert_eq!(grid.render(|b| if *b { '#' } else { '.' }), text);
+        assert_eq!(grid.iter().filter(|(_, b)| **b).count(), 5);
+    }
+
+    #[test]
+    fn test_grid_parse_errors() {
+        let digit = |c: char| c.to_digit(10);
+        assert_eq!(
+            Grid::parse("123\n45\n", digit),
+            Err(GridError::RaggedLine {
+                line: 2,
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            Grid::parse("123\n4x6\n", digit),
+            Err(GridError::InvalidChar {
+                line: 2,
+                column: 2,
+                c: 'x'
+            })
+        );
+        assert_eq!(
+            Grid::from_vec(2, 2, vec![1, 2, 3]),
+            Err(GridError::WrongSize {
+                expected: 4,
+                found: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_grid_neighbours() {
+        let mut grid = Grid::new(3, 2, 0);
+        let corner: Vec<Point> = grid.neighbours4(Point::ORIGIN).collect();
+        assert_eq!(corner, vec![Point::new(1, 0), Point::new(0, 1)]);
+        assert_eq!(grid.neighbours8(Point::new(1, 0)).count(), 5);
+        assert_eq!(grid.neighbours8(Point::new(1, 1)).count(), 5);
+
+        grid[Point::new(2, 1)] = 7;
+        assert_eq!(grid.cells(), &[0, 0, 0, 0, 0, 7]);
+        let doubled = Grid::from_fn(3, 2, |p| p.x * 2);
+        assert_eq!(doubled.rows().nth(1), Some(&[0, 2, 4][..]));
+    }
+}
@@ -0,0 +1,20 @@
+// Puzzle inputs are read from data/<year>/dayNN.txt. Not all of them are
+// committed, so a missing one stops with a message instead of a panic.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::process;
+
+pub fn read_to_string(path: &str) -> String {
+    match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(ref err) if err.kind() == ErrorKind::NotFound => {
+            eprintln!("missing {}", path);
+            process::exit(1)
+        }
+        Err(err) => {
+            eprintln!("cannot read {}: {}", path, err);
+            process::exit(1)
+        }
+    }
+}
@@ -0,0 +1,10 @@
+// The parts of the puzzles that are also built on from outside the binary:
+// the intcode computer is fuzzed from fuzz/.
+
+#[macro_use]
+extern crate lazy_static;
+
+pub mod input;
+pub mod y2019 {
+    pub mod computer;
+}
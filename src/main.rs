@@ -3,10 +3,21 @@ extern crate lazy_static;
 
 #[macro_use]
 extern crate clap;
+extern crate aoc_rs;
 extern crate regex;
 
+use aoc_rs::input;
+use aoc_rs::y2019::computer::history;
 use clap::{App, Arg};
+use std::time::Duration;
 
+mod combinatorics;
+mod counter;
+mod cycle;
+mod graph;
+mod grid;
+mod ocr;
+mod search;
 mod y2018;
 mod y2019;
 
@@ -15,15 +26,231 @@ fn main() {
         .arg(Arg::with_name("year"))
         .arg(Arg::with_name("day"))
         .arg(Arg::with_name("pbNumber"))
+        .arg(
+            Arg::with_name("profile")
+                .long("profile")
+                .value_name("PROGRAM")
+                .help("Run an intcode program and report where its execution time goes"),
+        )
+        .arg(
+            Arg::with_name("input")
+                .long("input")
+                .value_name("INPUTS")
+                .use_delimiter(true)
+                .requires("profile")
+                .help("Comma separated inputs for the profiled program"),
+        )
+        .arg(
+            Arg::with_name("annotate")
+                .long("annotate")
+                .requires("profile")
+                .help("Show the disassembly of the hot addresses"),
+        )
+        .arg(
+            Arg::with_name("step-limit")
+                .long("step-limit")
+                .value_name("STEPS")
+                .requires("profile")
+                .help("Stop the profiled program after that many instructions"),
+        )
+        .arg(
+            Arg::with_name("timeout")
+                .long("timeout")
+                .value_name("SECONDS")
+                .requires("profile")
+                .help("Stop the profiled program after that many seconds"),
+        )
+        .arg(
+            Arg::with_name("history")
+                .long("history")
+                .value_name("STEPS")
+                .requires("profile")
+                .help("Number of steps of the profiled program kept for --goto and --watch"),
+        )
+        .arg(
+            Arg::with_name("goto")
+                .long("goto")
+                .value_name("STEP")
+                .requires("profile")
+                .help("Go back to that step once the profiled program has stopped"),
+        )
+        .arg(
+            Arg::with_name("watch")
+                .long("watch")
+                .value_name("ADDRESSES")
+                .use_delimiter(true)
+                .requires("profile")
+                .help("Comma separated addresses to show with the step which last wrote them"),
+        )
+        .arg(
+            Arg::with_name("fuel")
+                .long("fuel")
+                .value_name("MASS")
+                .help("Total fuel of 2019 day 1 for a single mass, including the fuel for the fuel"),
+        )
+        .arg(
+            Arg::with_name("equation")
+                .long("equation")
+                .value_name("DIVISOR/SUBTRACTION")
+                .requires("fuel")
+                .help("Rocket equation used by --fuel, 3/2 by default"),
+        )
+        .arg(
+            Arg::with_name("render")
+                .long("render")
+                .value_name("SVG_FILE")
+                .help("Draw the wires of 2019 day 3 to an svg file and on the terminal"),
+        )
+        .arg(
+            Arg::with_name("transfers")
+                .long("transfers")
+                .value_name("QUERY_FILE")
+                .help("Orbital transfers between pairs of bodies of 2019 day 6, or bodies within reach"),
+        )
+        .arg(
+            Arg::with_name("export")
+                .long("export")
+                .value_name("FORMAT")
+                .possible_values(&["dot", "json"])
+                .help("Print the orbit map of 2019 day 6"),
+        )
+        .arg(
+            Arg::with_name("highlight")
+                .long("highlight")
+                .requires("export")
+                .help("Highlight the path from YOU to SAN in the exported map"),
+        )
+        .arg(
+            Arg::with_name("image")
+                .long("image")
+                .value_name("FILE")
+                .help("Write the message of 2019 day 8 as a pbm, ppm or png file"),
+        )
+        .arg(
+            Arg::with_name("scale")
+                .long("scale")
+                .value_name("FACTOR")
+                .requires("image")
+                .help("Size of each pixel of the written image"),
+        )
+        .arg(
+            Arg::with_name("animate")
+                .long("animate")
+                .help("Show how the layers of 2019 day 8 are stacked, as an animated png or gif with --image"),
+        )
+        .arg(
+            Arg::with_name("size")
+                .long("size")
+                .value_name("WIDTHxHEIGHT")
+                .default_value("25x6")
+                .help("Size of the layers of the 2019 day 8 image"),
+        )
         .get_matches();
 
+    if let Some(program) = matches.value_of("profile") {
+        let inputs = values_t!(matches, "input", i64).unwrap_or_else(|err| match err.kind {
+            clap::ErrorKind::ArgumentNotFound => Vec::new(),
+            _ => err.exit(),
+        });
+        let step_limit = value_t!(matches, "step-limit", u64)
+            .map(Some)
+            .unwrap_or_else(|err| match err.kind {
+                clap::ErrorKind::ArgumentNotFound => None,
+                _ => err.exit(),
+            });
+        let timeout = value_t!(matches, "timeout", u64)
+            .map(|s| Some(Duration::from_secs(s)))
+            .unwrap_or_else(|err| match err.kind {
+                clap::ErrorKind::ArgumentNotFound => None,
+                _ => err.exit(),
+            });
+        let capacity = value_t!(matches, "history", usize)
+            .map(Some)
+            .unwrap_or_else(|err| match err.kind {
+                clap::ErrorKind::ArgumentNotFound => None,
+                _ => err.exit(),
+            });
+        let goto = value_t!(matches, "goto", u64)
+            .map(Some)
+            .unwrap_or_else(|err| match err.kind {
+                clap::ErrorKind::ArgumentNotFound => None,
+                _ => err.exit(),
+            });
+        let watched = values_t!(matches, "watch", usize).unwrap_or_else(|err| match err.kind {
+            clap::ErrorKind::ArgumentNotFound => Vec::new(),
+            _ => err.exit(),
+        });
+        let rewind = if capacity.is_some() || goto.is_some() || !watched.is_empty() {
+            Some(history::Rewind {
+                capacity: capacity.unwrap_or(history::DEFAULT_CAPACITY),
+                step: goto,
+                watched,
+            })
+        } else {
+            None
+        };
+        y2019::computer::profile::profile_program(
+            program,
+            inputs,
+            matches.is_present("annotate"),
+            step_limit,
+            timeout,
+            rewind,
+        );
+        return;
+    }
+
+    if let Some(mass) = matches.value_of("fuel") {
+        y2019::day01::fuel_for(mass, matches.value_of("equation").unwrap_or("3/2"));
+        return;
+    }
+
+    if let Some(svg_path) = matches.value_of("render") {
+        y2019::day03::render(svg_path);
+        return;
+    }
+
+    if let Some(query_path) = matches.value_of("transfers") {
+        y2019::day06::answer_queries(query_path);
+        return;
+    }
+
+    if let Some(format) = matches.value_of("export") {
+        y2019::day06::export(format, matches.is_present("highlight"));
+        return;
+    }
+
+    let size = matches.value_of("size").unwrap();
+    let (width, height) = y2019::day08::parse_size(size).unwrap_or_else(|| {
+        println!("invalid size: {}", size);
+        std::process::exit(1);
+    });
+
+    if let Some(path) = matches.value_of("image") {
+        let scale = value_t!(matches, "scale", usize).unwrap_or_else(|err| match err.kind {
+            clap::ErrorKind::ArgumentNotFound => 1,
+            _ => err.exit(),
+        });
+        if scale == 0 {
+            println!("the scale must be at least 1");
+            std::process::exit(1);
+        }
+        y2019::day08::write_image(path, scale, matches.is_present("animate"), width, height);
+        return;
+    }
+
+    if matches.is_present("animate") {
+        y2019::day08::animate(width, height);
+        return;
+    }
+
     let year = value_t_or_exit!(matches, "year", String);
     let day = value_t_or_exit!(matches, "day", u8);
     let pb_number = value_t_or_exit!(matches, "pbNumber", u8);
 
     match year.as_ref() {
         "2018" => run2018(day, pb_number),
-        "2019" => run2019(day, pb_number),
+        "2019" => run2019(day, pb_number, (width, height)),
         _ => {
             println!("Unknown year: {}", year);
             std::process::exit(1);
@@ -37,11 +264,57 @@ fn run2018(day: u8, pb_number: u8) {
         12 => y2018::day01::answer2(),
         21 => y2018::day02::answer1(),
         22 => y2018::day02::answer2(),
+        31 => y2018::day03::answer1(),
+        32 => y2018::day03::answer2(),
+        41 => y2018::day04::answer1(),
+        42 => y2018::day04::answer2(),
+        51 => y2018::day05::answer1(),
+        52 => y2018::day05::answer2(),
+        61 => y2018::day06::answer1(),
+        62 => y2018::day06::answer2(),
+        71 => y2018::day07::answer1(),
+        72 => y2018::day07::answer2(),
+        81 => y2018::day08::answer1(),
+        82 => y2018::day08::answer2(),
+        91 => y2018::day09::answer1(),
+        92 => y2018::day09::answer2(),
+        101 => y2018::day10::answer1(),
+        102 => y2018::day10::answer2(),
+        111 => y2018::day11::answer1(),
+        112 => y2018::day11::answer2(),
+        121 => y2018::day12::answer1(),
+        122 => y2018::day12::answer2(),
+        131 => y2018::day13::answer1(),
+        132 => y2018::day13::answer2(),
+        141 => y2018::day14::answer1(),
+        142 => y2018::day14::answer2(),
+        151 => y2018::day15::answer1(),
+        152 => y2018::day15::answer2(),
+        161 => y2018::day16::answer1(),
+        162 => y2018::day16::answer2(),
+        171 => y2018::day17::answer1(),
+        172 => y2018::day17::answer2(),
+        181 => y2018::day18::answer1(),
+        182 => y2018::day18::answer2(),
+        191 => y2018::day19::answer1(),
+        192 => y2018::day19::answer2(),
+        201 => y2018::day20::answer1(),
+        202 => y2018::day20::answer2(),
+        211 => y2018::day21::answer1(),
+        212 => y2018::day21::answer2(),
+        221 => y2018::day22::answer1(),
+        222 => y2018::day22::answer2(),
+        231 => y2018::day23::answer1(),
+        232 => y2018::day23::answer2(),
+        241 => y2018::day24::answer1(),
+        242 => y2018::day24::answer2(),
+        251 => y2018::day25::answer1(),
         _ => println!("Unknown pair day-pb number: {} - {}", day, pb_number),
     }
 }
 
-fn run2019(day: u8, pb_number: u8) {
+// `sif_size` is the size of the layers of the day 8 image
+fn run2019(day: u8, pb_number: u8, sif_size: (usize, usize)) {
     match day * 10 + pb_number {
         11 => y2019::day01::answer1(),
         12 => y2019::day01::answer2(),
@@ -57,10 +330,41 @@ fn run2019(day: u8, pb_number: u8) {
         62 => y2019::day06::answer2(),
         71 => y2019::day07::answer1(),
         72 => y2019::day07::answer2(),
-        81 => y2019::day08::answer1(),
-        82 => y2019::day08::answer2(),
+        81 => y2019::day08::answer1(sif_size.0, sif_size.1),
+        82 => y2019::day08::answer2(sif_size.0, sif_size.1),
         91 => y2019::day09::answer1(),
         92 => y2019::day09::answer2(),
+        101 => y2019::day10::answer1(),
+        102 => y2019::day10::answer2(),
+        111 => y2019::day11::answer1(),
+        112 => y2019::day11::answer2(),
+        121 => y2019::day12::answer1(),
+        122 => y2019::day12::answer2(),
+        131 => y2019::day13::answer1(),
+        132 => y2019::day13::answer2(),
+        141 => y2019::day14::answer1(),
+        142 => y2019::day14::answer2(),
+        151 => y2019::day15::answer1(),
+        152 => y2019::day15::answer2(),
+        161 => y2019::day16::answer1(),
+        162 => y2019::day16::answer2(),
+        171 => y2019::day17::answer1(),
+        172 => y2019::day17::answer2(),
+        181 => y2019::day18::answer1(),
+        182 => y2019::day18::answer2(),
+        191 => y2019::day19::answer1(),
+        192 => y2019::day19::answer2(),
+        201 => y2019::day20::answer1(),
+        202 => y2019::day20::answer2(),
+        211 => y2019::day21::answer1(),
+        212 => y2019::day21::answer2(),
+        221 => y2019::day22::answer1(),
+        222 => y2019::day22::answer2(),
+        231 => y2019::day23::answer1(),
+        232 => y2019::day23::answer2(),
+        241 => y2019::day24::answer1(),
+        242 => y2019::day24::answer2(),
+        251 => y2019::day25::answer1(),
         _ => println!("Unknown pair day-pb number: {} - {}", day, pb_number),
     }
 }
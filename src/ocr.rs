@@ -0,0 +1,300 @@
+// Reading the block letters some puzzles answer with.
+//
+// Two fonts are used across the years: small letters 6 pixels high, drawn
+// on a pitch of 5 columns (4 for the letter and a blank one, except for the
+// wider Y), and large letters 10 pixels high on a pitch of 8 columns. The
+// letters never seen in an answer are missing from both.
+
+use std::fmt;
+
+use grid::{Grid, Point};
+
+struct Font {
+    height: usize,
+    pitch: usize,
+    glyphs: &'static [(char, &'static str)],
+}
+
+const SMALL: Font = Font {
+    height: 6,
+    pitch: 5,
+    glyphs: &[
+        ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+        ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+        ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+        ('E', "####\n#...\n###.\n#...\n#...\n####"),
+        ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+        ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+        ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+        ('I', ".###\n..#.\n..#.\n..#.\n..#.\n.###"),
+        ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+        ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+        ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+        ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+        ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+        ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+        ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+        ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+        ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+        ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+    ],
+};
+
+const LARGE: Font = Font {
+    height: 10,
+    pitch: 8,
+    glyphs: &[
+        (
+            'A',
+            "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+        ),
+        (
+            'B',
+            "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+        ),
+        (
+            'C',
+            ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+        ),
+        (
+            'E',
+            "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+        ),
+        (
+            'F',
+            "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+        ),
+        (
+            'G',
+            ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+        ),
+        (
+            'H',
+            "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+        ),
+        (
+            'J',
+            "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+        ),
+        (
+            'K',
+            "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+        ),
+        (
+            'L',
+            "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+        ),
+        (
+            'N',
+            "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+        ),
+        (
+            'P',
+            "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+        ),
+        (
+            'R',
+            "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+        ),
+        (
+            'X',
+            "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+        ),
+        (
+            'Z',
+            "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+        ),
+    ],
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    // no font has letters this high
+    UnknownHeight { height: usize },
+    // the `index`th letter, drawn with `#` and `.`
+    UnknownGlyph { index: usize, glyph: String },
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OcrError::UnknownHeight { height } => {
+                write!(f, "no font has letters {} pixels high", height)
+            }
+            OcrError::UnknownGlyph { index, glyph } => {
+                write!(f, "cannot read letter {}:\n{}", index + 1, glyph)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+// the text drawn on the grid, `lit` telling which cells are part of the
+// letters. Blank lines and columns around the text are ignored.
+pub fn recognize<T, F>(grid: &Grid<T>, lit: F) -> Result<String, OcrError>
+where
+    F: Fn(&T) -> bool,
+{
+    // columns can be negative, when the blank column of the first letter was
+    // cropped
+    let is_lit = |x: i32, y: usize| grid.get(Point::new(x, y as i32)).is_some_and(&lit);
+    let width = grid.width() as i32;
+    let lit_rows: Vec<usize> = (0..grid.height())
+        .filter(|&y| (0..width).any(|x| is_lit(x, y)))
+        .collect();
+    let (top, height) = match (lit_rows.first(), lit_rows.last()) {
+        (Some(&top), Some(&bottom)) => (top, bottom - top + 1),
+        _ => return Ok(String::new()),
+    };
+    let fonts = [SMALL, LARGE];
+    let font = fonts
+        .iter()
+        .find(|f| f.height == height)
+        .ok_or(OcrError::UnknownHeight { height })?;
+
+    // some letters start with a blank column, like the small I: the text can
+    // start a few columns before the first lit one
+    let first = (0..width)
+        .find(|&x| lit_rows.iter().any(|&y| is_lit(x, y)))
+        .unwrap();
+    let error = match read_letters(&is_lit, font, top, first, width) {
+        Ok(text) => return Ok(text),
+        Err(err) => err,
+    };
+    (first + 1 - font.pitch as i32..first)
+        .rev()
+        .find_map(|left| read_letters(&is_lit, font, top, left, width).ok())
+        .ok_or(error)
+}
+
+// the letters drawn from column `left` up to `width`
+fn read_letters(
+    is_lit: &dyn Fn(i32, usize) -> bool,
+    font: &Font,
+    top: usize,
+    left: i32,
+    width: i32,
+) -> Result<String, OcrError> {
+    let mut text = String::new();
+    let letters = ((width - left) as usize).div_ceil(font.pitch);
+    for index in 0..letters {
+        let start = left + (index * font.pitch) as i32;
+        let glyph: Vec<String> = (top..top + font.height)
+            .map(|y| {
+                (start..start + font.pitch as i32)
+                    .map(|x| if is_lit(x, y) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        if glyph.iter().all(|row| !row.contains('#')) {
+            continue;
+        }
+        let found = font
+            .glyphs
+            .iter()
+            .find(|(_, pattern)| matches(pattern, &glyph))
+            .ok_or_else(|| OcrError::UnknownGlyph {
+                index,
+                glyph: glyph.join("\n"),
+            })?;
+        text.push(found.0);
+    }
+    Ok(text)
+}
+
+// the pattern can be narrower than the glyph, which is then blank on the right
+fn matches(pattern: &str, glyph: &[String]) -> bool {
+    pattern
+        .lines()
+        .zip(glyph)
+        .all(|(p, g)| g.starts_with(p) && !g[p.len()..].contains('#'))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // the reverse of recognize, to check that each letter can be read back
+    fn draw(font: &Font, text: &str) -> Grid<bool> {
+        let mut grid = Grid::new(text.len() * font.pitch, font.height, false);
+        for (i, c) in text.chars().enumerate() {
+            let pattern = font.glyphs.iter().find(|(g, _)| *g == c).unwrap().1;
+            for (y, row) in pattern.lines().enumerate() {
+                for (x, pixel) in row.chars().enumerate() {
+                    let p = Point::new((i * font.pitch + x) as i32, y as i32);
+                    grid[p] = pixel == '#';
+                }
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn test_ocr_all_letters() {
+        for font in &[SMALL, LARGE] {
+            let text: String = font.glyphs.iter().map(|(c, _)| *c).collect();
+            assert_eq!(recognize(&draw(font, &text), |b| *b), Ok(text));
+        }
+    }
+
+    #[test]
+    fn test_ocr_offset() {
+        for font in &[SMALL, LARGE] {
+            let text: String = font.glyphs.iter().map(|(c, _)| *c).collect();
+            let drawn = draw(font, &text);
+            for offset in 0..font.pitch + 2 {
+                let shifted = Grid::from_fn(drawn.width() + offset, drawn.height() + 1, |p| {
+                    let q = Point::new(p.x - offset as i32, p.y - 1);
+                    drawn.get(q).cloned().unwrap_or(false)
+                });
+                assert_eq!(recognize(&shifted, |b| *b), Ok(text.clone()));
+            }
+        }
+        // the blank column of the I is not part of the picture
+        let drawn = draw(&SMALL, "IF");
+        let cropped = Grid::from_fn(drawn.width() - 1, drawn.height(), |p| {
+            drawn[Point::new(p.x + 1, p.y)]
+        });
+        assert_eq!(recognize(&cropped, |b| *b), Ok("IF".to_string()));
+    }
+
+    #[test]
+    fn test_ocr_touching_letters() {
+        // the wide Y runs into the next letter
+        let text = "\
+###...##..#...##..#.###..
+#..#.#..#.#...##.#..#..#.
+#..#.#.....#.#.##...#..#.
+###..#......#..#.#..###..
+#.#..#..#...#..#.#..#.#..
+#..#..##....#..#..#.#..#.
+.........................
+";
+        let grid = Grid::parse(text, |c| Some(c == '#')).unwrap();
+        assert_eq!(recognize(&grid, |b| *b), Ok("RCYKR".to_string()));
+    }
+
+    #[test]
+    fn test_ocr_errors() {
+        let grid = Grid::parse("#..#\n####\n#..#\n", |c| Some(c == '#')).unwrap();
+        assert_eq!(
+            recognize(&grid, |b| *b),
+            Err(OcrError::UnknownHeight { height: 3 })
+        );
+
+        let mut grid = draw(&SMALL, "HI");
+        grid[Point::new(5, 0)] = true;
+        assert_eq!(
+            recognize(&grid, |b| *b),
+            Err(OcrError::UnknownGlyph {
+                index: 1,
+                glyph: "####.\n..#..\n..#..\n..#..\n..#..\n.###.".to_string()
+            })
+        );
+        assert_eq!(
+            recognize(&Grid::new(3, 3, false), |b| *b),
+            Ok(String::new())
+        );
+    }
+}
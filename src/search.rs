@@ -0,0 +1,103 @@
+// Exhaustive searches spread over all the cores: the candidates are handed
+// out one at a time to a few worker threads, which score them and keep their
+// best one. The scoring function can borrow from the caller, so puzzles which
+// try every input on an Intcode program just share the program.
+
+use std::sync::Mutex;
+use std::thread;
+
+// the candidate with the highest score and its score, the first one in
+// candidate order on ties
+pub fn max_by_key<I, F, S>(candidates: I, score: F) -> Option<(I::Item, S)>
+where
+    I: Iterator + Send,
+    I::Item: Send,
+    F: Fn(&I::Item) -> S + Sync,
+    S: Ord + Send,
+{
+    max_by_key_on(default_threads(), candidates, score)
+}
+
+// same as `max_by_key`, with a given number of worker threads
+pub fn max_by_key_on<I, F, S>(threads: usize, candidates: I, score: F) -> Option<(I::Item, S)>
+where
+    I: Iterator + Send,
+    I::Item: Send,
+    F: Fn(&I::Item) -> S + Sync,
+    S: Ord + Send,
+{
+    let queue = Mutex::new(candidates.enumerate());
+    let (queue, score) = (&queue, &score);
+    thread::scope(|s| {
+        let workers: Vec<_> = (0..threads.max(1))
+            .map(|_| {
+                s.spawn(move || {
+                    let mut best = None;
+                    loop {
+                        // don't hold the lock while scoring
+                        let next = queue.lock().unwrap().next();
+                        let (index, candidate) = match next {
+                            Some(next) => next,
+                            None => return best,
+                        };
+                        let s = score(&candidate);
+                        best = better(best, (index, candidate, s));
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .filter_map(|w| w.join().unwrap())
+            .fold(None, better)
+    })
+    .map(|(_, candidate, score)| (candidate, score))
+}
+
+fn default_threads() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+// candidates come with their position, to break ties the same way whatever
+// the scheduling of the workers
+fn better<C, S: Ord>(best: Option<(usize, C, S)>, other: (usize, C, S)) -> Option<(usize, C, S)> {
+    match best {
+        Some(b) if b.2 > other.2 || (b.2 == other.2 && b.0 < other.0) => Some(b),
+        _ => Some(other),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_search_matches_serial() {
+        let score = |&x: &u64| (x * 7919) % 1013;
+        for threads in 1..6 {
+            let (best, s) = max_by_key_on(threads, 0..1000u64, score).unwrap();
+            assert_eq!(s, (0..1000).map(|x| score(&x)).max().unwrap());
+            assert_eq!(score(&best), s);
+        }
+    }
+
+    #[test]
+    fn test_search_ties() {
+        // every candidate has the same score: always the first one
+        for threads in 1..6 {
+            assert_eq!(max_by_key_on(threads, 10..100, |_| 0), Some((10, 0)));
+        }
+        assert_eq!(
+            max_by_key(vec!["a", "bb", "c", "dd"].into_iter(), |s| s.len()),
+            Some(("bb", 2))
+        );
+    }
+
+    #[test]
+    fn test_search_empty() {
+        assert_eq!(max_by_key(0..0, |&x| x), None);
+        assert_eq!(max_by_key_on(8, 0..1, |&x| x), Some((0, 0)));
+    }
+}
@@ -1,33 +1,26 @@
-use std::collections::HashSet;
+use cycle;
 use std::fs::File;
 use std::io::BufRead;
 use std::io::BufReader;
 
 pub fn answer1() {
     let freqs = get_frequencies();
-    let res: i32 = freqs.iter().sum();
+    let res: i64 = freqs.iter().sum();
     println!("{}", res);
 }
 
 pub fn answer2() {
     let freqs = get_frequencies();
-    let mut seen: HashSet<i32> = HashSet::new();
-    let mut current: i32 = 0;
-
-    for f in freqs.iter().cycle() {
-        if seen.contains(&current) {
-            println!("{}", current);
-            return;
-        }
-        seen.insert(current);
-        current += f;
+    match cycle::first_repeated_sum(&freqs) {
+        Ok(repeat) => println!("{}", repeat.value),
+        Err(err) => println!("{}", err),
     }
 }
 
-fn get_frequencies() -> Vec<i32> {
+fn get_frequencies() -> Vec<i64> {
     let f = File::open("data/2018/day01.txt").unwrap();
     let fd = BufReader::new(&f);
     fd.lines()
-        .map(|x| i32::from_str_radix(&x.unwrap(), 10).unwrap())
+        .map(|x| x.unwrap().parse::<i64>().unwrap())
         .collect()
 }
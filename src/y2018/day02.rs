@@ -1,47 +1,27 @@
+use combinatorics;
+use counter::Counter;
 use std::collections::HashMap;
 use std::fs::File;
-use std::hash::Hash;
 use std::io::BufRead;
 use std::io::BufReader;
 
-pub fn answer1() {
-    let ids = get_ids();
-    let counts = ids.iter().map(|l| {
-        let mut m = HashMap::new();
-        for c in l.as_str().chars() {
-            m.entry(c).and_modify(|x| *x += 1).or_insert(1);
-        }
-        m
-    });
-
-    let mut has_3 = 0;
-    let mut has_2 = 0;
-    for id in counts {
-        if has_value(&id, 3) {
-            has_3 += 1;
-        }
-        if has_value(&id, 2) {
-            has_2 += 1;
-        }
-    }
+// two IDs which only differ at a few positions
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Match {
+    // indices in the list, the lowest one first
+    pub ids: (usize, usize),
+    // the letters they have in common
+    pub common: String,
+}
 
-    println!("{}", has_3 * has_2);
+pub fn answer1() {
+    println!("{}", checksum(&get_ids(), &[2, 3]));
 }
 
 pub fn answer2() {
-    let ids = get_ids();
-
-    for (i, x) in ids.iter().enumerate() {
-        let l = x.len();
-        for y in ids.iter().skip(i + 1) {
-            let common = common_letters(x, y);
-            if common.len() == l - 1 {
-                println!("{}", common);
-                return;
-            }
-        }
+    for m in near_duplicates(&get_ids(), 1) {
+        println!("{}", m.common);
     }
-    unreachable!();
 }
 
 fn get_ids() -> Vec<String> {
@@ -50,14 +30,135 @@ fn get_ids() -> Vec<String> {
     fd.lines().map(|x| x.unwrap()).collect()
 }
 
-fn has_value<K: Eq + Hash, V: Eq>(m: &HashMap<K, V>, x: V) -> bool {
-    m.values().find(|v| *v == &x).is_some()
+// the product, for each `k`, of the number of IDs with some letter
+// appearing exactly `k` times
+fn checksum(ids: &[String], ks: &[usize]) -> usize {
+    // each ID counts once for every number of repeats of its letters
+    let mut ids_with: Counter<usize> = Counter::new();
+    for id in ids {
+        let counts = id.chars().collect::<Counter<char>>().counts_of_counts();
+        ids_with.merge(counts.iter().map(|(k, _)| *k).collect());
+    }
+    ks.iter().map(|k| ids_with.get(k)).product()
+}
+
+// Every pair of IDs of the same length differing at exactly `k` positions.
+//
+// For each way of picking `k` positions, IDs are put in buckets by what's
+// left once these positions are removed: two IDs differing at exactly these
+// positions end up in the same bucket, and no other pair of buckets needs to
+// be compared. Each match shows up in a single bucket, the one for the
+// positions where they differ.
+fn near_duplicates(ids: &[String], k: usize) -> Vec<Match> {
+    let ids: Vec<Vec<char>> = ids.iter().map(|id| id.chars().collect()).collect();
+    let longest = ids.iter().map(|id| id.len()).max().unwrap_or(0);
+    let all_positions: Vec<usize> = (0..longest).collect();
+
+    let mut result = Vec::new();
+    for positions in combinatorics::combinations(&all_positions, k) {
+        let mut buckets: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, id) in ids.iter().enumerate() {
+            if positions.last().is_some_and(|&p| p >= id.len()) {
+                continue;
+            }
+            let rest = id
+                .iter()
+                .enumerate()
+                .filter(|(p, _)| !positions.contains(p))
+                .map(|(_, c)| c)
+                .collect();
+            buckets.entry(rest).or_default().push(i);
+        }
+
+        for (common, bucket) in buckets {
+            for (n, &a) in bucket.iter().enumerate() {
+                for &b in &bucket[n + 1..] {
+                    if positions.iter().all(|&p| ids[a][p] != ids[b][p]) {
+                        result.push(Match {
+                            ids: (a, b),
+                            common: common.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    result.sort();
+    result
 }
 
-fn common_letters(x: &String, y: &String) -> String {
-    x.chars()
-        .zip(y.chars())
-        .filter(|(c1, c2)| c1 == c2)
-        .map(|x| x.0)
-        .collect()
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ids(text: &str) -> Vec<String> {
+        text.split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_checksum() {
+        // the example from the puzzle
+        let example = ids("abcdef bababc abbcde abcccd aabcdd abcdee ababab");
+        assert_eq!(checksum(&example, &[2, 3]), 4 * 3);
+        assert_eq!(checksum(&example, &[1]), 6);
+        assert_eq!(checksum(&example, &[3, 4]), 0);
+        assert_eq!(checksum(&example, &[]), 1);
+    }
+
+    #[test]
+    fn test_near_duplicates() {
+        // the example from the puzzle
+        let example = ids("abcde fghij klmno pqrst fguij axcye wvxyz");
+        assert_eq!(
+            near_duplicates(&example, 1),
+            vec![Match {
+                ids: (1, 4),
+                common: "fgij".to_string()
+            }]
+        );
+        assert_eq!(
+            near_duplicates(&example, 2),
+            vec![Match {
+                ids: (0, 5),
+                common: "ace".to_string()
+            }]
+        );
+        assert_eq!(near_duplicates(&example, 0), vec![]);
+
+        let others = ids("abc abd abc xbd ab abcd");
+        let pairs: Vec<(usize, usize)> =
+            near_duplicates(&others, 1).iter().map(|m| m.ids).collect();
+        assert_eq!(pairs, vec![(0, 1), (1, 2), (1, 3)]);
+        let pairs: Vec<(usize, usize)> =
+            near_duplicates(&others, 0).iter().map(|m| m.ids).collect();
+        assert_eq!(pairs, vec![(0, 2)]);
+    }
+
+    #[test]
+    fn test_near_duplicates_matches_brute_force() {
+        let ids = get_ids();
+        for k in 1..3 {
+            let mut expected = Vec::new();
+            for (a, x) in ids.iter().enumerate() {
+                for (b, y) in ids.iter().enumerate().skip(a + 1) {
+                    let common = common_letters(x, y);
+                    if x.len() == y.len() && common.len() == x.len() - k {
+                        expected.push(Match {
+                            ids: (a, b),
+                            common,
+                        });
+                    }
+                }
+            }
+            assert_eq!(near_duplicates(&ids, k), expected);
+        }
+    }
+
+    fn common_letters(x: &str, y: &str) -> String {
+        x.chars()
+            .zip(y.chars())
+            .filter(|(c1, c2)| c1 == c2)
+            .map(|x| x.0)
+            .collect()
+    }
 }
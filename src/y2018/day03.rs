@@ -0,0 +1,107 @@
+use grid::{Grid, Point};
+use input;
+use regex::Regex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Claim {
+    id: usize,
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Claim {
+    fn points(&self) -> impl Iterator<Item = Point> {
+        let (left, top, width, height) = (self.left, self.top, self.width, self.height);
+        (top..top + height)
+            .flat_map(move |y| (left..left + width).map(move |x| Point::new(x as i32, y as i32)))
+    }
+}
+
+pub fn answer1() {
+    let claims = read_claims();
+    println!("{}", overlapping(&claims));
+}
+
+pub fn answer2() {
+    let claims = read_claims();
+    match intact(&claims) {
+        Some(id) => println!("{}", id),
+        None => println!("every claim overlaps another one"),
+    }
+}
+
+fn read_claims() -> Vec<Claim> {
+    parse_claims(&input::read_to_string("data/2018/day03.txt"))
+}
+
+fn parse_claims(text: &str) -> Vec<Claim> {
+    lazy_static! {
+        static ref CLAIM: Regex = Regex::new(r"^#(\d+) @ (\d+),(\d+): (\d+)x(\d+)$").unwrap();
+    }
+    text.lines()
+        .map(|line| {
+            let caps = CLAIM
+                .captures(line.trim())
+                .unwrap_or_else(|| panic!("invalid claim {:?}", line));
+            let n = |i: usize| caps[i].parse::<usize>().unwrap();
+            Claim {
+                id: n(1),
+                left: n(2),
+                top: n(3),
+                width: n(4),
+                height: n(5),
+            }
+        })
+        .collect()
+}
+
+// number of claims covering each square inch
+fn fabric(claims: &[Claim]) -> Grid<usize> {
+    let width = claims.iter().map(|c| c.left + c.width).max().unwrap_or(0);
+    let height = claims.iter().map(|c| c.top + c.height).max().unwrap_or(0);
+    let mut fabric = Grid::new(width, height, 0);
+    for claim in claims {
+        for p in claim.points() {
+            fabric[p] += 1;
+        }
+    }
+    fabric
+}
+
+fn overlapping(claims: &[Claim]) -> usize {
+    fabric(claims).cells().iter().filter(|&&n| n > 1).count()
+}
+
+fn intact(claims: &[Claim]) -> Option<usize> {
+    let fabric = fabric(claims);
+    claims
+        .iter()
+        .find(|c| c.points().all(|p| fabric[p] == 1))
+        .map(|c| c.id)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n#3 @ 5,5: 2x2\n";
+
+    #[test]
+    fn test_claims() {
+        let claims = parse_claims(EXAMPLE);
+        assert_eq!(
+            claims[1],
+            Claim {
+                id: 2,
+                left: 3,
+                top: 1,
+                width: 4,
+                height: 4
+            }
+        );
+        assert_eq!(overlapping(&claims), 4);
+        assert_eq!(intact(&claims), Some(3));
+    }
+}
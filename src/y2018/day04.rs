@@ -0,0 +1,116 @@
+use counter::Counter;
+use input;
+use regex::Regex;
+use std::collections::HashMap;
+
+// the minutes of the midnight hour each guard was asleep, one entry per
+// minute and per night
+type Naps = HashMap<u32, Counter<u32>>;
+
+pub fn answer1() {
+    let naps = read_naps();
+    let (guard, minute) = strategy1(&naps);
+    println!("{}", guard * minute);
+}
+
+pub fn answer2() {
+    let naps = read_naps();
+    let (guard, minute) = strategy2(&naps);
+    println!("{}", guard * minute);
+}
+
+fn read_naps() -> Naps {
+    parse_naps(&input::read_to_string("data/2018/day04.txt"))
+}
+
+// the records can be in any order, but their timestamps sort as text
+fn parse_naps(text: &str) -> Naps {
+    lazy_static! {
+        static ref RECORD: Regex = Regex::new(
+            r"^\[\d+-\d+-\d+ \d+:(\d+)\] (?:Guard #(\d+) begins shift|(falls asleep)|(wakes up))$"
+        )
+        .unwrap();
+    }
+    let mut lines: Vec<&str> = text
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .collect();
+    lines.sort();
+
+    let mut naps = Naps::new();
+    let mut guard = None;
+    let mut asleep = None;
+    for line in lines {
+        let caps = RECORD
+            .captures(line)
+            .unwrap_or_else(|| panic!("invalid record {:?}", line));
+        let minute: u32 = caps[1].parse().unwrap();
+        if let Some(id) = caps.get(2) {
+            guard = Some(id.as_str().parse().unwrap());
+        } else if caps.get(3).is_some() {
+            asleep = Some(minute);
+        } else {
+            let start = asleep.take().expect("woke up without falling asleep");
+            let id = guard.expect("no guard on duty");
+            naps.entry(id).or_default().extend(start..minute);
+        }
+    }
+    naps
+}
+
+// the guard who sleeps the most, and the minute they're most often asleep
+fn strategy1(naps: &Naps) -> (u32, u32) {
+    let (guard, minutes) = naps
+        .iter()
+        .max_by_key(|(id, minutes)| (minutes.total(), *id))
+        .unwrap();
+    (*guard, minutes.top(1)[0].0)
+}
+
+// the guard most frequently asleep on the same minute, and that minute
+fn strategy2(naps: &Naps) -> (u32, u32) {
+    naps.iter()
+        .filter_map(|(id, minutes)| minutes.top(1).first().map(|&(m, n)| (n, *id, m)))
+        .max()
+        .map(|(_, id, m)| (id, m))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:25] wakes up
+[1518-11-01 00:30] falls asleep
+[1518-11-01 00:55] wakes up
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-02 00:50] wakes up
+[1518-11-03 00:05] Guard #10 begins shift
+[1518-11-03 00:24] falls asleep
+[1518-11-03 00:29] wakes up
+[1518-11-04 00:02] Guard #99 begins shift
+[1518-11-04 00:36] falls asleep
+[1518-11-04 00:46] wakes up
+[1518-11-05 00:03] Guard #99 begins shift
+[1518-11-05 00:45] falls asleep
+[1518-11-05 00:55] wakes up
+";
+
+    #[test]
+    fn test_strategies() {
+        let naps = parse_naps(EXAMPLE);
+        assert_eq!(naps[&10].total(), 50);
+        assert_eq!(strategy1(&naps), (10, 24));
+        assert_eq!(strategy2(&naps), (99, 45));
+
+        // the order of the records doesn't matter
+        let mut shuffled: Vec<&str> = EXAMPLE.lines().collect();
+        shuffled.reverse();
+        assert_eq!(parse_naps(&shuffled.join("\n")), naps);
+    }
+}
@@ -0,0 +1,54 @@
+use input;
+
+pub fn answer1() {
+    println!("{}", react(read_polymer().bytes()));
+}
+
+pub fn answer2() {
+    println!("{}", shortest_without_one_type(&read_polymer()));
+}
+
+fn read_polymer() -> String {
+    input::read_to_string("data/2018/day05.txt")
+        .trim()
+        .to_string()
+}
+
+// length of the polymer once all the units of the same type and opposite
+// polarity next to each other have destroyed each other. A stack keeps what
+// has survived so far, each new unit can only react with its top.
+fn react<I: Iterator<Item = u8>>(units: I) -> usize {
+    let mut stack: Vec<u8> = Vec::new();
+    for u in units {
+        match stack.last() {
+            Some(&top) if top != u && top.eq_ignore_ascii_case(&u) => {
+                stack.pop();
+            }
+            _ => stack.push(u),
+        }
+    }
+    stack.len()
+}
+
+fn shortest_without_one_type(polymer: &str) -> usize {
+    (b'a'..=b'z')
+        .map(|t| react(polymer.bytes().filter(|u| u.to_ascii_lowercase() != t)))
+        .min()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_react() {
+        // the examples from the puzzle
+        assert_eq!(react("aA".bytes()), 0);
+        assert_eq!(react("abBA".bytes()), 0);
+        assert_eq!(react("abAB".bytes()), 4);
+        assert_eq!(react("aabAAB".bytes()), 6);
+        assert_eq!(react("dabAcCaCBAcCcaDA".bytes()), 10);
+        assert_eq!(shortest_without_one_type("dabAcCaCBAcCcaDA"), 4);
+    }
+}
@@ -0,0 +1,115 @@
+use grid::Point;
+use input;
+use std::collections::HashSet;
+
+const MAX_TOTAL_DISTANCE: i32 = 10000;
+
+pub fn answer1() {
+    println!("{}", largest_finite_area(&read_points()));
+}
+
+pub fn answer2() {
+    println!("{}", safe_region(&read_points(), MAX_TOTAL_DISTANCE));
+}
+
+fn read_points() -> Vec<Point> {
+    parse_points(&input::read_to_string("data/2018/day06.txt"))
+}
+
+fn parse_points(text: &str) -> Vec<Point> {
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| {
+            let mut xy = l.split(',').map(|n| n.trim().parse().unwrap());
+            Point::new(xy.next().unwrap(), xy.next().unwrap())
+        })
+        .collect()
+}
+
+// smallest and largest corners of the box around the points
+fn bounds(points: &[Point]) -> (Point, Point) {
+    let min = Point::new(
+        points.iter().map(|p| p.x).min().unwrap(),
+        points.iter().map(|p| p.y).min().unwrap(),
+    );
+    let max = Point::new(
+        points.iter().map(|p| p.x).max().unwrap(),
+        points.iter().map(|p| p.y).max().unwrap(),
+    );
+    (min, max)
+}
+
+// index of the only closest point, None on ties
+fn closest(points: &[Point], p: Point) -> Option<usize> {
+    let mut distances: Vec<(i32, usize)> = points
+        .iter()
+        .enumerate()
+        .map(|(i, q)| (q.manhattan_distance(p), i))
+        .collect();
+    distances.sort();
+    match distances.as_slice() {
+        [(d1, i), (d2, _), ..] if d1 < d2 => Some(*i),
+        [(_, i)] => Some(*i),
+        _ => None,
+    }
+}
+
+// Areas touching the border of the bounding box go on forever: past the
+// border, getting further away gets every point further away too.
+fn largest_finite_area(points: &[Point]) -> usize {
+    let (min, max) = bounds(points);
+    let mut areas = vec![0; points.len()];
+    let mut infinite = HashSet::new();
+    for y in min.y..=max.y {
+        for x in min.x..=max.x {
+            if let Some(i) = closest(points, Point::new(x, y)) {
+                areas[i] += 1;
+                if x == min.x || x == max.x || y == min.y || y == max.y {
+                    infinite.insert(i);
+                }
+            }
+        }
+    }
+    areas
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !infinite.contains(i))
+        .map(|(_, a)| *a)
+        .max()
+        .unwrap_or(0)
+}
+
+// number of locations with a total distance to all the points under the
+// limit. They can be outside of the bounding box, by at most the limit
+// divided by the number of points.
+fn safe_region(points: &[Point], limit: i32) -> usize {
+    let (min, max) = bounds(points);
+    let margin = limit / points.len() as i32 + 1;
+    let mut count = 0;
+    for y in min.y - margin..=max.y + margin {
+        for x in min.x - margin..=max.x + margin {
+            let p = Point::new(x, y);
+            let total: i32 = points.iter().map(|q| q.manhattan_distance(p)).sum();
+            if total < limit {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "1, 1\n1, 6\n8, 3\n3, 4\n5, 5\n8, 9\n";
+
+    #[test]
+    fn test_areas() {
+        let points = parse_points(EXAMPLE);
+        assert_eq!(closest(&points, Point::new(0, 4)), None);
+        assert_eq!(closest(&points, Point::new(0, 0)), Some(0));
+        assert_eq!(largest_finite_area(&points), 17);
+        assert_eq!(safe_region(&points, 32), 16);
+    }
+}
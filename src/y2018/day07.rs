@@ -0,0 +1,104 @@
+use input;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+
+// the steps each step needs to be finished before it can begin
+type Requirements = BTreeMap<char, BTreeSet<char>>;
+
+const WORKERS: usize = 5;
+const BASE_DURATION: u32 = 60;
+
+pub fn answer1() {
+    println!("{}", order(&read_requirements()));
+}
+
+pub fn answer2() {
+    println!(
+        "{}",
+        assemble(&read_requirements(), WORKERS, BASE_DURATION).1
+    );
+}
+
+fn read_requirements() -> Requirements {
+    parse_requirements(&input::read_to_string("data/2018/day07.txt"))
+}
+
+fn parse_requirements(text: &str) -> Requirements {
+    lazy_static! {
+        static ref RULE: Regex =
+            Regex::new(r"^Step (\w) must be finished before step (\w) can begin\.$").unwrap();
+    }
+    let mut requirements = Requirements::new();
+    for line in text.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+        let caps = RULE
+            .captures(line)
+            .unwrap_or_else(|| panic!("invalid instruction {:?}", line));
+        let before = caps[1].chars().next().unwrap();
+        let after = caps[2].chars().next().unwrap();
+        requirements.entry(before).or_default();
+        requirements.entry(after).or_default().insert(before);
+    }
+    requirements
+}
+
+// alone, the steps are done one at a time
+fn order(requirements: &Requirements) -> String {
+    assemble(requirements, 1, 0).0
+}
+
+// Steps are started in alphabetical order as soon as they're available and
+// someone is free. Step A takes `base + 1` seconds, B `base + 2` and so on.
+// Returns the order in which the steps are finished, and the total time.
+fn assemble(requirements: &Requirements, workers: usize, base: u32) -> (String, u32) {
+    let mut started = BTreeSet::new();
+    let mut done = BTreeSet::new();
+    let mut finished = String::new();
+    // (end time, step) of the steps in progress
+    let mut in_progress: BTreeSet<(u32, char)> = BTreeSet::new();
+    let mut time = 0;
+    while done.len() < requirements.len() {
+        let available: Vec<char> = requirements
+            .iter()
+            .filter(|(step, before)| !started.contains(*step) && before.is_subset(&done))
+            .map(|(step, _)| *step)
+            .collect();
+        for step in available.into_iter().take(workers - in_progress.len()) {
+            started.insert(step);
+            in_progress.insert((time + base + (step as u32 - 'A' as u32 + 1), step));
+        }
+
+        let &(end, _) = in_progress.iter().next().expect("circular requirements");
+        time = end;
+        while let Some(&(end, step)) = in_progress.iter().next() {
+            if end != time {
+                break;
+            }
+            in_progress.remove(&(end, step));
+            done.insert(step);
+            finished.push(step);
+        }
+    }
+    (finished, time)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Step C must be finished before step A can begin.
+Step C must be finished before step F can begin.
+Step A must be finished before step B can begin.
+Step A must be finished before step D can begin.
+Step B must be finished before step E can begin.
+Step D must be finished before step E can begin.
+Step F must be finished before step E can begin.
+";
+
+    #[test]
+    fn test_assemble() {
+        let requirements = parse_requirements(EXAMPLE);
+        assert_eq!(order(&requirements), "CABDFE");
+        assert_eq!(assemble(&requirements, 2, 0), ("CABFDE".to_string(), 15));
+    }
+}
@@ -0,0 +1,73 @@
+use input;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Node {
+    children: Vec<Node>,
+    metadata: Vec<usize>,
+}
+
+impl Node {
+    fn metadata_sum(&self) -> usize {
+        self.metadata.iter().sum::<usize>()
+            + self
+                .children
+                .iter()
+                .map(|c| c.metadata_sum())
+                .sum::<usize>()
+    }
+
+    // without children, the sum of the metadata, otherwise the sum of the
+    // values of the children the metadata points to, starting from 1
+    fn value(&self) -> usize {
+        if self.children.is_empty() {
+            return self.metadata.iter().sum();
+        }
+        self.metadata
+            .iter()
+            .filter_map(|&i| i.checked_sub(1).and_then(|i| self.children.get(i)))
+            .map(|c| c.value())
+            .sum()
+    }
+}
+
+pub fn answer1() {
+    println!("{}", read_tree().metadata_sum());
+}
+
+pub fn answer2() {
+    println!("{}", read_tree().value());
+}
+
+fn read_tree() -> Node {
+    parse_tree(&input::read_to_string("data/2018/day08.txt"))
+}
+
+fn parse_tree(text: &str) -> Node {
+    let mut numbers = text.split_whitespace().map(|n| n.parse().unwrap());
+    parse_node(&mut numbers)
+}
+
+// a header with the number of children and of metadata entries, then the
+// children, then the metadata
+fn parse_node<I: Iterator<Item = usize>>(numbers: &mut I) -> Node {
+    let children = numbers.next().expect("missing child count");
+    let metadata = numbers.next().expect("missing metadata count");
+    Node {
+        children: (0..children).map(|_| parse_node(numbers)).collect(),
+        metadata: numbers.take(metadata).collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tree() {
+        // the example from the puzzle
+        let tree = parse_tree("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2");
+        assert_eq!(tree.children.len(), 2);
+        assert_eq!(tree.metadata_sum(), 138);
+        assert_eq!(tree.value(), 66);
+    }
+}
@@ -0,0 +1,68 @@
+use input;
+use regex::Regex;
+use std::collections::VecDeque;
+
+pub fn answer1() {
+    let (players, last) = read_game();
+    println!("{}", high_score(players, last));
+}
+
+pub fn answer2() {
+    let (players, last) = read_game();
+    println!("{}", high_score(players, last * 100));
+}
+
+fn read_game() -> (usize, u64) {
+    parse_game(&input::read_to_string("data/2018/day09.txt"))
+}
+
+fn parse_game(text: &str) -> (usize, u64) {
+    lazy_static! {
+        static ref GAME: Regex =
+            Regex::new(r"(\d+) players; last marble is worth (\d+) points").unwrap();
+    }
+    let caps = GAME
+        .captures(text)
+        .unwrap_or_else(|| panic!("invalid game {:?}", text));
+    (caps[1].parse().unwrap(), caps[2].parse().unwrap())
+}
+
+// The circle is a deque with the current marble at the back: moving around
+// the circle is rotating the deque, which is cheap for a few positions.
+fn high_score(players: usize, last: u64) -> u64 {
+    let mut scores = vec![0; players];
+    let mut circle = VecDeque::with_capacity(last as usize + 1);
+    circle.push_back(0);
+    for marble in 1..=last {
+        if marble.is_multiple_of(23) {
+            circle.rotate_right(7);
+            let removed = circle.pop_back().unwrap();
+            scores[(marble as usize - 1) % players] += marble + removed;
+            circle.rotate_left(1);
+        } else {
+            circle.rotate_left(1);
+            circle.push_back(marble);
+        }
+    }
+    scores.into_iter().max().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_high_score() {
+        // the examples from the puzzle
+        assert_eq!(high_score(9, 25), 32);
+        assert_eq!(high_score(10, 1618), 8317);
+        assert_eq!(high_score(13, 7999), 146373);
+        assert_eq!(high_score(17, 1104), 2764);
+        assert_eq!(high_score(21, 6111), 54718);
+        assert_eq!(high_score(30, 5807), 37305);
+        assert_eq!(
+            parse_game("10 players; last marble is worth 1618 points\n"),
+            (10, 1618)
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use grid::{Grid, Point};
+use input;
+use ocr;
+use regex::Regex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Star {
+    position: Point,
+    velocity: Point,
+}
+
+pub fn answer1() {
+    let (sky, _) = message(&read_stars());
+    match ocr::recognize(&sky, |lit| *lit) {
+        Ok(text) => println!("{}", text),
+        Err(err) => {
+            print!("{}", sky.render(|&lit| if lit { '#' } else { '.' }));
+            println!("{}", err);
+        }
+    }
+}
+
+pub fn answer2() {
+    println!("{}", message(&read_stars()).1);
+}
+
+fn read_stars() -> Vec<Star> {
+    parse_stars(&input::read_to_string("data/2018/day10.txt"))
+}
+
+fn parse_stars(text: &str) -> Vec<Star> {
+    lazy_static! {
+        static ref STAR: Regex =
+            Regex::new(r"^position=<\s*(-?\d+),\s*(-?\d+)> velocity=<\s*(-?\d+),\s*(-?\d+)>$")
+                .unwrap();
+    }
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|line| {
+            let caps = STAR
+                .captures(line.trim())
+                .unwrap_or_else(|| panic!("invalid star {:?}", line));
+            let n = |i: usize| caps[i].parse::<i32>().unwrap();
+            Star {
+                position: Point::new(n(1), n(2)),
+                velocity: Point::new(n(3), n(4)),
+            }
+        })
+        .collect()
+}
+
+fn positions(stars: &[Star], time: i32) -> Vec<Point> {
+    stars
+        .iter()
+        .map(|s| s.position + s.velocity * time)
+        .collect()
+}
+
+fn height(points: &[Point]) -> i32 {
+    let min = points.iter().map(|p| p.y).min().unwrap_or(0);
+    let max = points.iter().map(|p| p.y).max().unwrap_or(0);
+    max - min + 1
+}
+
+// The stars come together then drift apart: the message shows when they're
+// the closest to each other, which is when they take the fewest lines.
+// Returns the sky cropped around the stars at that moment, and the time.
+fn message(stars: &[Star]) -> (Grid<bool>, i32) {
+    let mut time = 0;
+    while height(&positions(stars, time + 1)) < height(&positions(stars, time)) {
+        time += 1;
+    }
+
+    let points = positions(stars, time);
+    let min_x = points.iter().map(|p| p.x).min().unwrap_or(0);
+    let min_y = points.iter().map(|p| p.y).min().unwrap_or(0);
+    let width = points.iter().map(|p| p.x - min_x + 1).max().unwrap_or(0);
+    let mut sky = Grid::new(width as usize, height(&points) as usize, false);
+    for p in points {
+        sky[p - Point::new(min_x, min_y)] = true;
+    }
+    (sky, time)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+position=< 9,  1> velocity=< 0,  2>
+position=< 7,  0> velocity=<-1,  0>
+position=< 3, -2> velocity=<-1,  1>
+position=< 6, 10> velocity=<-2, -1>
+position=< 2, -4> velocity=< 2,  2>
+position=<-6, 10> velocity=< 2, -2>
+position=< 1,  8> velocity=< 1, -1>
+position=< 1,  7> velocity=< 1,  0>
+position=<-3, 11> velocity=< 1, -2>
+position=< 7,  6> velocity=<-1, -1>
+position=<-2,  3> velocity=< 1,  0>
+position=<-4,  3> velocity=< 2,  0>
+position=<10, -3> velocity=<-1,  1>
+position=< 5, 11> velocity=< 1, -2>
+position=< 4,  7> velocity=< 0, -1>
+position=< 8, -2> velocity=< 0,  1>
+position=<15,  0> velocity=<-2,  0>
+position=< 1,  6> velocity=< 1,  0>
+position=< 8,  9> velocity=< 0, -1>
+position=< 3,  3> velocity=<-1,  1>
+position=< 0,  5> velocity=< 0, -1>
+position=<-2,  2> velocity=< 2,  0>
+position=< 5, -2> velocity=< 1,  2>
+position=< 1,  4> velocity=< 2,  1>
+position=<-2,  7> velocity=< 2, -2>
+position=< 3,  6> velocity=<-1, -1>
+position=< 5,  0> velocity=< 1,  0>
+position=<-6,  0> velocity=< 2,  0>
+position=< 5,  9> velocity=< 1, -2>
+position=<14,  7> velocity=<-2,  0>
+position=<-3,  6> velocity=< 2, -1>
+";
+
+    #[test]
+    fn test_message() {
+        let (sky, time) = message(&parse_stars(EXAMPLE));
+        assert_eq!(time, 3);
+        assert_eq!(
+            sky.render(|&lit| if lit { '#' } else { '.' }),
+            "\
+#...#..###
+#...#...#.
+#...#...#.
+#####...#.
+#...#...#.
+#...#...#.
+#...#...#.
+#...#..###
+"
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use input;
+
+const SIZE: usize = 300;
+
+pub fn answer1() {
+    let table = SummedArea::new(read_serial());
+    let (x, y, _) = best_square(&table, 3);
+    println!("{},{}", x, y);
+}
+
+pub fn answer2() {
+    let table = SummedArea::new(read_serial());
+    let (x, y, size) = best_square_any_size(&table);
+    println!("{},{},{}", x, y, size);
+}
+
+fn read_serial() -> i32 {
+    input::read_to_string("data/2018/day11.txt")
+        .trim()
+        .parse()
+        .unwrap()
+}
+
+// x and y go from 1 to 300
+fn power_level(x: usize, y: usize, serial: i32) -> i32 {
+    let rack = x as i32 + 10;
+    let power = (rack * y as i32 + serial) * rack;
+    power / 100 % 10 - 5
+}
+
+// sums[y][x] is the total power of the cells above and to the left of
+// (x, y), both included, so any square takes four lookups
+struct SummedArea {
+    sums: Vec<Vec<i32>>,
+}
+
+impl SummedArea {
+    fn new(serial: i32) -> SummedArea {
+        let mut sums = vec![vec![0; SIZE + 1]; SIZE + 1];
+        for y in 1..=SIZE {
+            for x in 1..=SIZE {
+                sums[y][x] = power_level(x, y, serial) + sums[y - 1][x] + sums[y][x - 1]
+                    - sums[y - 1][x - 1];
+            }
+        }
+        SummedArea { sums }
+    }
+
+    // total power of the square with its top left corner at (x, y)
+    fn square(&self, x: usize, y: usize, size: usize) -> i32 {
+        let (x0, y0, x1, y1) = (x - 1, y - 1, x + size - 1, y + size - 1);
+        self.sums[y1][x1] - self.sums[y0][x1] - self.sums[y1][x0] + self.sums[y0][x0]
+    }
+}
+
+// top left corner and total power of the most powerful square
+fn best_square(table: &SummedArea, size: usize) -> (usize, usize, i32) {
+    let mut best = (0, 0, i32::MIN);
+    for y in 1..=SIZE + 1 - size {
+        for x in 1..=SIZE + 1 - size {
+            let power = table.square(x, y, size);
+            if power > best.2 {
+                best = (x, y, power);
+            }
+        }
+    }
+    best
+}
+
+fn best_square_any_size(table: &SummedArea) -> (usize, usize, usize) {
+    let (x, y, size, _) = (1..=SIZE)
+        .map(|size| {
+            let (x, y, power) = best_square(table, size);
+            (x, y, size, power)
+        })
+        .max_by_key(|s| s.3)
+        .unwrap();
+    (x, y, size)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_power_level() {
+        assert_eq!(power_level(3, 5, 8), 4);
+        assert_eq!(power_level(122, 79, 57), -5);
+        assert_eq!(power_level(217, 196, 39), 0);
+        assert_eq!(power_level(101, 153, 71), 4);
+    }
+
+    #[test]
+    fn test_best_square() {
+        let table = SummedArea::new(18);
+        assert_eq!(best_square(&table, 3), (33, 45, 29));
+        assert_eq!(best_square(&SummedArea::new(42), 3), (21, 61, 30));
+        assert_eq!(best_square_any_size(&table), (90, 269, 16));
+    }
+}
@@ -0,0 +1,122 @@
+use input;
+use std::collections::HashSet;
+
+// pots with a plant, by number
+type Pots = HashSet<i64>;
+
+// patterns of 5 pots, the pot in the middle being the 3rd, which give a
+// plant in the middle pot at the next generation
+type Rules = HashSet<[bool; 5]>;
+
+const MANY_GENERATIONS: u64 = 50_000_000_000;
+
+pub fn answer1() {
+    let (pots, rules) = read_input();
+    println!("{}", sum_after(pots, &rules, 20));
+}
+
+pub fn answer2() {
+    let (pots, rules) = read_input();
+    println!("{}", sum_after(pots, &rules, MANY_GENERATIONS));
+}
+
+fn read_input() -> (Pots, Rules) {
+    parse_input(&input::read_to_string("data/2018/day12.txt"))
+}
+
+fn parse_input(text: &str) -> (Pots, Rules) {
+    let mut lines = text.lines().map(|l| l.trim()).filter(|l| !l.is_empty());
+    let initial = lines
+        .next()
+        .and_then(|l| l.strip_prefix("initial state: "))
+        .expect("missing initial state");
+    let pots = initial
+        .chars()
+        .enumerate()
+        .filter(|(_, c)| *c == '#')
+        .map(|(i, _)| i as i64)
+        .collect();
+    let mut rules = Rules::new();
+    for line in lines {
+        let mut parts = line.split(" => ");
+        let (pattern, result) = (parts.next().unwrap(), parts.next());
+        if result == Some("#") {
+            let mut key = [false; 5];
+            for (k, c) in key.iter_mut().zip(pattern.chars()) {
+                *k = c == '#';
+            }
+            rules.insert(key);
+        }
+    }
+    (pots, rules)
+}
+
+fn next_generation(pots: &Pots, rules: &Rules) -> Pots {
+    let min = pots.iter().min().cloned().unwrap_or(0);
+    let max = pots.iter().max().cloned().unwrap_or(0);
+    (min - 2..=max + 2)
+        .filter(|&i| {
+            let mut key = [false; 5];
+            for (k, j) in key.iter_mut().zip(i - 2..=i + 2) {
+                *k = pots.contains(&j);
+            }
+            rules.contains(&key)
+        })
+        .collect()
+}
+
+// The plants end up moving as a block, the same pattern shifting by the same
+// amount at each generation: once that happens, the rest is just an offset.
+fn sum_after(pots: Pots, rules: &Rules, generations: u64) -> i64 {
+    let mut pots = pots;
+    for generation in 0..generations {
+        let next = next_generation(&pots, rules);
+        let shift =
+            next.iter().min().cloned().unwrap_or(0) - pots.iter().min().cloned().unwrap_or(0);
+        let shifted: Pots = pots.iter().map(|p| p + shift).collect();
+        if shifted == next {
+            let remaining = (generations - generation) as i64;
+            return pots.iter().map(|p| p + shift * remaining).sum();
+        }
+        pots = next;
+    }
+    pots.iter().sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+initial state: #..#.#..##......###...###
+
+...## => #
+..#.. => #
+.#... => #
+.#.#. => #
+.#.## => #
+.##.. => #
+.#### => #
+#.#.# => #
+#.### => #
+##.#. => #
+##.## => #
+###.. => #
+###.# => #
+####. => #
+";
+
+    #[test]
+    fn test_generations() {
+        let (pots, rules) = parse_input(EXAMPLE);
+        assert_eq!(pots.len(), 11);
+        assert_eq!(sum_after(pots.clone(), &rules, 20), 325);
+
+        // a glider moving one pot to the right each time
+        let (glider, rules) =
+            parse_input("initial state: ##\n\n.##.. => #\n##... => #\n#.... => .\n");
+        assert_eq!(sum_after(glider.clone(), &rules, 5), 5 + 6);
+        let expected = 2 * MANY_GENERATIONS as i64 + 1;
+        assert_eq!(sum_after(glider, &rules, MANY_GENERATIONS), expected);
+    }
+}
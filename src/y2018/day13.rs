@@ -0,0 +1,156 @@
+use grid::{Direction, Grid, Point};
+use input;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cart {
+    position: Point,
+    direction: Direction,
+    // number of intersections crossed so far
+    intersections: usize,
+    crashed: bool,
+}
+
+pub fn answer1() {
+    let (tracks, carts) = read_map();
+    let p = first_crash(&tracks, carts);
+    println!("{},{}", p.x, p.y);
+}
+
+pub fn answer2() {
+    let (tracks, carts) = read_map();
+    let p = last_cart(&tracks, carts);
+    println!("{},{}", p.x, p.y);
+}
+
+fn read_map() -> (Grid<char>, Vec<Cart>) {
+    parse_map(&input::read_to_string("data/2018/day13.txt"))
+}
+
+// the carts are replaced by the track they're on
+fn parse_map(text: &str) -> (Grid<char>, Vec<Cart>) {
+    let lines: Vec<&str> = text.lines().collect();
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let padded: Vec<String> = lines
+        .iter()
+        .map(|l| format!("{:width$}", l, width = width))
+        .collect();
+    let mut tracks = Grid::parse(&padded.join("\n"), Some).unwrap();
+
+    let mut carts = Vec::new();
+    for p in tracks.points().collect::<Vec<Point>>() {
+        if let Some(direction) = Direction::from_char(tracks[p]) {
+            carts.push(Cart {
+                position: p,
+                direction,
+                intersections: 0,
+                crashed: false,
+            });
+            tracks[p] = match direction {
+                Direction::Up | Direction::Down => '|',
+                Direction::Left | Direction::Right => '-',
+            };
+        }
+    }
+    (tracks, carts)
+}
+
+impl Cart {
+    fn advance(&mut self, tracks: &Grid<char>) {
+        self.position += self.direction.delta();
+        self.direction = match (tracks[self.position], self.direction) {
+            ('/', Direction::Up) | ('/', Direction::Down) => self.direction.turn_right(),
+            ('/', _) => self.direction.turn_left(),
+            ('\\', Direction::Up) | ('\\', Direction::Down) => self.direction.turn_left(),
+            ('\\', _) => self.direction.turn_right(),
+            ('+', _) => {
+                self.intersections += 1;
+                match self.intersections % 3 {
+                    1 => self.direction.turn_left(),
+                    2 => self.direction,
+                    _ => self.direction.turn_right(),
+                }
+            }
+            _ => self.direction,
+        };
+    }
+}
+
+// Carts move one at a time, from the top row to the bottom one and from left
+// to right. Returns the places where carts crashed during the tick, the
+// crashed carts are left out.
+fn tick(tracks: &Grid<char>, carts: &mut Vec<Cart>) -> Vec<Point> {
+    carts.sort_by_key(|c| (c.position.y, c.position.x));
+    let mut crashes = Vec::new();
+    for i in 0..carts.len() {
+        if carts[i].crashed {
+            continue;
+        }
+        carts[i].advance(tracks);
+        let position = carts[i].position;
+        for j in 0..carts.len() {
+            if j != i && !carts[j].crashed && carts[j].position == position {
+                carts[i].crashed = true;
+                carts[j].crashed = true;
+                crashes.push(position);
+            }
+        }
+    }
+    carts.retain(|c| !c.crashed);
+    crashes
+}
+
+fn first_crash(tracks: &Grid<char>, carts: Vec<Cart>) -> Point {
+    let mut carts = carts;
+    loop {
+        if let Some(&p) = tick(tracks, &mut carts).first() {
+            return p;
+        }
+    }
+}
+
+// where the last cart standing is at the end of the tick the others crashed
+fn last_cart(tracks: &Grid<char>, carts: Vec<Cart>) -> Point {
+    assert!(
+        carts.len() % 2 == 1,
+        "an even number of carts can all crash"
+    );
+    let mut carts = carts;
+    while carts.len() > 1 {
+        tick(tracks, &mut carts);
+    }
+    carts[0].position
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_first_crash() {
+        let example = r"/->-\        
+|   |  /----\
+| /-+--+-\  |
+| | |  | v  |
+\-+-/  \-+--/
+  \------/   
+";
+        let (tracks, carts) = parse_map(example);
+        assert_eq!(carts.len(), 2);
+        assert_eq!(tracks[Point::new(2, 0)], '-');
+        assert_eq!(first_crash(&tracks, carts), Point::new(7, 3));
+    }
+
+    #[test]
+    fn test_last_cart() {
+        let example = r"/>-<\  
+|   |  
+| /<+-\
+| | | v
+\>+</ |
+  |   ^
+  \<->/
+";
+        let (tracks, carts) = parse_map(example);
+        assert_eq!(last_cart(&tracks, carts), Point::new(6, 4));
+    }
+}
@@ -0,0 +1,93 @@
+use input;
+
+pub fn answer1() {
+    let n: usize = read_input().parse().unwrap();
+    println!("{}", scores_after(n));
+}
+
+pub fn answer2() {
+    println!("{}", recipes_before(&read_input()));
+}
+
+fn read_input() -> String {
+    input::read_to_string("data/2018/day14.txt")
+        .trim()
+        .to_string()
+}
+
+// The scoreboard only grows: each round, the two elves combine their current
+// recipes into one or two new ones, then move forward.
+struct Scoreboard {
+    scores: Vec<u8>,
+    elves: [usize; 2],
+}
+
+impl Scoreboard {
+    fn new() -> Scoreboard {
+        Scoreboard {
+            scores: vec![3, 7],
+            elves: [0, 1],
+        }
+    }
+
+    fn round(&mut self) {
+        let sum = self.scores[self.elves[0]] + self.scores[self.elves[1]];
+        if sum >= 10 {
+            self.scores.push(sum / 10);
+        }
+        self.scores.push(sum % 10);
+        for e in self.elves.iter_mut() {
+            *e = (*e + 1 + self.scores[*e] as usize) % self.scores.len();
+        }
+    }
+}
+
+// the ten scores after the first `n` recipes
+fn scores_after(n: usize) -> String {
+    let mut board = Scoreboard::new();
+    while board.scores.len() < n + 10 {
+        board.round();
+    }
+    board.scores[n..n + 10]
+        .iter()
+        .map(|s| (b'0' + s) as char)
+        .collect()
+}
+
+// number of recipes before the digits first appear on the scoreboard. A
+// round adds up to two scores, so the digits can end on either of them.
+fn recipes_before(digits: &str) -> usize {
+    let target: Vec<u8> = digits.bytes().map(|b| b - b'0').collect();
+    let mut board = Scoreboard::new();
+    let mut checked = 0;
+    loop {
+        while checked + target.len() <= board.scores.len() {
+            if board.scores[checked..checked + target.len()] == target[..] {
+                return checked;
+            }
+            checked += 1;
+        }
+        board.round();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_scores_after() {
+        assert_eq!(scores_after(9), "5158916779");
+        assert_eq!(scores_after(5), "0124515891");
+        assert_eq!(scores_after(18), "9251071085");
+        assert_eq!(scores_after(2018), "5941429882");
+    }
+
+    #[test]
+    fn test_recipes_before() {
+        assert_eq!(recipes_before("51589"), 9);
+        assert_eq!(recipes_before("01245"), 5);
+        assert_eq!(recipes_before("92510"), 18);
+        assert_eq!(recipes_before("59414"), 2018);
+    }
+}
@@ -0,0 +1,269 @@
+use grid::{Grid, Point};
+use input;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Race {
+    Elf,
+    Goblin,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Unit {
+    race: Race,
+    position: Point,
+    hp: i32,
+    attack: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Outcome {
+    full_rounds: usize,
+    hp_left: i32,
+    winner: Race,
+    dead_elves: usize,
+}
+
+impl Outcome {
+    fn score(&self) -> i32 {
+        self.full_rounds as i32 * self.hp_left
+    }
+}
+
+const HP: i32 = 200;
+const ATTACK: i32 = 3;
+
+pub fn answer1() {
+    let (walls, units) = read_map();
+    println!("{}", fight(&walls, units, false).score());
+}
+
+pub fn answer2() {
+    let (walls, units) = read_map();
+    println!("{}", elves_win(&walls, &units).score());
+}
+
+fn read_map() -> (Grid<bool>, Vec<Unit>) {
+    parse_map(&input::read_to_string("data/2018/day15.txt"))
+}
+
+// the walls, and the units in reading order
+fn parse_map(text: &str) -> (Grid<bool>, Vec<Unit>) {
+    let cells = Grid::parse(text.trim(), |c| match c {
+        '#' | '.' | 'E' | 'G' => Some(c),
+        _ => None,
+    })
+    .unwrap();
+    let units = cells
+        .iter()
+        .filter_map(|(p, c)| {
+            let race = match c {
+                'E' => Race::Elf,
+                'G' => Race::Goblin,
+                _ => return None,
+            };
+            Some(Unit {
+                race,
+                position: p,
+                hp: HP,
+                attack: ATTACK,
+            })
+        })
+        .collect();
+    (cells.map(|c| *c == '#'), units)
+}
+
+fn reading_order(p: Point) -> (i32, i32) {
+    (p.y, p.x)
+}
+
+// up, left, right, down
+fn neighbours(p: Point) -> [Point; 4] {
+    [
+        p + Point::new(0, -1),
+        p + Point::new(-1, 0),
+        p + Point::new(1, 0),
+        p + Point::new(0, 1),
+    ]
+}
+
+// distance from `start` to every open square it can reach
+fn distances(walls: &Grid<bool>, units: &[Unit], start: Point) -> Grid<Option<usize>> {
+    let mut distances = Grid::new(walls.width(), walls.height(), None);
+    distances[start] = Some(0);
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+    while let Some(p) = queue.pop_front() {
+        let d = distances[p].unwrap();
+        for &n in neighbours(p).iter() {
+            let open = !walls[n] && units.iter().all(|u| u.hp <= 0 || u.position != n);
+            if open && distances[n].is_none() {
+                distances[n] = Some(d + 1);
+                queue.push_back(n);
+            }
+        }
+    }
+    distances
+}
+
+// where the unit moves this turn, if anywhere: one step towards the nearest
+// square next to an enemy, ties broken in reading order everywhere
+fn next_step(walls: &Grid<bool>, units: &[Unit], i: usize) -> Option<Point> {
+    let unit = units[i];
+    let enemies = || {
+        units
+            .iter()
+            .filter(move |u| u.hp > 0 && u.race != unit.race)
+    };
+    if enemies().any(|e| e.position.manhattan_distance(unit.position) == 1) {
+        return None;
+    }
+
+    let from_unit = distances(walls, units, unit.position);
+    let target = enemies()
+        .flat_map(|e| neighbours(e.position).to_vec())
+        .filter_map(|p| from_unit[p].map(|d| (d, reading_order(p), p)))
+        .min()?
+        .2;
+
+    let from_target = distances(walls, units, target);
+    neighbours(unit.position)
+        .iter()
+        .filter_map(|&p| from_target[p].map(|d| (d, reading_order(p), p)))
+        .min()
+        .map(|(_, _, p)| p)
+}
+
+// the adjacent enemy with the fewest hit points, first in reading order
+fn attack_target(units: &[Unit], i: usize) -> Option<usize> {
+    let unit = units[i];
+    units
+        .iter()
+        .enumerate()
+        .filter(|(_, u)| {
+            u.hp > 0 && u.race != unit.race && u.position.manhattan_distance(unit.position) == 1
+        })
+        .min_by_key(|(_, u)| (u.hp, reading_order(u.position)))
+        .map(|(j, _)| j)
+}
+
+// With `stop_on_elf_death`, the fight stops as soon as an elf dies.
+fn fight(walls: &Grid<bool>, units: Vec<Unit>, stop_on_elf_death: bool) -> Outcome {
+    let mut units = units;
+    let elves = units.iter().filter(|u| u.race == Race::Elf).count();
+    let mut full_rounds = 0;
+    'combat: loop {
+        units.sort_by_key(|u| reading_order(u.position));
+        for i in 0..units.len() {
+            if units[i].hp <= 0 {
+                continue;
+            }
+            let race = units[i].race;
+            if !units.iter().any(|u| u.hp > 0 && u.race != race) {
+                break 'combat;
+            }
+            if let Some(p) = next_step(walls, &units, i) {
+                units[i].position = p;
+            }
+            if let Some(j) = attack_target(&units, i) {
+                units[j].hp -= units[i].attack;
+                if units[j].hp <= 0 && units[j].race == Race::Elf && stop_on_elf_death {
+                    break 'combat;
+                }
+            }
+        }
+        units.retain(|u| u.hp > 0);
+        full_rounds += 1;
+    }
+    units.retain(|u| u.hp > 0);
+    Outcome {
+        full_rounds,
+        hp_left: units.iter().map(|u| u.hp).sum(),
+        winner: units[0].race,
+        dead_elves: elves - units.iter().filter(|u| u.race == Race::Elf).count(),
+    }
+}
+
+// the outcome with the weakest elves which all survive
+fn elves_win(walls: &Grid<bool>, units: &[Unit]) -> Outcome {
+    (ATTACK + 1..)
+        .map(|attack| {
+            let boosted = units
+                .iter()
+                .map(|u| match u.race {
+                    Race::Elf => Unit { attack, ..*u },
+                    Race::Goblin => *u,
+                })
+                .collect();
+            fight(walls, boosted, true)
+        })
+        .find(|o| o.dead_elves == 0)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLES: [(&str, i32, Option<i32>); 6] = [
+        (
+            "#######\n#.G...#\n#...EG#\n#.#.#G#\n#..G#E#\n#.....#\n#######\n",
+            27730,
+            Some(4988),
+        ),
+        (
+            "#######\n#G..#E#\n#E#E.E#\n#G.##.#\n#...#E#\n#...E.#\n#######\n",
+            36334,
+            None,
+        ),
+        (
+            "#######\n#E..EG#\n#.#G.E#\n#E.##E#\n#G..#.#\n#..E#.#\n#######\n",
+            39514,
+            Some(31284),
+        ),
+        (
+            "#######\n#E.G#.#\n#.#G..#\n#G.#.G#\n#G..#.#\n#...E.#\n#######\n",
+            27755,
+            Some(3478),
+        ),
+        (
+            "#######\n#.E...#\n#.#..G#\n#.###.#\n#E#G#G#\n#...#G#\n#######\n",
+            28944,
+            Some(6474),
+        ),
+        (
+            "#########\n#G......#\n#.E.#...#\n#..##..G#\n#...##..#\n#...#...#\n#.G...G.#\n#.....G.#\n#########\n",
+            18740,
+            Some(1140),
+        ),
+    ];
+
+    #[test]
+    fn test_fight() {
+        let (walls, units) = parse_map(EXAMPLES[0].0);
+        let outcome = fight(&walls, units, false);
+        assert_eq!(outcome.full_rounds, 47);
+        assert_eq!(outcome.hp_left, 590);
+        assert_eq!(outcome.winner, Race::Goblin);
+        for &(map, score, _) in EXAMPLES.iter() {
+            let (walls, units) = parse_map(map);
+            assert_eq!(fight(&walls, units, false).score(), score);
+        }
+    }
+
+    #[test]
+    fn test_elves_win() {
+        for &(map, _, score) in EXAMPLES.iter() {
+            if let Some(score) = score {
+                let (walls, units) = parse_map(map);
+                assert_eq!(elves_win(&walls, &units).score(), score);
+            }
+        }
+    }
+
+    #[test]
+    fn test_movement() {
+        let (walls, units) = parse_map("#######\n#E..G.#\n#...#.#\n#.G.#G#\n#######\n");
+        assert_eq!(next_step(&walls, &units, 0), Some(Point::new(2, 1)));
+    }
+}
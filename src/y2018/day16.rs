@@ -0,0 +1,158 @@
+use input;
+use std::collections::{BTreeMap, BTreeSet};
+use y2018::device::{Device, Instruction, Opcode, Program, Registers};
+
+// an instruction with a numbered opcode, and the registers around it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Sample {
+    before: Registers,
+    instruction: [u64; 4],
+    after: Registers,
+}
+
+impl Sample {
+    fn candidates(&self) -> BTreeSet<Opcode> {
+        let [_, a, b, c] = self.instruction;
+        Opcode::ALL
+            .iter()
+            .cloned()
+            .filter(|op| op.execute(&self.before, a, b, c) == Some(self.after))
+            .collect()
+    }
+}
+
+pub fn answer1() {
+    let (samples, _) = read_input();
+    let ambiguous = samples.iter().filter(|s| s.candidates().len() >= 3).count();
+    println!("{}", ambiguous);
+}
+
+pub fn answer2() {
+    let (samples, program) = read_input();
+    let opcodes = identify(&samples).expect("the samples are ambiguous");
+    println!("{}", run(&opcodes, &program)[0]);
+}
+
+fn read_input() -> (Vec<Sample>, Vec<[u64; 4]>) {
+    parse_input(&input::read_to_string("data/2018/day16.txt"))
+}
+
+fn parse_registers(text: &str) -> Registers {
+    let mut registers = [0; 6];
+    let values = text.trim().trim_start_matches('[').trim_end_matches(']');
+    for (r, v) in registers.iter_mut().zip(values.split(',')) {
+        *r = v.trim().parse().unwrap();
+    }
+    registers
+}
+
+fn parse_numbers(line: &str) -> [u64; 4] {
+    let mut numbers = [0; 4];
+    for (n, v) in numbers.iter_mut().zip(line.split_whitespace()) {
+        *n = v.parse().unwrap();
+    }
+    numbers
+}
+
+// the samples, then the test program
+fn parse_input(text: &str) -> (Vec<Sample>, Vec<[u64; 4]>) {
+    let mut samples = Vec::new();
+    let mut program = Vec::new();
+    let mut lines = text.lines().map(|l| l.trim()).filter(|l| !l.is_empty());
+    while let Some(line) = lines.next() {
+        match line.strip_prefix("Before:") {
+            Some(before) => {
+                let instruction = parse_numbers(lines.next().unwrap());
+                let after = lines.next().and_then(|l| l.strip_prefix("After:"));
+                samples.push(Sample {
+                    before: parse_registers(before),
+                    instruction,
+                    after: parse_registers(after.expect("missing After line")),
+                });
+            }
+            None => program.push(parse_numbers(line)),
+        }
+    }
+    (samples, program)
+}
+
+// the opcode behind each number, None unless the samples tell them apart
+fn identify(samples: &[Sample]) -> Option<BTreeMap<u64, Opcode>> {
+    let mut candidates: BTreeMap<u64, BTreeSet<Opcode>> = BTreeMap::new();
+    for sample in samples {
+        let matching = sample.candidates();
+        candidates
+            .entry(sample.instruction[0])
+            .and_modify(|c| *c = c.intersection(&matching).cloned().collect())
+            .or_insert(matching);
+    }
+
+    // numbers with a single candidate take it away from all the others
+    let mut opcodes = BTreeMap::new();
+    while let Some((&number, ops)) = candidates.iter().find(|(_, ops)| ops.len() == 1) {
+        let op = *ops.iter().next().unwrap();
+        opcodes.insert(number, op);
+        candidates.remove(&number);
+        for ops in candidates.values_mut() {
+            ops.remove(&op);
+        }
+    }
+    if candidates.is_empty() {
+        Some(opcodes)
+    } else {
+        None
+    }
+}
+
+fn run(opcodes: &BTreeMap<u64, Opcode>, program: &[[u64; 4]]) -> Registers {
+    let program = Program {
+        ip_register: None,
+        instructions: program
+            .iter()
+            .map(|&[n, a, b, c]| Instruction {
+                op: opcodes[&n],
+                a,
+                b,
+                c,
+            })
+            .collect(),
+    };
+    let mut device = Device::new([0; 6]);
+    device.run(&program);
+    device.registers
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_candidates() {
+        let (samples, program) =
+            parse_input("Before: [3, 2, 1, 1]\n9 2 1 2\nAfter:  [3, 2, 2, 1]\n\n\n\n9 0 0 1\n");
+        assert_eq!(
+            samples[0].candidates(),
+            [Opcode::Addi, Opcode::Mulr, Opcode::Seti]
+                .iter()
+                .cloned()
+                .collect()
+        );
+        assert_eq!(program, vec![[9, 0, 0, 1]]);
+    }
+
+    #[test]
+    fn test_identify() {
+        // 0 can only be seti, 1 is setr, addi or bori, until the last sample
+        let text = "Before: [1, 0, 0, 0]\n0 7 0 1\nAfter:  [1, 7, 0, 0]\n\n\
+                    Before: [3, 4, 0, 0]\n1 1 0 2\nAfter:  [3, 4, 4, 0]\n\n\
+                    Before: [3, 4, 0, 0]\n1 1 5 2\nAfter:  [3, 4, 4, 0]\n\n\n\n\
+                    0 5 0 0\n1 0 0 2\n";
+        let (samples, program) = parse_input(text);
+        assert_eq!(samples.len(), 3);
+        assert_eq!(identify(&samples[..2]), None);
+        let opcodes = identify(&samples).unwrap();
+        assert_eq!(opcodes[&0], Opcode::Seti);
+        assert_eq!(opcodes[&1], Opcode::Setr);
+        assert_eq!(run(&opcodes, &program), [5, 0, 5, 0, 0, 0]);
+    }
+}
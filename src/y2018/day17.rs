@@ -0,0 +1,179 @@
+use grid::{Grid, Point};
+use input;
+use regex::Regex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Sand,
+    Clay,
+    Flowing,
+    Settled,
+}
+
+const SPRING: Point = Point { x: 500, y: 0 };
+const DOWN: Point = Point { x: 0, y: 1 };
+const UP: Point = Point { x: 0, y: -1 };
+
+// the ground around the clay, one column of sand wider on each side for the
+// water to flow around, and the first line scanned
+struct Ground {
+    tiles: Grid<Tile>,
+    x_offset: i32,
+    min_y: i32,
+}
+
+pub fn answer1() {
+    let mut ground = read_ground();
+    ground.flood();
+    println!("{}", ground.count(&[Tile::Flowing, Tile::Settled]));
+}
+
+pub fn answer2() {
+    let mut ground = read_ground();
+    ground.flood();
+    println!("{}", ground.count(&[Tile::Settled]));
+}
+
+fn read_ground() -> Ground {
+    parse_ground(&input::read_to_string("data/2018/day17.txt"))
+}
+
+// lines like "x=495, y=2..7" or "y=7, x=495..501"
+fn parse_ground(text: &str) -> Ground {
+    lazy_static! {
+        static ref VEIN: Regex = Regex::new(r"^([xy])=(\d+), [xy]=(\d+)\.\.(\d+)$").unwrap();
+    }
+    let mut clay = Vec::new();
+    for line in text.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+        let caps = VEIN
+            .captures(line)
+            .unwrap_or_else(|| panic!("invalid vein {:?}", line));
+        let n = |i: usize| caps[i].parse::<i32>().unwrap();
+        for i in n(3)..=n(4) {
+            clay.push(if &caps[1] == "x" {
+                Point::new(n(2), i)
+            } else {
+                Point::new(i, n(2))
+            });
+        }
+    }
+
+    let min_x = clay.iter().map(|p| p.x).min().unwrap() - 1;
+    let max_x = clay.iter().map(|p| p.x).max().unwrap() + 1;
+    let max_y = clay.iter().map(|p| p.y).max().unwrap();
+    let offset = Point::new(min_x, 0);
+    let mut tiles = Grid::new((max_x - min_x + 1) as usize, max_y as usize + 1, Tile::Sand);
+    for &p in &clay {
+        tiles[p - offset] = Tile::Clay;
+    }
+    Ground {
+        tiles,
+        x_offset: min_x,
+        min_y: clay.iter().map(|p| p.y).min().unwrap(),
+    }
+}
+
+impl Ground {
+    fn flood(&mut self) {
+        self.fall(SPRING - Point::new(self.x_offset, 0));
+    }
+
+    // tiles of the given kinds, between the first and last lines scanned
+    fn count(&self, kinds: &[Tile]) -> usize {
+        self.tiles
+            .iter()
+            .filter(|(p, t)| p.y >= self.min_y && kinds.contains(t))
+            .count()
+    }
+
+    // water falls from `start` until it reaches something to spread on, then
+    // fills the reservoir it's in line by line, until it overflows
+    fn fall(&mut self, start: Point) {
+        let mut p = start;
+        loop {
+            let below = p + DOWN;
+            match self.tiles.get(below) {
+                None | Some(Tile::Flowing) => return,
+                Some(Tile::Sand) => {
+                    self.tiles[below] = Tile::Flowing;
+                    p = below;
+                }
+                Some(Tile::Clay) | Some(Tile::Settled) => break,
+            }
+        }
+        loop {
+            let (left, left_wall) = self.spread(p, -1);
+            let (right, right_wall) = self.spread(p, 1);
+            let tile = if left_wall && right_wall {
+                Tile::Settled
+            } else {
+                Tile::Flowing
+            };
+            for x in left.x..=right.x {
+                self.tiles[Point::new(x, p.y)] = tile;
+            }
+            if tile == Tile::Flowing {
+                if !left_wall {
+                    self.fall(left);
+                }
+                if !right_wall {
+                    self.fall(right);
+                }
+                return;
+            }
+            p += UP;
+        }
+    }
+
+    // how far water goes sideways from `p`, and whether it's stopped by clay
+    // rather than falling off an edge
+    fn spread(&self, p: Point, dx: i32) -> (Point, bool) {
+        let mut p = p;
+        loop {
+            match self.tiles[p + DOWN] {
+                Tile::Sand | Tile::Flowing => return (p, false),
+                Tile::Clay | Tile::Settled => {}
+            }
+            let next = p + Point::new(dx, 0);
+            if self.tiles[next] == Tile::Clay {
+                return (p, true);
+            }
+            p = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "x=495, y=2..7
+y=7, x=495..501
+x=501, y=3..7
+x=498, y=2..4
+x=506, y=1..2
+x=498, y=10..13
+x=504, y=10..13
+y=13, x=498..504
+";
+
+    #[test]
+    fn test_flood() {
+        let mut ground = parse_ground(EXAMPLE);
+        ground.flood();
+        assert_eq!(ground.count(&[Tile::Flowing, Tile::Settled]), 57);
+        assert_eq!(ground.count(&[Tile::Settled]), 29);
+    }
+
+    #[test]
+    fn test_nested_reservoirs() {
+        // a small bucket inside a large one: the water fills the small one,
+        // then rises around it, over the 5 tiles of clay
+        let mut ground = parse_ground(
+            "x=495, y=2..8\nx=505, y=2..8\ny=8, x=495..505\n\
+             x=499, y=5..6\nx=501, y=5..6\ny=6, x=499..501\n",
+        );
+        ground.flood();
+        assert_eq!(ground.count(&[Tile::Settled]), 9 * 6 - 5);
+    }
+}
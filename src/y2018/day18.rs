@@ -0,0 +1,104 @@
+use cycle;
+use grid::Grid;
+use input;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Acre {
+    Open,
+    Trees,
+    Lumberyard,
+}
+
+const LONG_TIME: usize = 1_000_000_000;
+
+pub fn answer1() {
+    println!("{}", resource_value(&after(read_area(), 10)));
+}
+
+pub fn answer2() {
+    println!("{}", resource_value(&after(read_area(), LONG_TIME)));
+}
+
+fn read_area() -> Grid<Acre> {
+    parse_area(&input::read_to_string("data/2018/day18.txt"))
+}
+
+fn parse_area(text: &str) -> Grid<Acre> {
+    Grid::parse(text.trim(), |c| match c {
+        '.' => Some(Acre::Open),
+        '|' => Some(Acre::Trees),
+        '#' => Some(Acre::Lumberyard),
+        _ => None,
+    })
+    .unwrap()
+}
+
+fn next_minute(area: &Grid<Acre>) -> Grid<Acre> {
+    Grid::from_fn(area.width(), area.height(), |p| {
+        let around = |acre| area.neighbours8(p).filter(|&n| area[n] == acre).count();
+        match area[p] {
+            Acre::Open if around(Acre::Trees) >= 3 => Acre::Trees,
+            Acre::Trees if around(Acre::Lumberyard) >= 3 => Acre::Lumberyard,
+            Acre::Lumberyard if around(Acre::Lumberyard) == 0 || around(Acre::Trees) == 0 => {
+                Acre::Open
+            }
+            acre => acre,
+        }
+    })
+}
+
+// the area soon settles in a loop, which is skipped over
+fn after(area: Grid<Acre>, minutes: usize) -> Grid<Acre> {
+    let cycle = cycle::brent(&area, next_minute);
+    let minutes = if minutes > cycle.start {
+        cycle.start + (minutes - cycle.start) % cycle.length
+    } else {
+        minutes
+    };
+    (0..minutes).fold(area, |area, _| next_minute(&area))
+}
+
+fn resource_value(area: &Grid<Acre>) -> usize {
+    let count = |acre| area.cells().iter().filter(|&&a| a == acre).count();
+    count(Acre::Trees) * count(Acre::Lumberyard)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = ".#.#...|#.
+.....#|##|
+.|..|...#.
+..|#.....#
+#.#|||#|#|
+...#.||...
+.|....|...
+||...#|.#|
+|.||||..|.
+....#|..|.
+";
+
+    #[test]
+    fn test_after() {
+        let area = parse_area(EXAMPLE);
+        let ten = after(area.clone(), 10);
+        assert_eq!(
+            ten.render(|a| match a {
+                Acre::Open => '.',
+                Acre::Trees => '|',
+                Acre::Lumberyard => '#',
+            }),
+            ".||##.....\n||###.....\n||##......\n|##.....##\n|##.....##\n\
+             |##....##|\n||##.####|\n||#####|||\n||||#|||||\n||||||||||\n"
+        );
+        assert_eq!(resource_value(&ten), 1147);
+
+        // skipping the loop gives the same as the plain simulation
+        let mut area = area;
+        for minutes in 1..=30 {
+            area = next_minute(&area);
+            assert_eq!(after(parse_area(EXAMPLE), minutes), area);
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use input;
+use y2018::device::{parse_program, Device, Program};
+
+pub fn answer1() {
+    let program = read_program();
+    let mut device = Device::new([0; 6]);
+    device.run(&program);
+    println!("{}", device.registers[0]);
+}
+
+pub fn answer2() {
+    let program = read_program();
+    let mut device = Device::new([1, 0, 0, 0, 0, 0]);
+    println!("{}", sum_of_divisors(target(&mut device, &program)));
+}
+
+fn read_program() -> Program {
+    parse_program(&input::read_to_string("data/2018/day19.txt"))
+}
+
+// The program computes a large number, then jumps back to instruction 1
+// to add up its divisors, trying every pair of numbers below it: way too
+// slow. So it only runs until the number is ready, the largest register.
+fn target(device: &mut Device, program: &Program) -> u64 {
+    while device.step(program) {
+        if device.ip == 1 {
+            break;
+        }
+    }
+    *device.registers.iter().max().unwrap()
+}
+
+fn sum_of_divisors(n: u64) -> u64 {
+    (1..)
+        .take_while(|d| d * d <= n)
+        .filter(|&d| n.is_multiple_of(d))
+        .map(|d| if d * d == n { d } else { d + n / d })
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sum_of_divisors() {
+        assert_eq!(sum_of_divisors(1), 1);
+        assert_eq!(sum_of_divisors(12), 1 + 2 + 3 + 4 + 6 + 12);
+        assert_eq!(sum_of_divisors(49), 1 + 7 + 49);
+    }
+
+    #[test]
+    fn test_target() {
+        // the same shape as the puzzle on a small number: the setup at the
+        // end computes 2 * 3 * 5 in register 4 then jumps back to the start,
+        // where the slow loop adds up the divisors into register 0
+        let program = parse_program(
+            "#ip 3
+addi 3 16 3
+seti 1 0 1
+seti 1 0 2
+mulr 1 2 5
+eqrr 5 4 5
+addr 5 3 3
+addi 3 1 3
+addr 1 0 0
+addi 2 1 2
+gtrr 2 4 5
+addr 3 5 3
+seti 2 0 3
+addi 1 1 1
+gtrr 1 4 5
+addr 5 3 3
+seti 1 0 3
+mulr 3 3 3
+seti 30 0 4
+seti 0 0 3
+",
+        );
+        let mut device = Device::new([0; 6]);
+        assert_eq!(target(&mut device.clone(), &program), 30);
+        device.run(&program);
+        assert_eq!(device.registers[0], sum_of_divisors(30));
+    }
+}
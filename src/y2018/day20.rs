@@ -0,0 +1,102 @@
+use graph::Graph;
+use grid::{Direction, Point};
+use input;
+use std::collections::BTreeSet;
+
+pub fn answer1() {
+    let distances = read_distances();
+    println!("{}", distances.iter().max().unwrap());
+}
+
+pub fn answer2() {
+    let distances = read_distances();
+    println!("{}", distances.iter().filter(|&&d| d >= 1000).count());
+}
+
+fn read_distances() -> Vec<usize> {
+    let text = input::read_to_string("data/2018/day20.txt");
+    distances(&build_map(text.trim()))
+}
+
+// The rooms, linked by doors. Every branch of the regex is followed from
+// every room the previous part can end in, so "(N|S)E" goes east from both
+// the north and the south rooms.
+fn build_map(regex: &str) -> Graph<Point> {
+    let mut map = Graph::undirected();
+    map.intern(Point::ORIGIN);
+    let mut current: BTreeSet<Point> = BTreeSet::new();
+    current.insert(Point::ORIGIN);
+    // where the enclosing groups start, and where their branches ended
+    let mut groups: Vec<(BTreeSet<Point>, BTreeSet<Point>)> = Vec::new();
+    for c in regex.trim_start_matches('^').trim_end_matches('$').chars() {
+        match c {
+            '(' => groups.push((current.clone(), BTreeSet::new())),
+            '|' => {
+                let (starts, ends) = groups.last_mut().expect("| outside of a group");
+                ends.extend(current);
+                current = starts.clone();
+            }
+            ')' => {
+                let (_, ends) = groups.pop().expect("unbalanced )");
+                current.extend(ends);
+            }
+            _ => {
+                let delta = match c {
+                    'N' => Direction::Up,
+                    'E' => Direction::Right,
+                    'S' => Direction::Down,
+                    'W' => Direction::Left,
+                    _ => panic!("unexpected {:?} in the regex", c),
+                }
+                .delta();
+                current = current
+                    .into_iter()
+                    .map(|room| {
+                        map.add_edge(room, room + delta);
+                        room + delta
+                    })
+                    .collect();
+            }
+        }
+    }
+    map
+}
+
+// the fewest doors to go through to reach each room
+fn distances(map: &Graph<Point>) -> Vec<usize> {
+    map.bfs(map.id(&Point::ORIGIN).unwrap())
+        .into_iter()
+        .map(|d| d.unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn furthest(regex: &str) -> usize {
+        *distances(&build_map(regex)).iter().max().unwrap()
+    }
+
+    #[test]
+    fn test_furthest() {
+        assert_eq!(furthest("^WNE$"), 3);
+        assert_eq!(furthest("^ENWWW(NEEE|SSE(EE|N))$"), 10);
+        assert_eq!(furthest("^ENNWSWW(NEWS|)SSSEEN(WNSE|)EE(SWEN|)NNN$"), 18);
+        assert_eq!(
+            furthest("^ESSWWN(E|NNENN(EESS(WNSE|)SSS|WWWSSSSE(SW|NNNE)))$"),
+            23
+        );
+        assert_eq!(
+            furthest("^WSSEESWWWNW(S|NENNEEEENN(ESSSSW(NWSW|SSEN)|WSWWN(E|WWS(E|SS))))$"),
+            31
+        );
+    }
+
+    #[test]
+    fn test_distances() {
+        let distances = distances(&build_map("^ENWWW(NEEE|SSE(EE|N))$"));
+        assert_eq!(distances.len(), 16);
+        assert_eq!(distances.iter().filter(|&&d| d >= 10).count(), 1);
+    }
+}
@@ -0,0 +1,78 @@
+use input;
+use std::collections::HashSet;
+use y2018::device::{parse_program, Device, Opcode, Program};
+
+pub fn answer1() {
+    let program = read_program();
+    println!("{}", halting_values(&program).next().unwrap());
+}
+
+pub fn answer2() {
+    let program = read_program();
+    println!("{}", halting_values(&program).last().unwrap());
+}
+
+fn read_program() -> Program {
+    parse_program(&input::read_to_string("data/2018/day21.txt"))
+}
+
+// Register 0 is only ever read by a single `eqrr`, which halts the program
+// when it's equal to some other register. The values that register takes
+// there, in order, are the values of register 0 which halt the program, the
+// earlier the faster. They loop eventually: the values after the loop would
+// never be reached.
+fn halting_values<'a>(program: &'a Program) -> impl Iterator<Item = u64> + 'a {
+    let (check, other) = program
+        .instructions
+        .iter()
+        .enumerate()
+        .find_map(|(i, ins)| match (ins.op, ins.a, ins.b) {
+            (Opcode::Eqrr, 0, r) | (Opcode::Eqrr, r, 0) => Some((i, r as usize)),
+            _ => None,
+        })
+        .expect("register 0 is never compared");
+    let mut device = Device::new([0; 6]);
+    let mut seen = HashSet::new();
+    std::iter::from_fn(move || {
+        while device.step(program) {
+            if device.ip == check {
+                let value = device.registers[other];
+                return if seen.insert(value) {
+                    Some(value)
+                } else {
+                    None
+                };
+            }
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_halting_values() {
+        // x = (3 * x + 1) % 8, until it's equal to register 0
+        let program = parse_program(
+            "#ip 5
+seti 0 0 3
+muli 1 3 1
+addi 1 1 1
+bani 1 7 1
+eqrr 1 0 2
+addr 2 5 5
+seti 0 0 5
+",
+        );
+        let values: Vec<u64> = halting_values(&program).collect();
+        assert_eq!(values, vec![1, 4, 5, 0]);
+
+        // each of them does halt the program
+        for &v in &values {
+            let mut device = Device::new([v, 0, 0, 0, 0, 0]);
+            device.run(&program);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use graph::Graph;
+use grid::{Grid, Point};
+use input;
+
+// the cave only matters up to that far beyond the target: going around
+// by there would be longer than going straight
+const MARGIN: usize = 100;
+const SWITCH_TIME: u64 = 7;
+
+// the tools which can't be used in a region are the ones with the same
+// number as its type: rocky (0), wet (1) and narrow (2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Tool {
+    Neither = 0,
+    Torch = 1,
+    ClimbingGear = 2,
+}
+
+const TOOLS: [Tool; 3] = [Tool::Neither, Tool::Torch, Tool::ClimbingGear];
+
+struct Cave {
+    // 0, 1 or 2 for rocky, wet or narrow
+    types: Grid<usize>,
+    target: Point,
+}
+
+pub fn answer1() {
+    println!("{}", read_cave().risk_level());
+}
+
+pub fn answer2() {
+    println!("{}", read_cave().rescue_time());
+}
+
+fn read_cave() -> Cave {
+    let text = input::read_to_string("data/2018/day22.txt");
+    let mut lines = text.lines();
+    let depth = lines
+        .next()
+        .and_then(|l| l.strip_prefix("depth: "))
+        .expect("missing depth");
+    let target = lines
+        .next()
+        .and_then(|l| l.strip_prefix("target: "))
+        .expect("missing target");
+    let mut coords = target.trim().split(',').map(|c| c.parse().unwrap());
+    Cave::new(
+        depth.trim().parse().unwrap(),
+        Point::new(coords.next().unwrap(), coords.next().unwrap()),
+    )
+}
+
+impl Cave {
+    fn new(depth: usize, target: Point) -> Cave {
+        let width = target.x as usize + 1 + MARGIN;
+        let height = target.y as usize + 1 + MARGIN;
+        let mut erosion = Grid::new(width, height, 0);
+        for p in erosion.points() {
+            let geologic_index = if p == target {
+                0
+            } else if p.y == 0 {
+                p.x as usize * 16807
+            } else if p.x == 0 {
+                p.y as usize * 48271
+            } else {
+                erosion[p - Point::new(1, 0)] * erosion[p - Point::new(0, 1)]
+            };
+            erosion[p] = (geologic_index + depth) % 20183;
+        }
+        Cave {
+            types: erosion.map(|e| e % 3),
+            target,
+        }
+    }
+
+    fn risk_level(&self) -> usize {
+        self.types
+            .iter()
+            .filter(|(p, _)| p.x <= self.target.x && p.y <= self.target.y)
+            .map(|(_, t)| t)
+            .sum()
+    }
+
+    // the positions with each tool that can be used there, moving to a
+    // neighbour without changing tools or switching tools in place
+    fn map(&self) -> Graph<(Point, Tool)> {
+        let mut map = Graph::undirected();
+        let usable = |p: Point, tool: Tool| tool as usize != self.types[p];
+        for p in self.types.points() {
+            let tools: Vec<Tool> = TOOLS.iter().cloned().filter(|&t| usable(p, t)).collect();
+            map.add_weighted_edge((p, tools[0]), (p, tools[1]), SWITCH_TIME);
+            for &n in [p + Point::new(1, 0), p + Point::new(0, 1)].iter() {
+                if self.types.get(n).is_none() {
+                    continue;
+                }
+                for &tool in tools.iter().filter(|&&t| usable(n, t)) {
+                    map.add_weighted_edge((p, tool), (n, tool), 1);
+                }
+            }
+        }
+        map
+    }
+
+    // starting and ending with the torch
+    fn rescue_time(&self) -> u64 {
+        let map = self.map();
+        let start = map.id(&(Point::ORIGIN, Tool::Torch)).unwrap();
+        let goal = map.id(&(self.target, Tool::Torch)).unwrap();
+        map.shortest_path(start, goal)
+            .expect("the target can't be reached")
+            .0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cave() {
+        let cave = Cave::new(510, Point::new(10, 10));
+        let top_left = cave.types.render(|t| ['.', '=', '|'][*t]);
+        assert!(top_left.starts_with(".=.|=.|.|=.|=|=."));
+        assert_eq!(cave.risk_level(), 114);
+        assert_eq!(cave.rescue_time(), 45);
+    }
+}
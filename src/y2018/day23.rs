@@ -0,0 +1,169 @@
+use input;
+use regex::Regex;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+type Position = [i64; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Nanobot {
+    position: Position,
+    radius: i64,
+}
+
+// a cube of `size` units along each axis, from `min` included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Cube {
+    min: Position,
+    size: i64,
+}
+
+fn distance(a: &Position, b: &Position) -> i64 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y).abs()).sum()
+}
+
+impl Cube {
+    // from the closest point of the cube
+    fn distance(&self, p: &Position) -> i64 {
+        (0..3)
+            .map(|i| {
+                let max = self.min[i] + self.size - 1;
+                (self.min[i] - p[i]).max(0) + (p[i] - max).max(0)
+            })
+            .sum()
+    }
+
+    fn in_range(&self, bots: &[Nanobot]) -> usize {
+        bots.iter()
+            .filter(|b| self.distance(&b.position) <= b.radius)
+            .count()
+    }
+
+    fn octants(&self) -> impl Iterator<Item = Cube> {
+        let (min, size) = (self.min, self.size / 2);
+        (0..8).map(move |i| Cube {
+            min: [
+                min[0] + size * (i & 1),
+                min[1] + size * (i >> 1 & 1),
+                min[2] + size * (i >> 2 & 1),
+            ],
+            size,
+        })
+    }
+}
+
+pub fn answer1() {
+    println!("{}", in_range_of_strongest(&read_bots()));
+}
+
+pub fn answer2() {
+    println!("{}", best_distance(&read_bots()));
+}
+
+fn read_bots() -> Vec<Nanobot> {
+    parse_bots(&input::read_to_string("data/2018/day23.txt"))
+}
+
+fn parse_bots(text: &str) -> Vec<Nanobot> {
+    lazy_static! {
+        static ref BOT: Regex = Regex::new(r"^pos=<(-?\d+),(-?\d+),(-?\d+)>, r=(\d+)$").unwrap();
+    }
+    text.lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .map(|line| {
+            let caps = BOT
+                .captures(line)
+                .unwrap_or_else(|| panic!("invalid nanobot {:?}", line));
+            let n = |i: usize| caps[i].parse::<i64>().unwrap();
+            Nanobot {
+                position: [n(1), n(2), n(3)],
+                radius: n(4),
+            }
+        })
+        .collect()
+}
+
+fn in_range_of_strongest(bots: &[Nanobot]) -> usize {
+    let strongest = bots.iter().max_by_key(|b| b.radius).unwrap();
+    bots.iter()
+        .filter(|b| distance(&b.position, &strongest.position) <= strongest.radius)
+        .count()
+}
+
+// Distance to the origin of the closest position in range of the most bots.
+// The space is cut in cubes, the most promising one split first: the number
+// of bots in range of a cube is at least that of any of its positions, so
+// the first single position out of the queue is the best one.
+fn best_distance(bots: &[Nanobot]) -> i64 {
+    let furthest = bots
+        .iter()
+        .flat_map(|b| b.position.iter().map(move |c| c.abs() + b.radius))
+        .max()
+        .unwrap();
+    let mut size = 1;
+    while size < 2 * furthest + 1 {
+        size *= 2;
+    }
+    let space = Cube {
+        min: [-size / 2; 3],
+        size,
+    };
+
+    let origin = [0; 3];
+    let mut queue = BinaryHeap::new();
+    queue.push((
+        space.in_range(bots),
+        Reverse(space.distance(&origin)),
+        space,
+    ));
+    while let Some((_, Reverse(d), cube)) = queue.pop() {
+        if cube.size == 1 {
+            return d;
+        }
+        for octant in cube.octants() {
+            queue.push((
+                octant.in_range(bots),
+                Reverse(octant.distance(&origin)),
+                octant,
+            ));
+        }
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_in_range_of_strongest() {
+        let bots = parse_bots(
+            "pos=<0,0,0>, r=4
+pos=<1,0,0>, r=1
+pos=<4,0,0>, r=3
+pos=<0,2,0>, r=1
+pos=<0,5,0>, r=3
+pos=<0,0,3>, r=1
+pos=<1,1,1>, r=1
+pos=<1,1,2>, r=1
+pos=<1,3,1>, r=1
+",
+        );
+        assert_eq!(in_range_of_strongest(&bots), 7);
+    }
+
+    #[test]
+    fn test_best_distance() {
+        let bots = parse_bots(
+            "pos=<10,12,12>, r=2
+pos=<12,14,12>, r=2
+pos=<16,12,12>, r=4
+pos=<14,14,14>, r=6
+pos=<50,50,50>, r=200
+pos=<10,10,10>, r=5
+",
+        );
+        assert_eq!(best_distance(&bots), 36);
+    }
+}
@@ -0,0 +1,201 @@
+use input;
+use regex::Regex;
+use std::cmp::Reverse;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Army {
+    ImmuneSystem,
+    Infection,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Group {
+    army: Army,
+    units: u64,
+    hit_points: u64,
+    weaknesses: Vec<String>,
+    immunities: Vec<String>,
+    damage: u64,
+    attack: String,
+    initiative: u64,
+}
+
+impl Group {
+    fn effective_power(&self) -> u64 {
+        self.units * self.damage
+    }
+
+    fn damage_to(&self, other: &Group) -> u64 {
+        if other.immunities.contains(&self.attack) {
+            0
+        } else if other.weaknesses.contains(&self.attack) {
+            2 * self.effective_power()
+        } else {
+            self.effective_power()
+        }
+    }
+}
+
+pub fn answer1() {
+    let (_, units) = fight(read_groups()).expect("the fight never ends");
+    println!("{}", units);
+}
+
+pub fn answer2() {
+    println!("{}", smallest_boost(&read_groups()).1);
+}
+
+fn read_groups() -> Vec<Group> {
+    parse_groups(&input::read_to_string("data/2018/day24.txt"))
+}
+
+fn parse_groups(text: &str) -> Vec<Group> {
+    lazy_static! {
+        static ref GROUP: Regex = Regex::new(
+            r"^(\d+) units each with (\d+) hit points (?:\((.*)\) )?with an attack that does (\d+) (\w+) damage at initiative (\d+)$"
+        )
+        .unwrap();
+    }
+    let mut army = None;
+    let mut groups = Vec::new();
+    for line in text.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+        match line {
+            "Immune System:" => army = Some(Army::ImmuneSystem),
+            "Infection:" => army = Some(Army::Infection),
+            _ => {
+                let caps = GROUP
+                    .captures(line)
+                    .unwrap_or_else(|| panic!("invalid group {:?}", line));
+                let n = |i: usize| caps[i].parse::<u64>().unwrap();
+                // "weak to fire, cold; immune to slashing", in any order
+                let mut weaknesses = Vec::new();
+                let mut immunities = Vec::new();
+                for part in caps.get(3).map_or("", |m| m.as_str()).split("; ") {
+                    let (list, types) = if let Some(t) = part.strip_prefix("weak to ") {
+                        (&mut weaknesses, t)
+                    } else if let Some(t) = part.strip_prefix("immune to ") {
+                        (&mut immunities, t)
+                    } else {
+                        continue;
+                    };
+                    list.extend(types.split(", ").map(|t| t.to_string()));
+                }
+                groups.push(Group {
+                    army: army.expect("group outside of an army"),
+                    units: n(1),
+                    hit_points: n(2),
+                    weaknesses,
+                    immunities,
+                    damage: n(4),
+                    attack: caps[5].to_string(),
+                    initiative: n(6),
+                });
+            }
+        }
+    }
+    groups
+}
+
+// the target of each group, if any
+fn select_targets(groups: &[Group]) -> Vec<Option<usize>> {
+    let mut order: Vec<usize> = (0..groups.len()).collect();
+    order.sort_by_key(|&i| Reverse((groups[i].effective_power(), groups[i].initiative)));
+    let mut targets = vec![None; groups.len()];
+    let mut taken = vec![false; groups.len()];
+    for i in order {
+        let attacker = &groups[i];
+        let target = (0..groups.len())
+            .filter(|&j| !taken[j] && groups[j].army != attacker.army)
+            .filter(|&j| attacker.damage_to(&groups[j]) > 0)
+            .max_by_key(|&j| {
+                let g = &groups[j];
+                (attacker.damage_to(g), g.effective_power(), g.initiative)
+            });
+        if let Some(j) = target {
+            taken[j] = true;
+            targets[i] = Some(j);
+        }
+    }
+    targets
+}
+
+// The winning army and its units left, None if the fight reaches a point
+// where no unit dies anymore.
+fn fight(groups: Vec<Group>) -> Option<(Army, u64)> {
+    let mut groups = groups;
+    loop {
+        let army = groups[0].army;
+        if groups.iter().all(|g| g.army == army) {
+            return Some((army, groups.iter().map(|g| g.units).sum()));
+        }
+
+        let targets = select_targets(&groups);
+        let mut order: Vec<usize> = (0..groups.len()).collect();
+        order.sort_by_key(|&i| Reverse(groups[i].initiative));
+        let mut killed = 0;
+        for i in order {
+            if let Some(j) = targets[i] {
+                let dead =
+                    (groups[i].damage_to(&groups[j]) / groups[j].hit_points).min(groups[j].units);
+                groups[j].units -= dead;
+                killed += dead;
+            }
+        }
+        if killed == 0 {
+            return None;
+        }
+        groups.retain(|g| g.units > 0);
+    }
+}
+
+// the smallest boost which lets the immune system win, and its units left
+fn smallest_boost(groups: &[Group]) -> (u64, u64) {
+    (1..)
+        .find_map(|boost| {
+            let boosted = groups
+                .iter()
+                .map(|g| match g.army {
+                    Army::ImmuneSystem => Group {
+                        damage: g.damage + boost,
+                        ..g.clone()
+                    },
+                    Army::Infection => g.clone(),
+                })
+                .collect();
+            match fight(boosted) {
+                Some((Army::ImmuneSystem, units)) => Some((boost, units)),
+                _ => None,
+            }
+        })
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "Immune System:
+17 units each with 5390 hit points (weak to radiation, bludgeoning) with an attack that does 4507 fire damage at initiative 2
+989 units each with 1274 hit points (immune to fire; weak to bludgeoning, slashing) with an attack that does 25 slashing damage at initiative 3
+
+Infection:
+801 units each with 4706 hit points (weak to radiation) with an attack that does 116 bludgeoning damage at initiative 1
+4485 units each with 2961 hit points (immune to radiation; weak to fire, cold) with an attack that does 12 slashing damage at initiative 4
+";
+
+    #[test]
+    fn test_parse_groups() {
+        let groups = parse_groups(EXAMPLE);
+        assert_eq!(groups.len(), 4);
+        assert_eq!(groups[1].immunities, vec!["fire"]);
+        assert_eq!(groups[1].weaknesses, vec!["bludgeoning", "slashing"]);
+        assert_eq!(groups[3].army, Army::Infection);
+        assert_eq!(groups[3].effective_power(), 4485 * 12);
+    }
+
+    #[test]
+    fn test_fight() {
+        assert_eq!(fight(parse_groups(EXAMPLE)), Some((Army::Infection, 5216)));
+        assert_eq!(smallest_boost(&parse_groups(EXAMPLE)), (1570, 51));
+    }
+}
@@ -0,0 +1,91 @@
+use input;
+
+type Point4 = [i32; 4];
+
+const CLOSE: i32 = 3;
+
+pub fn answer1() {
+    let text = input::read_to_string("data/2018/day25.txt");
+    println!("{}", constellations(&parse_points(&text)));
+}
+
+fn parse_points(text: &str) -> Vec<Point4> {
+    text.lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .map(|line| {
+            let mut point = [0; 4];
+            for (c, v) in point.iter_mut().zip(line.split(',')) {
+                *c = v.trim().parse().unwrap();
+            }
+            point
+        })
+        .collect()
+}
+
+fn distance(a: &Point4, b: &Point4) -> i32 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y).abs()).sum()
+}
+
+// union find, each point pointing towards the first point of its
+// constellation
+fn constellations(points: &[Point4]) -> usize {
+    fn root(parents: &mut [usize], i: usize) -> usize {
+        let mut i = i;
+        while parents[i] != i {
+            parents[i] = parents[parents[i]];
+            i = parents[i];
+        }
+        i
+    }
+
+    let mut parents: Vec<usize> = (0..points.len()).collect();
+    let mut count = points.len();
+    for i in 0..points.len() {
+        for j in 0..i {
+            if distance(&points[i], &points[j]) <= CLOSE {
+                let (a, b) = (root(&mut parents, i), root(&mut parents, j));
+                if a != b {
+                    parents[a.max(b)] = a.min(b);
+                    count -= 1;
+                }
+            }
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_constellations() {
+        let count = |text| constellations(&parse_points(text));
+        assert_eq!(
+            count("0,0,0,0\n3,0,0,0\n0,3,0,0\n0,0,3,0\n0,0,0,3\n0,0,0,6\n9,0,0,0\n12,0,0,0\n"),
+            2
+        );
+        assert_eq!(
+            count(
+                "-1,2,2,0\n0,0,2,-2\n0,0,0,-2\n-1,2,0,0\n-2,-2,-2,2\n3,0,2,-1\n-1,3,2,2\n\
+                 -1,0,-1,0\n0,2,1,-2\n3,0,0,0\n"
+            ),
+            4
+        );
+        assert_eq!(
+            count(
+                "1,-1,0,1\n2,0,-1,0\n3,2,-1,0\n0,0,3,1\n0,0,-1,-1\n2,3,-2,0\n-2,2,0,0\n\
+                 2,-2,0,-1\n1,-1,0,-1\n3,2,0,2\n"
+            ),
+            3
+        );
+        assert_eq!(
+            count(
+                "1,-1,-1,-2\n-2,-2,0,1\n0,2,1,3\n-2,3,-2,1\n0,2,3,-2\n-1,-1,1,-2\n\
+                 0,-2,-1,0\n-2,2,3,-1\n1,2,2,0\n-1,-2,0,-2\n"
+            ),
+            8
+        );
+    }
+}
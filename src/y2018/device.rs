@@ -0,0 +1,207 @@
+// The wrist device from days 16, 19 and 21: six registers and sixteen
+// opcodes, each taking two inputs `a` and `b` and writing to register `c`.
+// Depending on the opcode, `a` and `b` are register numbers ("r") or values
+// ("i").
+
+use std::convert::TryFrom;
+
+pub type Registers = [u64; 6];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Opcode {
+    Addr,
+    Addi,
+    Mulr,
+    Muli,
+    Banr,
+    Bani,
+    Borr,
+    Bori,
+    Setr,
+    Seti,
+    Gtir,
+    Gtri,
+    Gtrr,
+    Eqir,
+    Eqri,
+    Eqrr,
+}
+
+use self::Opcode::*;
+
+impl Opcode {
+    pub const ALL: [Opcode; 16] = [
+        Addr, Addi, Mulr, Muli, Banr, Bani, Borr, Bori, Setr, Seti, Gtir, Gtri, Gtrr, Eqir, Eqri,
+        Eqrr,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Addr => "addr",
+            Addi => "addi",
+            Mulr => "mulr",
+            Muli => "muli",
+            Banr => "banr",
+            Bani => "bani",
+            Borr => "borr",
+            Bori => "bori",
+            Setr => "setr",
+            Seti => "seti",
+            Gtir => "gtir",
+            Gtri => "gtri",
+            Gtrr => "gtrr",
+            Eqir => "eqir",
+            Eqri => "eqri",
+            Eqrr => "eqrr",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Opcode> {
+        Opcode::ALL.iter().cloned().find(|op| op.name() == name)
+    }
+
+    // the registers after the instruction, None if it refers to a register
+    // which doesn't exist
+    pub fn execute(self, registers: &Registers, a: u64, b: u64, c: u64) -> Option<Registers> {
+        let reg = |r: u64| registers.get(r as usize).cloned();
+        let value = match self {
+            Addr => reg(a)?.wrapping_add(reg(b)?),
+            Addi => reg(a)?.wrapping_add(b),
+            Mulr => reg(a)?.wrapping_mul(reg(b)?),
+            Muli => reg(a)?.wrapping_mul(b),
+            Banr => reg(a)? & reg(b)?,
+            Bani => reg(a)? & b,
+            Borr => reg(a)? | reg(b)?,
+            Bori => reg(a)? | b,
+            Setr => reg(a)?,
+            Seti => a,
+            Gtir => (a > reg(b)?) as u64,
+            Gtri => (reg(a)? > b) as u64,
+            Gtrr => (reg(a)? > reg(b)?) as u64,
+            Eqir => (a == reg(b)?) as u64,
+            Eqri => (reg(a)? == b) as u64,
+            Eqrr => (reg(a)? == reg(b)?) as u64,
+        };
+        let mut result = *registers;
+        *result.get_mut(c as usize)? = value;
+        Some(result)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub op: Opcode,
+    pub a: u64,
+    pub b: u64,
+    pub c: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    // the register the instruction pointer is bound to, if any
+    pub ip_register: Option<usize>,
+    pub instructions: Vec<Instruction>,
+}
+
+// "addi 1 2 3"
+pub fn parse_instruction(line: &str) -> Instruction {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    assert_eq!(parts.len(), 4, "invalid instruction {:?}", line);
+    let n = |i: usize| {
+        parts[i]
+            .parse()
+            .unwrap_or_else(|_| panic!("invalid instruction {:?}", line))
+    };
+    Instruction {
+        op: Opcode::from_name(parts[0]).unwrap_or_else(|| panic!("unknown opcode {:?}", line)),
+        a: n(1),
+        b: n(2),
+        c: n(3),
+    }
+}
+
+// an optional "#ip 3" line, then one instruction per line
+pub fn parse_program(text: &str) -> Program {
+    let mut ip_register = None;
+    let mut instructions = Vec::new();
+    for line in text.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+        match line.strip_prefix("#ip ") {
+            Some(r) => ip_register = Some(r.parse().expect("invalid #ip line")),
+            None => instructions.push(parse_instruction(line)),
+        }
+    }
+    Program {
+        ip_register,
+        instructions,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Device {
+    pub registers: Registers,
+    pub ip: usize,
+}
+
+impl Device {
+    pub fn new(registers: Registers) -> Device {
+        Device { registers, ip: 0 }
+    }
+
+    // run one instruction, false if the program has halted
+    pub fn step(&mut self, program: &Program) -> bool {
+        let instruction = match program.instructions.get(self.ip) {
+            Some(i) => i,
+            None => return false,
+        };
+        if let Some(r) = program.ip_register {
+            self.registers[r] = self.ip as u64;
+        }
+        let Instruction { op, a, b, c } = *instruction;
+        self.registers = op
+            .execute(&self.registers, a, b, c)
+            .unwrap_or_else(|| panic!("invalid register in {:?}", instruction));
+        let next = match program.ip_register {
+            Some(r) => self.registers[r].wrapping_add(1),
+            None => self.ip as u64 + 1,
+        };
+        // anything out of range halts the program
+        self.ip = usize::try_from(next).unwrap_or(usize::MAX);
+        self.ip < program.instructions.len()
+    }
+
+    pub fn run(&mut self, program: &Program) {
+        while self.step(program) {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_execute() {
+        // the sample from day 16, which behaves like mulr, addi and seti
+        let before = [3, 2, 1, 1, 0, 0];
+        let after = [3, 2, 2, 1, 0, 0];
+        let matching: Vec<&str> = Opcode::ALL
+            .iter()
+            .filter(|op| op.execute(&before, 2, 1, 2) == Some(after))
+            .map(|op| op.name())
+            .collect();
+        assert_eq!(matching, vec!["addi", "mulr", "seti"]);
+        assert_eq!(Addr.execute(&before, 7, 0, 0), None);
+        assert_eq!(Seti.execute(&before, 7, 0, 6), None);
+    }
+
+    #[test]
+    fn test_run() {
+        // the example from day 19
+        let program = parse_program(
+            "#ip 0\nseti 5 0 1\nseti 6 0 2\naddi 0 1 0\naddr 1 2 3\nsetr 1 0 0\nseti 8 0 4\nseti 9 0 5\n",
+        );
+        assert_eq!(program.ip_register, Some(0));
+        let mut device = Device::new([0; 6]);
+        device.run(&program);
+        assert_eq!(device.registers, [6, 5, 6, 0, 0, 9]);
+    }
+}
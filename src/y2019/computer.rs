@@ -1,7 +1,32 @@
-use std::fs::File;
-use std::io::Read;
+use input;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+
+mod disasm;
+// called by the fuzz targets in fuzz/
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzz;
+pub mod history;
+pub mod instruction_set;
+pub mod profile;
+pub mod symbolic;
+
+use self::instruction_set::{Effect, InstructionSet, OpcodeDef, Role, MAX_PARAMS};
+
+lazy_static! {
+    static ref DEFAULT_INSTRUCTION_SET: Arc<InstructionSet> = Arc::new(InstructionSet::default());
+}
+
+// programs can grow their memory by writing past the end of it, cap that so a
+// buggy (or random) program gets an error instead of exhausting the RAM.
+pub const DEFAULT_MEMORY_LIMIT: usize = 1 << 24;
+
+// same for programs stuck in a loop: this is far more instructions than the
+// puzzles need, and about ten seconds of a release build
+pub const DEFAULT_STEP_LIMIT: u64 = 1 << 28;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Computer {
     pub instruction_idx: usize,
     pub codes: Vec<i64>,
@@ -9,14 +34,38 @@ pub struct Computer {
     input_idx: usize,
     pub outputs: Vec<i64>,
     relative_base: i64,
+    memory_limit: usize,
+    instruction_set: Arc<InstructionSet>,
+    // number of instructions executed so far, and the same broken down by opcode
+    steps: u64,
+    opcode_counts: [u64; 100],
+    step_limit: Option<u64>,
+    deadline: Option<Instant>,
+    profile: Option<Box<profile::Profile>>,
+    history: Option<Box<history::History>>,
+    // cache of decoded instructions indexed by address, only filled by
+    // `run_predecoded` and invalidated when the program writes over itself.
+    // It covers the memory as it was when first filled, so that a program
+    // jumping far away can't make it grow up to the memory limit.
+    decoded: Vec<Option<Instruction>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ComputerError {
     UnknownCode { code: i64 },
     MissingInput,
+    InvalidMode { code: i64, nth_param: usize },
+    NegativeAddress { addr: i64 },
+    OutOfMemory { addr: i64, limit: usize },
+    Overflow { code: i64 },
+    StepLimitExceeded { steps: u64 },
+    DeadlineExceeded { steps: u64 },
+    HistoryUnavailable { step: u64 },
+    // symbolic execution needs a concrete value to go on
+    SymbolicValue { addr: usize },
 }
 
+#[derive(Debug, PartialEq, Eq)]
 enum StepResult {
     Done,
     AwaitInput,
@@ -24,6 +73,7 @@ enum StepResult {
     Continue,
 }
 
+#[derive(Debug, PartialEq, Eq)]
 pub enum RunResult {
     Done,
     AwaitInput,
@@ -37,15 +87,40 @@ pub enum Mode {
     Relative,
 }
 
+// a parameter as it appears in memory, before being resolved against the
+// memory and the relative base
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Param {
+    Position(i64),
+    Immediate(i64),
+    Relative(i64),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Instruction {
+    code: i64,
+    op: OpcodeDef,
+    params: [Param; MAX_PARAMS],
+}
+
 impl Computer {
     pub fn new(codes: Vec<i64>) -> Computer {
         Computer {
             instruction_idx: 0,
-            codes: codes,
+            codes,
             inputs: Vec::new(),
             input_idx: 0,
             outputs: Vec::new(),
             relative_base: 0,
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            instruction_set: Arc::clone(&DEFAULT_INSTRUCTION_SET),
+            steps: 0,
+            opcode_counts: [0; 100],
+            step_limit: Some(DEFAULT_STEP_LIMIT),
+            deadline: None,
+            profile: None,
+            history: None,
+            decoded: Vec::new(),
         }
     }
 
@@ -53,9 +128,52 @@ impl Computer {
         self.inputs = inputs;
     }
 
+    pub fn with_memory_limit(&mut self, memory_limit: usize) {
+        self.memory_limit = memory_limit;
+    }
+
+    // restrict or extend the opcodes the program can use
+    pub fn with_instruction_set(&mut self, instruction_set: InstructionSet) {
+        self.instruction_set = Arc::new(instruction_set);
+        self.decoded.clear();
+    }
+
+    // stop with an error once that many instructions have been executed,
+    // `DEFAULT_STEP_LIMIT` unless set
+    pub fn with_step_limit(&mut self, step_limit: u64) {
+        self.step_limit = Some(step_limit);
+    }
+
+    pub fn with_deadline(&mut self, deadline: Instant) {
+        self.deadline = Some(deadline);
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    // start recording where the execution time goes, see `profile::Profile`
+    pub fn enable_profiling(&mut self) {
+        self.profile = Some(Box::new(profile::Profile::new(self.block_leaders())));
+    }
+
+    pub fn profile(&self) -> Option<&profile::Profile> {
+        self.profile.as_deref()
+    }
+
+    // how many times each opcode has been executed, ordered by opcode
+    pub fn opcode_stats(&self) -> Vec<(i64, u64)> {
+        self.opcode_counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(opcode, count)| (opcode as i64, *count))
+            .collect()
+    }
+
     pub fn run(&mut self) -> Result<RunResult, ComputerError> {
         loop {
-            let step_result = self.run_step()?;
+            let step_result = self.counted_step(false)?;
             match step_result {
                 StepResult::Done => break Ok(RunResult::Done),
                 StepResult::AwaitInput => break Ok(RunResult::AwaitInput),
@@ -67,7 +185,7 @@ impl Computer {
 
     pub fn run_until_halt(&mut self) -> Result<RunResult, ComputerError> {
         loop {
-            let step_result = self.run_step()?;
+            let step_result = self.counted_step(false)?;
             match step_result {
                 StepResult::Done => break Ok(RunResult::Done),
                 StepResult::AwaitInput => break Err(ComputerError::MissingInput),
@@ -82,164 +200,374 @@ impl Computer {
         self.run()
     }
 
-    fn run_step(&mut self) -> Result<StepResult, ComputerError> {
-        let code = self.get_at_mem(self.instruction_idx);
-        let opcode = code % 100;
-        // println!("run step with code: {}", code);
-
-        match opcode {
-            1 => self
-                .binary_op(code, &|x, y| x + y)
-                .map(|_| StepResult::Continue),
-
-            2 => self
-                .binary_op(code, &|x, y| x * y)
-                .map(|_| StepResult::Continue),
-
-            3 => {
-                let addr = self.get_val(code, 1) as usize;
-                // let addr = self.codes[self.instruction_idx + 1] as usize;
-                if self.input_idx >= self.inputs.len() {
-                    Ok(StepResult::AwaitInput)
-                } else {
-                    self.set_at_mem(addr, self.inputs[self.input_idx]);
-                    self.input_idx += 1;
-                    self.instruction_idx += 2;
-                    Ok(StepResult::Continue)
-                }
+    // queue inputs for the next runs, after the ones not read yet
+    pub fn push_inputs(&mut self, inputs: &[i64]) {
+        self.inputs.extend_from_slice(inputs);
+    }
+
+    // run until the program halts or needs an input it doesn't have, and
+    // return what it output on the way
+    pub fn run_until_blocked(&mut self) -> Result<(Vec<i64>, RunResult), ComputerError> {
+        let start = self.outputs.len();
+        loop {
+            match self.run_predecoded()? {
+                RunResult::Output(_) => continue,
+                result => return Ok((self.outputs[start..].to_vec(), result)),
+            }
+        }
+    }
+
+    // same as `run`, but instructions are decoded once and then executed from
+    // the cache, which avoids re-parsing the modes in tight loops
+    pub fn run_predecoded(&mut self) -> Result<RunResult, ComputerError> {
+        loop {
+            let step_result = self.counted_step(true)?;
+            match step_result {
+                StepResult::Done => break Ok(RunResult::Done),
+                StepResult::AwaitInput => break Ok(RunResult::AwaitInput),
+                StepResult::Continue => continue,
+                StepResult::Output(x) => break Ok(RunResult::Output(x)),
+            }
+        }
+    }
+
+    // execute one instruction while enforcing the limits and keeping the counters
+    fn counted_step(&mut self, predecoded: bool) -> Result<StepResult, ComputerError> {
+        let result = self.limited_step(predecoded);
+        if let Some(profile) = self.profile.as_mut() {
+            if result != Ok(StepResult::Continue) {
+                profile.pause();
             }
+        }
+        result
+    }
 
-            4 => {
-                let output_val = self.get_val(code, 1);
-                self.outputs.push(output_val);
-                self.instruction_idx += 2;
-                Ok(StepResult::Output(output_val))
+    fn limited_step(&mut self, predecoded: bool) -> Result<StepResult, ComputerError> {
+        if let Some(limit) = self.step_limit {
+            if self.steps >= limit {
+                return Err(ComputerError::StepLimitExceeded { steps: self.steps });
+            }
+        }
+        // reading the clock at every step would dominate the running time
+        if let Some(deadline) = self.deadline {
+            if self.steps.is_multiple_of(1024) && Instant::now() >= deadline {
+                return Err(ComputerError::DeadlineExceeded { steps: self.steps });
             }
+        }
 
-            5 => {
-                let val1 = self.get_val(code, 1);
-                if val1 != 0 {
-                    self.instruction_idx = self.get_val(code, 2) as usize;
-                } else {
-                    self.instruction_idx += 3;
-                }
-                Ok(StepResult::Continue)
+        let ip = self.instruction_idx;
+        let code = self.get_at_mem(ip)?;
+        self.begin_step(code % 100);
+        let result = if predecoded {
+            self.step_predecoded()
+        } else {
+            self.run_step()
+        };
+        let result = match result {
+            Ok(StepResult::AwaitInput) => {
+                self.discard_step();
+                StepResult::AwaitInput
+            }
+            Ok(result) => result,
+            Err(err) => {
+                self.discard_step();
+                return Err(err);
             }
+        };
+        if result != StepResult::AwaitInput {
+            self.steps += 1;
+            self.opcode_counts[(code % 100) as usize] += 1;
+        }
+        if let Some(profile) = self.profile.as_mut() {
+            if result != StepResult::AwaitInput {
+                let op = self.instruction_set.get(code % 100);
+                profile.record(ip, op.is_some_and(|op| op.jump_target.is_some()));
+            }
+        }
+        Ok(result)
+    }
+
+    fn run_step(&mut self) -> Result<StepResult, ComputerError> {
+        let code = self.get_at_mem(self.instruction_idx)?;
+        let op = *self
+            .instruction_set
+            .get(code % 100)
+            .ok_or(ComputerError::UnknownCode { code: code % 100 })?;
+
+        let mut args = [0; MAX_PARAMS];
+        for (i, role) in op.params().iter().enumerate() {
+            args[i] = match role {
+                Role::Read => self.get_val(code, i + 1)?,
+                Role::Write => self.get_addr(code, i + 1)? as i64,
+            };
+        }
+        let effect = (op.handler)(code, &args[..op.params().len()])?;
+        self.apply(code, op.size(), effect)
+    }
+
+    fn step_predecoded(&mut self) -> Result<StepResult, ComputerError> {
+        let ip = self.instruction_idx;
+        if self.decoded.is_empty() {
+            self.decoded = vec![None; self.codes.len()];
+        }
+        let instruction = match self.decoded.get(ip) {
+            Some(Some(instruction)) => *instruction,
+            Some(None) => {
+                let instruction = self.decode(ip)?;
+                self.decoded[ip] = Some(instruction);
+                instruction
+            }
+            // written at runtime past the end of the program, decoded each time
+            None => self.decode(ip)?,
+        };
+        self.execute(instruction)
+    }
 
-            6 => {
-                let val1 = self.get_val(code, 1);
-                if val1 == 0 {
-                    self.instruction_idx = self.get_val(code, 2) as usize;
-                } else {
-                    self.instruction_idx += 3;
+    fn decode(&self, ip: usize) -> Result<Instruction, ComputerError> {
+        let code = self.get_at_mem(ip)?;
+        let op = *self
+            .instruction_set
+            .get(code % 100)
+            .ok_or(ComputerError::UnknownCode { code: code % 100 })?;
+
+        let mut params = [Param::Immediate(0); MAX_PARAMS];
+        for (i, role) in op.params().iter().enumerate() {
+            let nth_param = i + 1;
+            let raw = self.get_at_mem(ip + nth_param)?;
+            params[i] = match get_mode(code, nth_param)? {
+                Mode::Immediate if *role == Role::Write => {
+                    return Err(ComputerError::InvalidMode { code, nth_param })
                 }
+                Mode::Position => Param::Position(raw),
+                Mode::Immediate => Param::Immediate(raw),
+                Mode::Relative => Param::Relative(raw),
+            };
+        }
+
+        Ok(Instruction { code, op, params })
+    }
+
+    fn execute(&mut self, instruction: Instruction) -> Result<StepResult, ComputerError> {
+        let Instruction { code, op, params } = instruction;
+        let mut args = [0; MAX_PARAMS];
+        for (i, role) in op.params().iter().enumerate() {
+            args[i] = match role {
+                Role::Read => self.load(code, params[i])?,
+                Role::Write => self.store_addr(code, params[i])? as i64,
+            };
+        }
+        let effect = (op.handler)(code, &args[..op.params().len()])?;
+        self.apply(code, op.size(), effect)
+    }
+
+    fn apply(
+        &mut self,
+        code: i64,
+        len: usize,
+        effect: Effect,
+    ) -> Result<StepResult, ComputerError> {
+        let next_idx = self.instruction_idx + len;
+        match effect {
+            Effect::Continue => {
+                self.instruction_idx = next_idx;
                 Ok(StepResult::Continue)
             }
-
-            7 => {
-                let val1 = self.get_val(code, 1);
-                let val2 = self.get_val(code, 2);
-                let res = if val1 < val2 { 1 } else { 0 };
-                let addr = self.get_at_mem(self.instruction_idx + 3) as usize;
-                self.codes[addr] = res;
-                self.instruction_idx += 4;
+            Effect::Write { addr, value } => {
+                let addr = self.to_addr(addr)?;
+                self.set_at_mem(addr, value)?;
+                self.instruction_idx = next_idx;
                 Ok(StepResult::Continue)
             }
-
-            8 => {
-                let val1 = self.get_val(code, 1);
-                let val2 = self.get_val(code, 2);
-                let res = if val1 == val2 { 1 } else { 0 };
-                let addr = self.get_at_mem(self.instruction_idx + 3) as usize;
-                self.set_at_mem(addr, res);
-                self.instruction_idx += 4;
+            Effect::Jump(target) => {
+                self.instruction_idx = self.to_addr(target)?;
                 Ok(StepResult::Continue)
             }
-
-            9 => {
-                let delta = self.get_val(code, 1);
-                self.relative_base += delta;
-                self.instruction_idx += 2;
-                // println!("new base {}", self.relative_base);
+            Effect::AdjustRelativeBase(delta) => {
+                self.relative_base = self
+                    .relative_base
+                    .checked_add(delta)
+                    .ok_or(ComputerError::Overflow { code })?;
+                self.instruction_idx = next_idx;
                 Ok(StepResult::Continue)
             }
+            Effect::Input { addr } => match self.inputs.get(self.input_idx) {
+                None => Ok(StepResult::AwaitInput),
+                Some(&input) => {
+                    let addr = self.to_addr(addr)?;
+                    self.set_at_mem(addr, input)?;
+                    self.input_idx += 1;
+                    self.instruction_idx = next_idx;
+                    Ok(StepResult::Continue)
+                }
+            },
+            Effect::Output(value) => {
+                self.outputs.push(value);
+                self.instruction_idx = next_idx;
+                Ok(StepResult::Output(value))
+            }
+            Effect::Halt => Ok(StepResult::Done),
+        }
+    }
+
+    fn load(&self, code: i64, param: Param) -> Result<i64, ComputerError> {
+        match param {
+            Param::Immediate(val) => Ok(val),
+            Param::Position(addr) => self.get_at_mem(self.to_addr(addr)?),
+            Param::Relative(offset) => {
+                let addr = self.relative_addr(code, offset)?;
+                self.get_at_mem(addr)
+            }
+        }
+    }
 
-            99 => Ok(StepResult::Done),
+    fn store_addr(&self, code: i64, param: Param) -> Result<usize, ComputerError> {
+        match param {
+            Param::Position(addr) => self.to_addr(addr),
+            Param::Relative(offset) => self.relative_addr(code, offset),
+            // rejected when decoding, writes are never in immediate mode
+            Param::Immediate(_) => Err(ComputerError::InvalidMode { code, nth_param: 0 }),
+        }
+    }
 
-            c => Err(ComputerError::UnknownCode { code: c }),
+    fn to_addr(&self, addr: i64) -> Result<usize, ComputerError> {
+        if addr < 0 {
+            Err(ComputerError::NegativeAddress { addr })
+        } else if addr as u64 >= self.memory_limit as u64 {
+            Err(ComputerError::OutOfMemory {
+                addr,
+                limit: self.memory_limit,
+            })
+        } else {
+            Ok(addr as usize)
         }
     }
 
-    fn get_at_mem(&mut self, i: usize) -> i64 {
-        if i >= self.codes.len() {
-            // println!("get and resizing to {}", i+4);
-            self.codes.resize(i + 4, 0);
+    fn relative_addr(&self, code: i64, offset: i64) -> Result<usize, ComputerError> {
+        let addr = offset
+            .checked_add(self.relative_base)
+            .ok_or(ComputerError::Overflow { code })?;
+        self.to_addr(addr)
+    }
+
+    fn get_at_mem(&self, i: usize) -> Result<i64, ComputerError> {
+        if i >= self.memory_limit {
+            return Err(ComputerError::OutOfMemory {
+                addr: i as i64,
+                limit: self.memory_limit,
+            });
         }
-        self.codes[i]
+        Ok(self.codes.get(i).cloned().unwrap_or(0))
     }
 
-    fn set_at_mem(&mut self, i: usize, val: i64) {
+    fn set_at_mem(&mut self, i: usize, val: i64) -> Result<(), ComputerError> {
+        if i >= self.memory_limit {
+            return Err(ComputerError::OutOfMemory {
+                addr: i as i64,
+                limit: self.memory_limit,
+            });
+        }
+        if let Some(history) = self.history.as_mut() {
+            history.record_write(i, self.codes.get(i).cloned().unwrap_or(0));
+        }
         if i >= self.codes.len() {
-            // println!("set and resizing to {}", i+4);
-            self.codes.resize(i + 4, 0);
+            self.codes.resize(i + 1, 0);
         }
         self.codes[i] = val;
+        self.invalidate_decoded(i);
+        Ok(())
     }
 
-    fn get_val(&mut self, code: i64, nth_param: usize) -> i64 {
-        let mode = get_mode(code, nth_param);
-        // println!("getting val with mode {} and pos {}", code, nth_param);
-        match mode {
-            Mode::Position => {
-                let i = self.instruction_idx + nth_param;
-                let addr = self.get_at_mem(i);
-                self.get_at_mem(addr as usize)
-            }
-            Mode::Immediate => self.get_at_mem(self.instruction_idx + nth_param),
-            Mode::Relative => {
-                let i = self.instruction_idx + nth_param;
-                // println!(
-                //     "get val relative for code {} and param {} with base {} and i={}",
-                //     code, nth_param, self.relative_base, i
-                // );
-                let addr = self.get_at_mem(i);
-                self.get_at_mem((addr + self.relative_base) as usize)
+    // any decoded instruction starting up to MAX_PARAMS cells before the
+    // written one may have been overwritten
+    fn invalidate_decoded(&mut self, i: usize) {
+        if !self.decoded.is_empty() {
+            let start = i.saturating_sub(MAX_PARAMS);
+            for slot in self.decoded.iter_mut().take(i + 1).skip(start) {
+                *slot = None;
             }
         }
     }
 
-    fn binary_op(&mut self, code: i64, f: &dyn Fn(i64, i64) -> i64) -> Result<(), ComputerError> {
-        let val1 = self.get_val(code, 1);
-        let val2 = self.get_val(code, 2);
-        let idx_res = self.get_at_mem(self.instruction_idx + 3) as usize;
-        let result = f(val1, val2);
-        self.set_at_mem(idx_res, result);
-        self.instruction_idx = self.instruction_idx + 4;
-        Ok(())
+    fn get_val(&self, code: i64, nth_param: usize) -> Result<i64, ComputerError> {
+        let mode = get_mode(code, nth_param)?;
+        let raw = self.get_at_mem(self.instruction_idx + nth_param)?;
+        match mode {
+            Mode::Position => self.get_at_mem(self.to_addr(raw)?),
+            Mode::Immediate => Ok(raw),
+            Mode::Relative => self.get_at_mem(self.relative_addr(code, raw)?),
+        }
+    }
+
+    // address pointed by a parameter which is written to
+    fn get_addr(&self, code: i64, nth_param: usize) -> Result<usize, ComputerError> {
+        let mode = get_mode(code, nth_param)?;
+        let raw = self.get_at_mem(self.instruction_idx + nth_param)?;
+        match mode {
+            Mode::Position => self.to_addr(raw),
+            Mode::Immediate => Err(ComputerError::InvalidMode { code, nth_param }),
+            Mode::Relative => self.relative_addr(code, raw),
+        }
     }
 }
 
-pub fn get_mode(code: i64, nth_param: usize) -> Mode {
-    let base: usize = 10;
-    let i = (code as usize) / base.pow((nth_param + 1) as u32) % 10;
+impl fmt::Display for ComputerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ComputerError::UnknownCode { code } => write!(f, "unknown opcode {}", code),
+            ComputerError::MissingInput => write!(f, "program is waiting for a missing input"),
+            ComputerError::InvalidMode { code, nth_param } => write!(
+                f,
+                "invalid mode for parameter {} of instruction {}",
+                nth_param, code
+            ),
+            ComputerError::NegativeAddress { addr } => write!(f, "negative address {}", addr),
+            ComputerError::OutOfMemory { addr, limit } => write!(
+                f,
+                "address {} is beyond the memory limit of {} cells",
+                addr, limit
+            ),
+            ComputerError::Overflow { code } => {
+                write!(f, "arithmetic overflow in instruction {}", code)
+            }
+            ComputerError::StepLimitExceeded { steps } => {
+                write!(f, "step limit reached after {} instructions", steps)
+            }
+            ComputerError::DeadlineExceeded { steps } => {
+                write!(f, "deadline reached after {} instructions", steps)
+            }
+            ComputerError::HistoryUnavailable { step } => {
+                write!(
+                    f,
+                    "step {} is before the start of the recorded history",
+                    step
+                )
+            }
+            ComputerError::SymbolicValue { addr } => write!(
+                f,
+                "the instruction at address {} depends on an unknown value",
+                addr
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ComputerError {}
+
+pub fn get_mode(code: i64, nth_param: usize) -> Result<Mode, ComputerError> {
+    let i = code / 10_i64.pow((nth_param + 1) as u32) % 10;
     match i {
-        0 => Mode::Position,
-        1 => Mode::Immediate,
-        2 => Mode::Relative,
-        _ => panic!(
-            "Cannot get mode for code {} and nth_param {}",
-            code, nth_param
-        ),
+        0 => Ok(Mode::Position),
+        1 => Ok(Mode::Immediate),
+        2 => Ok(Mode::Relative),
+        _ => Err(ComputerError::InvalidMode { code, nth_param }),
     }
 }
 
 pub fn read_codes(file_path: &str) -> Vec<i64> {
-    let mut f = File::open(file_path).unwrap();
-    let mut buf = String::new();
-    f.read_to_string(&mut buf).unwrap();
-    buf.trim()
+    input::read_to_string(file_path)
+        .trim()
         .split(',')
-        .map(|x| i64::from_str_radix(&x, 10).unwrap())
+        .map(|x| x.parse().unwrap())
         .collect()
 }
 
@@ -249,9 +577,16 @@ mod test {
 
     #[test]
     fn test_computer_get_mode() {
-        assert_eq!(get_mode(1002, 1), Mode::Position);
-        assert_eq!(get_mode(1002, 2), Mode::Immediate);
-        assert_eq!(get_mode(1002, 3), Mode::Position);
+        assert_eq!(get_mode(1002, 1), Ok(Mode::Position));
+        assert_eq!(get_mode(1002, 2), Ok(Mode::Immediate));
+        assert_eq!(get_mode(1002, 3), Ok(Mode::Position));
+        assert_eq!(
+            get_mode(1302, 1),
+            Err(ComputerError::InvalidMode {
+                code: 1302,
+                nth_param: 1
+            })
+        );
     }
 
     #[test]
@@ -291,4 +626,163 @@ mod test {
         c.run_with_inputs(vec![10]).unwrap();
         assert_eq!(c.outputs.last().unwrap(), &1001);
     }
+
+    #[test]
+    fn test_computer_run_until_blocked() {
+        // outputs twice each input, until it reads a 0
+        let mut c = Computer::new(vec![
+            3, 15, 1006, 15, 14, 102, 2, 15, 16, 4, 16, 1105, 1, 0, 99, 0, 0,
+        ]);
+        assert_eq!(c.run_until_blocked(), Ok((vec![], RunResult::AwaitInput)));
+        c.push_inputs(&[3, 4]);
+        assert_eq!(
+            c.run_until_blocked(),
+            Ok((vec![6, 8], RunResult::AwaitInput))
+        );
+        c.push_inputs(&[5, 0, 7]);
+        assert_eq!(c.run_until_blocked(), Ok((vec![10], RunResult::Done)));
+    }
+
+    #[test]
+    fn test_computer_run_until_blocked_real_programs() {
+        // the diagnostics of day 5 waits for the system id, then outputs 0
+        // for each passing test and the diagnostic code last
+        let mut c = Computer::new(read_codes("data/2019/day05.txt"));
+        assert_eq!(c.run_until_blocked(), Ok((vec![], RunResult::AwaitInput)));
+        c.push_inputs(&[1]);
+        let (outputs, result) = c.run_until_blocked().unwrap();
+        assert_eq!(result, RunResult::Done);
+        assert_eq!(outputs.last(), Some(&10987514));
+        assert!(outputs[..outputs.len() - 1].iter().all(|&x| x == 0));
+
+        // BOOST in test mode reports the opcodes it found broken, or only
+        // its keycode when everything works
+        let mut c = Computer::new(read_codes("data/2019/day09.txt"));
+        c.push_inputs(&[1]);
+        assert_eq!(
+            c.run_until_blocked(),
+            Ok((vec![3013554615], RunResult::Done))
+        );
+    }
+
+    #[test]
+    fn test_computer_errors_instead_of_panics() {
+        let mut c = Computer::new(vec![1, -1, 0, 0, 99]);
+        assert_eq!(
+            c.run().err(),
+            Some(ComputerError::NegativeAddress { addr: -1 })
+        );
+
+        let mut c = Computer::new(vec![1101, 1, 1, 100, 99]);
+        c.with_memory_limit(50);
+        assert_eq!(
+            c.run().err(),
+            Some(ComputerError::OutOfMemory {
+                addr: 100,
+                limit: 50
+            })
+        );
+        assert!(c.codes.len() <= 50);
+
+        let mut c = Computer::new(vec![1102, i64::MAX, 2, 0, 99]);
+        assert_eq!(c.run().err(), Some(ComputerError::Overflow { code: 1102 }));
+
+        let mut c = Computer::new(vec![11101, 1, 1, 0, 99]);
+        assert_eq!(
+            c.run().err(),
+            Some(ComputerError::InvalidMode {
+                code: 11101,
+                nth_param: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_computer_step_limit() {
+        assert_eq!(Computer::new(vec![99]).step_limit, Some(DEFAULT_STEP_LIMIT));
+
+        // jumps to itself forever
+        let mut c = Computer::new(vec![1105, 1, 0]);
+        c.with_step_limit(1000);
+        assert_eq!(
+            c.run_until_halt().err(),
+            Some(ComputerError::StepLimitExceeded { steps: 1000 })
+        );
+        assert_eq!(c.steps(), 1000);
+
+        let mut c = Computer::new(vec![1105, 1, 0]);
+        c.with_step_limit(1000);
+        assert_eq!(
+            c.run_predecoded().err(),
+            Some(ComputerError::StepLimitExceeded { steps: 1000 })
+        );
+    }
+
+    #[test]
+    fn test_computer_decoded_cache_size() {
+        // writes a jump back to 12 at address 1000, then jumps there forever
+        let mut c = Computer::new(vec![
+            1101, 1105, 0, 1000, 1101, 1, 0, 1001, 1101, 12, 0, 1002, 1105, 1, 1000,
+        ]);
+        c.with_step_limit(100);
+        assert_eq!(
+            c.run_predecoded(),
+            Err(ComputerError::StepLimitExceeded { steps: 100 })
+        );
+        assert_eq!(c.codes.len(), 1003);
+        assert_eq!(c.decoded.len(), 15);
+    }
+
+    #[test]
+    fn test_computer_deadline() {
+        let mut c = Computer::new(vec![1105, 1, 0]);
+        c.with_deadline(Instant::now() + std::time::Duration::from_millis(20));
+        match c.run_until_halt() {
+            Err(ComputerError::DeadlineExceeded { steps }) => assert!(steps > 0),
+            _ => panic!("should have hit the deadline"),
+        }
+    }
+
+    #[test]
+    fn test_computer_counters() {
+        let mut c = Computer::new(vec![1101, 1, 1, 0, 4, 0, 3, 0, 99]);
+        assert_eq!(c.run(), Ok(RunResult::Output(2)));
+        // waiting for an input doesn't count as a step
+        assert_eq!(c.run(), Ok(RunResult::AwaitInput));
+        assert_eq!(c.steps(), 2);
+        c.run_with_inputs(vec![7]).unwrap();
+        assert_eq!(c.steps(), 4);
+        assert_eq!(c.opcode_stats(), vec![(1, 1), (3, 1), (4, 1), (99, 1)]);
+    }
+
+    #[test]
+    fn test_computer_predecoded_self_modifying() {
+        // increments a counter, outputs it, then rewrites the first instruction
+        // into a multiplication and loops once: the decoded addition cached for
+        // the first pass must not survive that write
+        let codes = vec![
+            1001, 30, 1, 30, 4, 30, 1101, 2, 0, 0, 1005, 31, 20, 1101, 1, 0, 31, 1105, 1, 0, 99,
+        ];
+        let mut reference = Computer::new(codes.clone());
+        reference.run_until_halt().unwrap();
+        assert_eq!(reference.outputs, vec![1, 30]);
+
+        let mut c = Computer::new(codes);
+        while let RunResult::Output(_) = c.run_predecoded().unwrap() {}
+        assert_eq!(c.outputs, reference.outputs);
+        assert_eq!(c.codes, reference.codes);
+    }
+
+    #[test]
+    fn test_computer_predecoded_day09() {
+        let codes = read_codes("data/2019/day09.txt");
+        let mut reference = Computer::new(codes.clone());
+        reference.with_input(vec![1]);
+        reference.run_until_halt().unwrap();
+
+        let mut c = Computer::new(codes);
+        c.with_input(vec![1]);
+        while let RunResult::Output(_) = c.run_predecoded().unwrap() {}
+        assert_eq!(c.outputs, reference.outputs);
+    }
 }
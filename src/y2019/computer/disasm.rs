@@ -0,0 +1,81 @@
+use std::fmt;
+
+use super::{Computer, Param};
+
+impl fmt::Display for Param {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Param::Position(addr) => write!(f, "[{}]", addr),
+            Param::Immediate(val) => write!(f, "{}", val),
+            Param::Relative(offset) if *offset < 0 => write!(f, "[rb-{}]", -offset),
+            Param::Relative(offset) => write!(f, "[rb+{}]", offset),
+        }
+    }
+}
+
+impl Computer {
+    pub fn mnemonic(&self, opcode: i64) -> &'static str {
+        self.instruction_set.get(opcode).map_or("???", |op| op.name)
+    }
+
+    // textual form of the instruction at the given address, along with its
+    // length. Cells which cannot be decoded are shown as raw data.
+    pub fn disassemble_at(&self, addr: usize) -> (String, usize) {
+        match self.decode(addr) {
+            Ok(instruction) => {
+                let op = instruction.op;
+                let params: Vec<String> = instruction.params[..op.params().len()]
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect();
+                if params.is_empty() {
+                    (op.name.to_string(), op.size())
+                } else {
+                    (format!("{} {}", op.name, params.join(", ")), op.size())
+                }
+            }
+            Err(_) => (format!("data {}", self.get_at_mem(addr).unwrap_or(0)), 1),
+        }
+    }
+
+    // linear sweep over the whole memory
+    pub fn disassemble(&self) -> Vec<(usize, String)> {
+        let mut result = Vec::new();
+        let mut addr = 0;
+        while addr < self.codes.len() {
+            let (text, len) = self.disassemble_at(addr);
+            result.push((addr, text));
+            addr += len;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_disassemble() {
+        let c = Computer::new(vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99, 42,
+        ]);
+        let listing: Vec<String> = c
+            .disassemble()
+            .into_iter()
+            .map(|(addr, text)| format!("{}: {}", addr, text))
+            .collect();
+        assert_eq!(
+            listing,
+            vec![
+                "0: arb 1",
+                "2: out [rb-1]",
+                "4: add [100], 1, [100]",
+                "8: eq [100], 16, [101]",
+                "12: jz [101], 0",
+                "15: hlt",
+                "16: data 42",
+            ]
+        );
+    }
+}
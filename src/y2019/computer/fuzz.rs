@@ -0,0 +1,271 @@
+// Random program generation and differential testing for the intcode computer.
+//
+// `fuzz_one` is the entry point for a coverage guided fuzzer, see
+// fuzz/fuzz_targets/intcode.rs: it turns an arbitrary byte string into a
+// program and its inputs, then runs it through both the reference `run_step`
+// and the predecoded path in lockstep. The tests at the bottom drive the same
+// generators from a seeded prng, and
+// `INTCODE_FUZZ_ITERATIONS=<n> cargo test -- --ignored fuzz` runs a longer
+// campaign.
+
+use super::{Computer, StepResult};
+
+const MAX_STEPS: usize = 10_000;
+const MEMORY_LIMIT: usize = 4096;
+
+// source of randomness for the generators
+pub trait Entropy {
+    fn next_u64(&mut self) -> u64;
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    // inclusive on both ends
+    fn between(&mut self, lo: i64, hi: i64) -> i64 {
+        lo + self.below((hi - lo + 1) as u64) as i64
+    }
+
+    fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+}
+
+// xorshift64*, good enough to generate test cases
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+}
+
+impl Entropy for Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+// replays the bytes given by a fuzzer, then only zeroes
+pub struct Bytes<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Entropy for Bytes<'a> {
+    fn next_u64(&mut self) -> u64 {
+        let (head, tail) = self.data.split_at(self.data.len().min(2));
+        self.data = tail;
+        head.iter().fold(0, |acc, b| (acc << 8) | u64::from(*b))
+    }
+}
+
+// a program made mostly of well formed instructions whose addresses point
+// around the program, sprinkled with invalid modes and junk values
+pub fn gen_program<E: Entropy>(e: &mut E, max_len: usize) -> Vec<i64> {
+    let target_len = 1 + e.below(max_len as u64) as usize;
+    let mut codes = Vec::with_capacity(target_len + 4);
+    while codes.len() < target_len {
+        if e.chance(5) {
+            codes.push(gen_junk(e));
+            continue;
+        }
+
+        let (opcode, write_param) = match e.below(10) {
+            0 => (1, Some(3)),
+            1 => (2, Some(3)),
+            2 => (3, Some(1)),
+            3 => (4, None),
+            4 => (5, None),
+            5 => (6, None),
+            6 => (7, Some(3)),
+            7 => (8, Some(3)),
+            8 => (9, None),
+            _ => (99, None),
+        };
+        let arity = match opcode {
+            1 | 2 | 7 | 8 => 3,
+            5 | 6 => 2,
+            99 => 0,
+            _ => 1,
+        };
+
+        let mut code = opcode;
+        let mut params = Vec::with_capacity(arity);
+        for nth_param in 1..=arity {
+            let mode = if e.chance(3) {
+                e.between(3, 9)
+            } else if write_param == Some(nth_param) {
+                if e.chance(50) {
+                    0
+                } else {
+                    2
+                }
+            } else {
+                e.between(0, 2)
+            };
+            code += mode * 10_i64.pow(nth_param as u32 + 1);
+            params.push(match mode {
+                0 => e.between(0, target_len as i64 + 8),
+                1 => e.between(-20, 100),
+                2 => e.between(-10, 20),
+                _ => gen_junk(e),
+            });
+        }
+        codes.push(code);
+        codes.extend(params);
+    }
+    codes
+}
+
+pub fn gen_inputs<E: Entropy>(e: &mut E, max_len: usize) -> Vec<i64> {
+    let len = e.below(max_len as u64 + 1) as usize;
+    (0..len)
+        .map(|_| {
+            if e.chance(10) {
+                gen_junk(e)
+            } else {
+                e.between(-10, 10)
+            }
+        })
+        .collect()
+}
+
+fn gen_junk<E: Entropy>(e: &mut E) -> i64 {
+    match e.below(4) {
+        0 => e.next_u64() as i64,
+        1 => i64::MAX - e.below(10) as i64,
+        2 => i64::MIN + e.below(10) as i64,
+        _ => e.between(-100_000, 100_000),
+    }
+}
+
+// run both execution paths side by side and report the first divergence
+pub fn check_program(codes: &[i64], inputs: &[i64]) -> Result<(), String> {
+    let mut reference = Computer::new(codes.to_vec());
+    reference.with_memory_limit(MEMORY_LIMIT);
+    reference.with_input(inputs.to_vec());
+    let mut fast = reference.clone();
+
+    for step in 0..MAX_STEPS {
+        let expected = reference.run_step();
+        let actual = fast.step_predecoded();
+
+        for c in [&reference, &fast].iter() {
+            if c.codes.len() > MEMORY_LIMIT {
+                return Err(format!(
+                    "memory grew to {} cells at step {}",
+                    c.codes.len(),
+                    step
+                ));
+            }
+        }
+        if fast.decoded.len() > codes.len() {
+            return Err(format!(
+                "the decoded cache grew to {} entries at step {}",
+                fast.decoded.len(),
+                step
+            ));
+        }
+
+        let halted = match (&expected, &actual) {
+            (Ok(StepResult::Continue), Ok(StepResult::Continue))
+            | (Ok(StepResult::Output(_)), Ok(StepResult::Output(_))) => false,
+            (Ok(_), Ok(_)) | (Err(_), Err(_)) => true,
+            _ => {
+                return Err(format!(
+                    "step {}: reference gave {:?}, predecoded gave {:?}",
+                    step, expected, actual
+                ))
+            }
+        };
+
+        // the error reported can legitimately differ when an instruction is
+        // wrong in more than one way, but both paths must leave the machine
+        // in the same state
+        let same_result = expected == actual || (expected.is_err() && actual.is_err());
+        if !same_result
+            || reference.instruction_idx != fast.instruction_idx
+            || reference.relative_base != fast.relative_base
+            || reference.input_idx != fast.input_idx
+            || reference.codes != fast.codes
+            || reference.outputs != fast.outputs
+        {
+            return Err(format!(
+                "step {}: state diverged\nreference: {:?}\npredecoded: {:?}",
+                step, reference, fast
+            ));
+        }
+
+        if halted {
+            return Ok(());
+        }
+    }
+    Ok(())
+}
+
+pub fn fuzz_one(data: &[u8]) -> Result<(), String> {
+    let mut e = Bytes { data };
+    let codes = gen_program(&mut e, 64);
+    let inputs = gen_inputs(&mut e, 8);
+    check_program(&codes, &inputs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_seed(seed: u64) {
+        let mut rng = Rng::new(seed);
+        let codes = gen_program(&mut rng, 128);
+        let inputs = gen_inputs(&mut rng, 8);
+        if let Err(msg) = check_program(&codes, &inputs) {
+            panic!(
+                "seed {}\nprogram: {:?}\ninputs: {:?}\n{}",
+                seed, codes, inputs, msg
+            );
+        }
+    }
+
+    #[test]
+    fn test_fuzz_generated_programs() {
+        for seed in 0..2000 {
+            assert_seed(seed);
+        }
+    }
+
+    #[test]
+    fn test_fuzz_arbitrary_bytes() {
+        let mut rng = Rng::new(42);
+        for _ in 0..2000 {
+            let len = rng.below(256) as usize;
+            let data: Vec<u8> = (0..len).map(|_| rng.next_u64() as u8).collect();
+            if let Err(msg) = fuzz_one(&data) {
+                panic!("input {:?}\n{}", data, msg);
+            }
+        }
+        assert_eq!(fuzz_one(&[]), Ok(()));
+    }
+
+    #[test]
+    fn test_fuzz_known_programs() {
+        let codes = ::y2019::computer::read_codes("data/2019/day09.txt");
+        assert_eq!(check_program(&codes, &[1]), Ok(()));
+        let codes = ::y2019::computer::read_codes("data/2019/day05.txt");
+        assert_eq!(check_program(&codes, &[5]), Ok(()));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_fuzz_long_campaign() {
+        let iterations = std::env::var("INTCODE_FUZZ_ITERATIONS")
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(100_000);
+        for seed in 0..iterations {
+            assert_seed(seed);
+        }
+    }
+}
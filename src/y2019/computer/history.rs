@@ -0,0 +1,369 @@
+// Undo log for time travel debugging.
+//
+// Before each executed instruction, the registers, the input cursor and the
+// sizes of the memory and outputs are saved, and every memory write records
+// the value it overwrote. Replaying that log backwards restores any earlier
+// state, and moving forward again is simply running the program.
+//
+// Step numbers are the value of `Computer::steps` once an instruction has
+// been executed: going to step `n` shows the state right after the `n`th
+// instruction.
+//
+// Only the last steps are kept, the log would otherwise grow as long as the
+// program runs: past its capacity, the oldest step and its writes are
+// forgotten.
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+use super::{Computer, ComputerError, StepResult};
+
+// number of steps recorded when nothing else is asked for
+pub const DEFAULT_CAPACITY: usize = 100_000;
+
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    // value of the step counter before the oldest recorded step, the steps
+    // before that cannot be undone
+    start: u64,
+    capacity: usize,
+    checkpoints: VecDeque<Checkpoint>,
+    // address and previous value of every memory write since the oldest
+    // recorded step, in order
+    writes: VecDeque<(usize, i64)>,
+    // writes forgotten along with the oldest steps. `writes_start` counts
+    // them, so that it doesn't change when they are dropped.
+    dropped_writes: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Checkpoint {
+    instruction_idx: usize,
+    relative_base: i64,
+    input_idx: usize,
+    outputs_len: usize,
+    codes_len: usize,
+    writes_start: usize,
+    opcode: i64,
+}
+
+impl History {
+    pub fn record_write(&mut self, addr: usize, previous: i64) {
+        self.writes.push_back((addr, previous));
+    }
+
+    fn push(&mut self, checkpoint: Checkpoint) {
+        self.checkpoints.push_back(checkpoint);
+        if self.checkpoints.len() > self.capacity {
+            self.checkpoints.pop_front();
+            self.start += 1;
+            let kept = match self.checkpoints.front() {
+                Some(oldest) => oldest.writes_start,
+                None => self.dropped_writes + self.writes.len(),
+            };
+            self.writes.drain(..kept - self.dropped_writes);
+            self.dropped_writes = kept;
+        }
+    }
+
+    // the writes of the given step and all the ones after it
+    fn split_off(&mut self, writes_start: usize) -> VecDeque<(usize, i64)> {
+        self.writes.split_off(writes_start - self.dropped_writes)
+    }
+}
+
+impl Computer {
+    // start recording, from the current step onward, keeping at most
+    // `capacity` steps
+    pub fn enable_history(&mut self, capacity: usize) {
+        self.history = Some(Box::new(History {
+            start: self.steps,
+            capacity,
+            ..History::default()
+        }));
+    }
+
+    // undo the last executed instruction, false if there is nothing to undo
+    pub fn step_back(&mut self) -> bool {
+        let checkpoint = match self.history.as_mut().and_then(|h| h.checkpoints.pop_back()) {
+            Some(checkpoint) => checkpoint,
+            None => return false,
+        };
+        self.steps -= 1;
+        self.opcode_counts[checkpoint.opcode as usize] -= 1;
+        self.restore(checkpoint);
+        true
+    }
+
+    // go backward or forward to the given step. Going forward stops early if
+    // the program halts or needs an input, the step reached is returned.
+    pub fn goto_step(&mut self, step: u64) -> Result<u64, ComputerError> {
+        while self.steps > step {
+            if !self.step_back() {
+                return Err(ComputerError::HistoryUnavailable { step });
+            }
+        }
+        while self.steps < step {
+            match self.counted_step(false)? {
+                StepResult::Done | StepResult::AwaitInput => break,
+                StepResult::Continue | StepResult::Output(_) => continue,
+            }
+        }
+        Ok(self.steps)
+    }
+
+    // the step during which the given address was last written, if that
+    // happened since the recording started
+    pub fn last_write(&self, addr: usize) -> Option<u64> {
+        let history = self.history.as_ref()?;
+        let write_idx =
+            history.dropped_writes + history.writes.iter().rposition(|(a, _)| *a == addr)?;
+        let checkpoint_idx = history
+            .checkpoints
+            .partition_point(|c| c.writes_start <= write_idx);
+        Some(history.start + checkpoint_idx as u64)
+    }
+
+    pub(super) fn begin_step(&mut self, opcode: i64) {
+        if let Some(history) = self.history.as_mut() {
+            let writes_start = history.dropped_writes + history.writes.len();
+            history.push(Checkpoint {
+                instruction_idx: self.instruction_idx,
+                relative_base: self.relative_base,
+                input_idx: self.input_idx,
+                outputs_len: self.outputs.len(),
+                codes_len: self.codes.len(),
+                writes_start,
+                opcode,
+            });
+        }
+    }
+
+    // the instruction didn't execute (error or missing input), forget about it
+    pub(super) fn discard_step(&mut self) {
+        if let Some(checkpoint) = self.history.as_mut().and_then(|h| h.checkpoints.pop_back()) {
+            self.restore(checkpoint);
+        }
+    }
+
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        let writes = match self.history.as_mut() {
+            Some(history) => history.split_off(checkpoint.writes_start),
+            None => VecDeque::new(),
+        };
+        for (addr, previous) in writes.into_iter().rev() {
+            self.codes[addr] = previous;
+            self.invalidate_decoded(addr);
+        }
+        self.codes.truncate(checkpoint.codes_len);
+        self.instruction_idx = checkpoint.instruction_idx;
+        self.relative_base = checkpoint.relative_base;
+        self.input_idx = checkpoint.input_idx;
+        self.outputs.truncate(checkpoint.outputs_len);
+    }
+}
+
+// What to look at once a program has stopped, see `profile_program`: the
+// state at an earlier step, and when some memory cells were last written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rewind {
+    pub capacity: usize,
+    // the step to go back to, the last one if None
+    pub step: Option<u64>,
+    pub watched: Vec<usize>,
+}
+
+impl Rewind {
+    pub fn report(&self, c: &mut Computer) -> String {
+        let mut out = String::new();
+        if let Some(step) = self.step {
+            match c.goto_step(step) {
+                Ok(step) => writeln!(
+                    out,
+                    "at step {}: ip {}, relative base {}, outputs {:?}",
+                    step, c.instruction_idx, c.relative_base, c.outputs
+                )
+                .unwrap(),
+                Err(err) => writeln!(out, "{}", err).unwrap(),
+            }
+        }
+        for &addr in &self.watched {
+            let value = c.codes.get(addr).cloned().unwrap_or(0);
+            match c.last_write(addr) {
+                Some(step) => writeln!(out, "[{}] = {}, written at step {}", addr, value, step),
+                None => writeln!(
+                    out,
+                    "[{}] = {}, not written in the recorded steps",
+                    addr, value
+                ),
+            }
+            .unwrap();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{read_codes, RunResult};
+    use super::*;
+
+    // state after exactly `steps` instructions, without any time travel
+    fn run_for(codes: &[i64], inputs: &[i64], steps: u64) -> Computer {
+        let mut c = Computer::new(codes.to_vec());
+        c.with_input(inputs.to_vec());
+        c.with_step_limit(steps);
+        match c.run_until_halt() {
+            Err(ComputerError::StepLimitExceeded { .. }) | Ok(_) => c,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    fn assert_same_state(a: &Computer, b: &Computer) {
+        assert_eq!(a.steps, b.steps);
+        assert_eq!(a.instruction_idx, b.instruction_idx);
+        assert_eq!(a.relative_base, b.relative_base);
+        assert_eq!(a.input_idx, b.input_idx);
+        assert_eq!(a.outputs, b.outputs);
+        assert_eq!(a.codes, b.codes);
+    }
+
+    #[test]
+    fn test_history_goto_step() {
+        let codes = read_codes("data/2019/day09.txt");
+        let mut c = Computer::new(codes.clone());
+        c.with_input(vec![1]);
+        c.enable_history(DEFAULT_CAPACITY);
+        c.run_until_halt().unwrap();
+        let total = c.steps();
+        assert!(total > 100);
+
+        for &step in [total - 1, 150, 3, 0, 42, total].iter() {
+            assert_eq!(c.goto_step(step), Ok(step));
+            assert_same_state(&c, &run_for(&codes, &[1], step));
+        }
+    }
+
+    #[test]
+    fn test_history_predecoded() {
+        // the loop body gets cached then overwritten, see the computer tests
+        let codes = vec![
+            1001, 30, 1, 30, 4, 30, 1101, 2, 0, 0, 1005, 31, 20, 1101, 1, 0, 31, 1105, 1, 0, 99,
+        ];
+        let mut c = Computer::new(codes.clone());
+        c.enable_history(DEFAULT_CAPACITY);
+        while let Ok(RunResult::Output(_)) = c.run_predecoded() {}
+        assert_eq!(c.outputs, vec![1, 30]);
+
+        assert_eq!(c.goto_step(3), Ok(3));
+        assert_same_state(&c, &run_for(&codes, &[], 3));
+        while let Ok(RunResult::Output(_)) = c.run_predecoded() {}
+        assert_eq!(c.outputs, vec![1, 30]);
+    }
+
+    #[test]
+    fn test_history_last_write() {
+        // writes 5 to cell 20 at step 1, then 10 at step 3
+        let codes = vec![1101, 2, 3, 20, 4, 20, 1002, 20, 2, 20, 99];
+        let mut c = Computer::new(codes);
+        c.enable_history(DEFAULT_CAPACITY);
+        c.run_until_halt().unwrap();
+        assert_eq!(c.codes[20], 10);
+        assert_eq!(c.last_write(20), Some(3));
+        assert_eq!(c.last_write(0), None);
+
+        c.goto_step(2).unwrap();
+        assert_eq!(c.codes[20], 5);
+        assert_eq!(c.last_write(20), Some(1));
+        c.goto_step(0).unwrap();
+        assert_eq!(c.last_write(20), None);
+        assert_eq!(c.codes.len(), 11);
+    }
+
+    #[test]
+    fn test_history_io() {
+        let mut c = Computer::new(vec![3, 9, 4, 9, 3, 10, 99, 0, 0, 0, 0]);
+        c.enable_history(DEFAULT_CAPACITY);
+        assert_eq!(c.run_with_inputs(vec![7]), Ok(RunResult::Output(7)));
+        // waiting for the second input isn't a step
+        assert_eq!(c.run(), Ok(RunResult::AwaitInput));
+        assert_eq!(c.steps(), 2);
+
+        assert!(c.step_back());
+        assert!(c.step_back());
+        assert!(!c.step_back());
+        assert!(c.outputs.is_empty());
+        assert_eq!(c.codes[9], 0);
+
+        assert_eq!(c.goto_step(5), Ok(2));
+        assert_eq!(c.outputs, vec![7]);
+    }
+
+    #[test]
+    fn test_history_unavailable() {
+        let mut c = Computer::new(vec![1101, 1, 1, 5, 1101, 1, 1, 0, 99]);
+        c.with_step_limit(1);
+        assert!(c.run().is_err());
+        c.enable_history(DEFAULT_CAPACITY);
+        c.with_step_limit(10);
+        c.run_until_halt().unwrap();
+        assert_eq!(c.goto_step(1), Ok(1));
+        assert_eq!(
+            c.goto_step(0),
+            Err(ComputerError::HistoryUnavailable { step: 0 })
+        );
+    }
+
+    #[test]
+    fn test_history_capacity() {
+        let codes = read_codes("data/2019/day09.txt");
+        let mut c = Computer::new(codes.clone());
+        c.with_input(vec![1]);
+        c.enable_history(10);
+        c.run_until_halt().unwrap();
+        let total = c.steps();
+        {
+            let history = c.history.as_ref().unwrap();
+            assert_eq!(history.checkpoints.len(), 10);
+            assert_eq!(history.start, total - 10);
+            let kept = history.checkpoints.front().unwrap().writes_start;
+            assert_eq!(history.dropped_writes, kept);
+            assert!(history.writes.len() <= 10);
+        }
+
+        assert_eq!(
+            c.goto_step(total - 11),
+            Err(ComputerError::HistoryUnavailable { step: total - 11 })
+        );
+        assert_same_state(&c, &run_for(&codes, &[1], total - 10));
+        assert_eq!(c.goto_step(total - 3), Ok(total - 3));
+        assert_same_state(&c, &run_for(&codes, &[1], total - 3));
+
+        // the writes of the forgotten steps are forgotten too
+        let mut c = Computer::new(vec![1101, 2, 3, 20, 1101, 0, 0, 21, 1101, 0, 0, 22, 99]);
+        c.enable_history(2);
+        c.run_until_halt().unwrap();
+        assert_eq!(c.last_write(20), None);
+        assert_eq!(c.last_write(22), Some(3));
+        assert_eq!(c.goto_step(2), Ok(2));
+        assert_eq!(c.codes[20], 5);
+    }
+
+    #[test]
+    fn test_rewind_report() {
+        let mut c = Computer::new(vec![1101, 2, 3, 20, 4, 20, 1002, 20, 2, 20, 99]);
+        c.enable_history(DEFAULT_CAPACITY);
+        c.run_until_halt().unwrap();
+        let rewind = Rewind {
+            capacity: DEFAULT_CAPACITY,
+            step: Some(2),
+            watched: vec![20, 0],
+        };
+        assert_eq!(
+            rewind.report(&mut c),
+            "at step 2: ip 6, relative base 0, outputs [5]\n\
+             [20] = 5, written at step 1\n\
+             [0] = 1101, not written in the recorded steps\n"
+        );
+    }
+}
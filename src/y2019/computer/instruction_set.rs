@@ -0,0 +1,372 @@
+// Opcodes understood by the computer.
+//
+// Each opcode is registered with a name, the role of each of its parameters
+// and a handler. Before calling the handler, the computer resolves every
+// parameter according to its mode: a `Read` parameter is replaced by its
+// value, a `Write` parameter by the address it points to (and cannot be in
+// immediate mode). The handler then describes what the instruction does with
+// an `Effect`, which the computer applies. Opcodes combining their first two
+// parameters into the third can also say how they combine unknowns, for
+// symbolic execution, and the ones which can jump say which parameter is the
+// destination, for the profiler.
+
+use std::fmt;
+
+use super::symbolic::Expr;
+use super::ComputerError;
+
+// an instruction is at most that long, not counting the opcode itself
+pub const MAX_PARAMS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    // move on to the next instruction
+    Continue,
+    Write { addr: i64, value: i64 },
+    Jump(i64),
+    AdjustRelativeBase(i64),
+    // store the next input at the address, or wait until there is one
+    Input { addr: i64 },
+    Output(i64),
+    Halt,
+}
+
+// called with the full instruction code and the resolved parameters
+pub type Handler = fn(i64, &[i64]) -> Result<Effect, ComputerError>;
+
+// the same operation on expressions, None on overflow
+pub type SymbolicHandler = fn(&Expr, &Expr) -> Option<Expr>;
+
+#[derive(Clone, Copy)]
+pub struct OpcodeDef {
+    pub name: &'static str,
+    roles: [Role; MAX_PARAMS],
+    arity: usize,
+    pub handler: Handler,
+    // None if the opcode needs concrete values
+    pub symbolic: Option<SymbolicHandler>,
+    // index of the parameter holding where the opcode may jump to, None if
+    // it always moves on to the next instruction
+    pub jump_target: Option<usize>,
+}
+
+impl OpcodeDef {
+    pub fn params(&self) -> &[Role] {
+        &self.roles[..self.arity]
+    }
+
+    // number of cells taken by the instruction, opcode included
+    pub fn size(&self) -> usize {
+        self.arity + 1
+    }
+}
+
+impl fmt::Debug for OpcodeDef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{:?}", self.name, self.params())
+    }
+}
+
+#[derive(Clone)]
+pub struct InstructionSet {
+    ops: [Option<OpcodeDef>; 100],
+}
+
+impl InstructionSet {
+    pub fn empty() -> InstructionSet {
+        InstructionSet { ops: [None; 100] }
+    }
+
+    // add or replace an opcode. Opcodes are the last two digits of an
+    // instruction, so they must be in 0..100
+    pub fn register(
+        &mut self,
+        opcode: i64,
+        name: &'static str,
+        params: &[Role],
+        handler: Handler,
+    ) -> &mut InstructionSet {
+        assert!(
+            (0..100).contains(&opcode),
+            "opcode {} must be in 0..100",
+            opcode
+        );
+        assert!(
+            params.len() <= MAX_PARAMS,
+            "{} has {} parameters, at most {} are supported",
+            name,
+            params.len(),
+            MAX_PARAMS
+        );
+        let mut roles = [Role::Read; MAX_PARAMS];
+        roles[..params.len()].copy_from_slice(params);
+        self.ops[opcode as usize] = Some(OpcodeDef {
+            name,
+            roles,
+            arity: params.len(),
+            handler,
+            symbolic: None,
+            jump_target: None,
+        });
+        self
+    }
+
+    // an opcode computing something from its first two parameters and
+    // writing it to the third, which can also run on unknowns
+    pub fn register_arithmetic(
+        &mut self,
+        opcode: i64,
+        name: &'static str,
+        handler: Handler,
+        symbolic: SymbolicHandler,
+    ) -> &mut InstructionSet {
+        use self::Role::{Read, Write};
+        self.register(opcode, name, &[Read, Read, Write], handler);
+        if let Some(op) = self.ops[opcode as usize].as_mut() {
+            op.symbolic = Some(symbolic);
+        }
+        self
+    }
+
+    // an opcode which may jump to the address given by its `target`th
+    // parameter, counting from 0
+    pub fn register_jump(
+        &mut self,
+        opcode: i64,
+        name: &'static str,
+        params: &[Role],
+        target: usize,
+        handler: Handler,
+    ) -> &mut InstructionSet {
+        assert!(
+            target < params.len(),
+            "{} has no parameter {} to jump to",
+            name,
+            target
+        );
+        self.register(opcode, name, params, handler);
+        if let Some(op) = self.ops[opcode as usize].as_mut() {
+            op.jump_target = Some(target);
+        }
+        self
+    }
+
+    // executing a disabled opcode is an `UnknownCode` error
+    pub fn disable(&mut self, opcode: i64) -> &mut InstructionSet {
+        if let Some(op) = self.ops.get_mut(opcode as usize) {
+            *op = None;
+        }
+        self
+    }
+
+    // a copy of this set restricted to the given opcodes
+    pub fn only(&self, opcodes: &[i64]) -> InstructionSet {
+        let mut result = InstructionSet::empty();
+        for &opcode in opcodes {
+            if let Some(op) = self.get(opcode) {
+                result.ops[opcode as usize] = Some(*op);
+            }
+        }
+        result
+    }
+
+    pub fn get(&self, opcode: i64) -> Option<&OpcodeDef> {
+        if (0..100).contains(&opcode) {
+            self.ops[opcode as usize].as_ref()
+        } else {
+            None
+        }
+    }
+
+    // the original computer from day 2: day 5 before it got inputs,
+    // outputs, jumps and comparisons
+    pub fn day02() -> InstructionSet {
+        let mut set = InstructionSet::day05();
+        for opcode in 3..=8 {
+            set.disable(opcode);
+        }
+        set
+    }
+
+    // input, output, jumps and comparisons from day 5, but no relative base
+    pub fn day05() -> InstructionSet {
+        InstructionSet::default().only(&[1, 2, 3, 4, 5, 6, 7, 8, 99])
+    }
+}
+
+impl fmt::Debug for InstructionSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.ops
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(opcode, op)| op.map(|op| (opcode, op))),
+            )
+            .finish()
+    }
+}
+
+// the complete instruction set, as of day 9
+impl Default for InstructionSet {
+    fn default() -> InstructionSet {
+        use self::Role::{Read, Write};
+        let mut set = InstructionSet::empty();
+        set.register_arithmetic(1, "add", add, Expr::checked_add)
+            .register_arithmetic(2, "mul", mul, Expr::checked_mul)
+            .register(3, "in", &[Write], input)
+            .register(4, "out", &[Read], output)
+            .register_jump(5, "jnz", &[Read, Read], 1, jump_if_true)
+            .register_jump(6, "jz", &[Read, Read], 1, jump_if_false)
+            .register(7, "lt", &[Read, Read, Write], less_than)
+            .register(8, "eq", &[Read, Read, Write], equals)
+            .register(9, "arb", &[Read], adjust_relative_base)
+            .register(99, "hlt", &[], halt);
+        set
+    }
+}
+
+fn add(code: i64, args: &[i64]) -> Result<Effect, ComputerError> {
+    let value = args[0]
+        .checked_add(args[1])
+        .ok_or(ComputerError::Overflow { code })?;
+    Ok(Effect::Write {
+        addr: args[2],
+        value,
+    })
+}
+
+fn mul(code: i64, args: &[i64]) -> Result<Effect, ComputerError> {
+    let value = args[0]
+        .checked_mul(args[1])
+        .ok_or(ComputerError::Overflow { code })?;
+    Ok(Effect::Write {
+        addr: args[2],
+        value,
+    })
+}
+
+fn input(_code: i64, args: &[i64]) -> Result<Effect, ComputerError> {
+    Ok(Effect::Input { addr: args[0] })
+}
+
+fn output(_code: i64, args: &[i64]) -> Result<Effect, ComputerError> {
+    Ok(Effect::Output(args[0]))
+}
+
+fn jump_if_true(_code: i64, args: &[i64]) -> Result<Effect, ComputerError> {
+    Ok(if args[0] != 0 {
+        Effect::Jump(args[1])
+    } else {
+        Effect::Continue
+    })
+}
+
+fn jump_if_false(_code: i64, args: &[i64]) -> Result<Effect, ComputerError> {
+    Ok(if args[0] == 0 {
+        Effect::Jump(args[1])
+    } else {
+        Effect::Continue
+    })
+}
+
+fn less_than(_code: i64, args: &[i64]) -> Result<Effect, ComputerError> {
+    Ok(Effect::Write {
+        addr: args[2],
+        value: (args[0] < args[1]) as i64,
+    })
+}
+
+fn equals(_code: i64, args: &[i64]) -> Result<Effect, ComputerError> {
+    Ok(Effect::Write {
+        addr: args[2],
+        value: (args[0] == args[1]) as i64,
+    })
+}
+
+fn adjust_relative_base(_code: i64, args: &[i64]) -> Result<Effect, ComputerError> {
+    Ok(Effect::AdjustRelativeBase(args[0]))
+}
+
+fn halt(_code: i64, _args: &[i64]) -> Result<Effect, ComputerError> {
+    Ok(Effect::Halt)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Computer, RunResult};
+    use super::*;
+
+    #[test]
+    fn test_instruction_set_day02() {
+        // 1 + 1 then output it, but day 2 doesn't know about outputs yet
+        let codes = vec![1101, 1, 1, 0, 4, 0, 99];
+        let mut c = Computer::new(codes.clone());
+        c.with_instruction_set(InstructionSet::day02());
+        assert_eq!(c.run(), Err(ComputerError::UnknownCode { code: 4 }));
+        assert_eq!(c.codes[0], 2);
+
+        let mut c = Computer::new(codes);
+        assert_eq!(c.run(), Ok(RunResult::Output(2)));
+    }
+
+    #[test]
+    fn test_instruction_set_custom_opcode() {
+        fn sub(code: i64, args: &[i64]) -> Result<Effect, ComputerError> {
+            let value = args[0]
+                .checked_sub(args[1])
+                .ok_or(ComputerError::Overflow { code })?;
+            Ok(Effect::Write {
+                addr: args[2],
+                value,
+            })
+        }
+
+        let mut set = InstructionSet::default();
+        set.register(10, "sub", &[Role::Read, Role::Read, Role::Write], sub);
+
+        // 10 - 3, output it, halt
+        let codes = vec![1110, 10, 3, 9, 4, 9, 99, 0, 0, 0];
+        let mut c = Computer::new(codes.clone());
+        c.with_instruction_set(set.clone());
+        c.run_until_halt().unwrap();
+        assert_eq!(c.outputs, vec![7]);
+        assert_eq!(c.disassemble_at(0).0, "sub 10, 3, [9]");
+
+        let mut c = Computer::new(codes.clone());
+        c.with_instruction_set(set.clone());
+        assert_eq!(c.run_predecoded(), Ok(RunResult::Output(7)));
+
+        // writing in immediate mode is still rejected
+        let mut c = Computer::new(vec![11110, 10, 3, 9, 99]);
+        c.with_instruction_set(set);
+        assert_eq!(
+            c.run(),
+            Err(ComputerError::InvalidMode {
+                code: 11110,
+                nth_param: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_instruction_set_disable() {
+        let mut set = InstructionSet::default();
+        set.disable(9);
+        assert!(set.get(9).is_none());
+        assert_eq!(set.get(1).unwrap().size(), 4);
+        assert_eq!(set.get(99).unwrap().params(), &[]);
+        assert!(set.get(100).is_none());
+        assert!(set.get(-1).is_none());
+
+        let mut c = Computer::new(vec![109, 1, 99]);
+        c.with_instruction_set(set);
+        assert_eq!(c.run(), Err(ComputerError::UnknownCode { code: 9 }));
+    }
+}
@@ -0,0 +1,339 @@
+// Execution profile of an intcode program.
+//
+// Every executed instruction is counted against its address. Time is measured
+// per basic block. The leaders, where blocks start, are found before running:
+// the entry point, the immediate targets of jumps and the instructions right
+// after jumps. A jump through memory can land anywhere, so its target becomes
+// a leader when it is taken. The clock is only read when entering a block and
+// is stopped whenever the computer hands control back to the caller.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use super::history::Rewind;
+use super::{read_codes, Computer, Param};
+
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    address_counts: Vec<u64>,
+    leaders: BTreeSet<usize>,
+    blocks: BTreeMap<usize, BlockStats>,
+    // leader of the block being executed, and since when
+    current_block: Option<usize>,
+    since: Option<Instant>,
+    after_jump: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockStats {
+    pub entries: u64,
+    pub instructions: u64,
+    pub time: Duration,
+}
+
+impl Profile {
+    pub fn new(leaders: BTreeSet<usize>) -> Profile {
+        Profile {
+            leaders,
+            ..Profile::default()
+        }
+    }
+
+    // `jumps` tells whether the instruction could have jumped, in which case
+    // the next one starts a block
+    pub fn record(&mut self, addr: usize, jumps: bool) {
+        if self.after_jump {
+            self.leaders.insert(addr);
+        }
+        let leader = match self.current_block {
+            Some(leader) if !self.leaders.contains(&addr) => {
+                if self.since.is_none() {
+                    self.since = Some(Instant::now());
+                }
+                leader
+            }
+            _ => {
+                let now = Instant::now();
+                self.stop_clock(now);
+                self.current_block = Some(addr);
+                self.since = Some(now);
+                self.blocks.entry(addr).or_default().entries += 1;
+                addr
+            }
+        };
+
+        self.blocks.entry(leader).or_default().instructions += 1;
+        if addr >= self.address_counts.len() {
+            self.address_counts.resize(addr + 1, 0);
+        }
+        self.address_counts[addr] += 1;
+        self.after_jump = jumps;
+    }
+
+    // the computer stopped running, don't charge the caller's time to the
+    // current block
+    pub fn pause(&mut self) {
+        if self.since.is_some() {
+            self.stop_clock(Instant::now());
+        }
+    }
+
+    fn stop_clock(&mut self, now: Instant) {
+        if let (Some(leader), Some(since)) = (self.current_block, self.since.take()) {
+            self.blocks.entry(leader).or_default().time += now - since;
+        }
+    }
+
+    pub fn leaders(&self) -> &BTreeSet<usize> {
+        &self.leaders
+    }
+
+    pub fn count_at(&self, addr: usize) -> u64 {
+        self.address_counts.get(addr).cloned().unwrap_or(0)
+    }
+
+    pub fn blocks(&self) -> &BTreeMap<usize, BlockStats> {
+        &self.blocks
+    }
+
+    // addresses sorted by decreasing number of executions
+    pub fn hot_addresses(&self) -> Vec<(usize, u64)> {
+        let mut hot: Vec<(usize, u64)> = self
+            .address_counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(addr, count)| (addr, *count))
+            .collect();
+        hot.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hot
+    }
+
+    // a human readable report of the `top` hottest addresses and blocks. With
+    // `annotate`, each address is followed by the disassembly of the
+    // instruction currently in memory there.
+    pub fn report(&self, computer: &Computer, top: usize, annotate: bool) -> String {
+        let total = computer.steps();
+        let percent = |n: u64| 100.0 * n as f64 / total.max(1) as f64;
+        let mut out = String::new();
+
+        writeln!(out, "{} instructions executed", total).unwrap();
+
+        writeln!(out, "\nhot addresses").unwrap();
+        for (addr, count) in self.hot_addresses().into_iter().take(top) {
+            write!(out, "{:>12} {:>6.2}% {:>8}", count, percent(count), addr).unwrap();
+            if annotate {
+                write!(out, "  {}", computer.disassemble_at(addr).0).unwrap();
+            }
+            writeln!(out).unwrap();
+        }
+
+        writeln!(out, "\nopcodes").unwrap();
+        let mut opcodes = computer.opcode_stats();
+        opcodes.sort_by_key(|&(_, count)| Reverse(count));
+        for (opcode, count) in opcodes {
+            writeln!(
+                out,
+                "{:>12} {:>6.2}%  {}",
+                count,
+                percent(count),
+                computer.mnemonic(opcode)
+            )
+            .unwrap();
+        }
+
+        writeln!(out, "\nbasic blocks").unwrap();
+        writeln!(
+            out,
+            "{:>8} {:>12} {:>12} {:>12}",
+            "leader", "entries", "instructions", "time (µs)"
+        )
+        .unwrap();
+        let mut blocks: Vec<(&usize, &BlockStats)> = self.blocks.iter().collect();
+        blocks.sort_by(|a, b| b.1.time.cmp(&a.1.time).then(a.0.cmp(b.0)));
+        for (leader, stats) in blocks.into_iter().take(top) {
+            writeln!(
+                out,
+                "{:>8} {:>12} {:>12} {:>12}",
+                leader,
+                stats.entries,
+                stats.instructions,
+                stats.time.as_micros()
+            )
+            .unwrap();
+        }
+        out
+    }
+}
+
+impl Computer {
+    // decode the program from its start, one instruction after the other, and
+    // collect the addresses where basic blocks start. Cells which don't decode
+    // are skipped one by one.
+    pub fn block_leaders(&self) -> BTreeSet<usize> {
+        let mut leaders = BTreeSet::new();
+        leaders.insert(self.instruction_idx);
+        let mut ip = 0;
+        while ip < self.codes.len() {
+            let instruction = match self.decode(ip) {
+                Ok(instruction) => instruction,
+                Err(_) => {
+                    ip += 1;
+                    continue;
+                }
+            };
+            ip += instruction.op.size();
+            if let Some(target) = instruction.op.jump_target {
+                if let Param::Immediate(target) = instruction.params[target] {
+                    if target >= 0 {
+                        leaders.insert(target as usize);
+                    }
+                }
+                leaders.insert(ip);
+            }
+        }
+        leaders
+    }
+}
+
+// run the program until it halts and print its outputs followed by the
+// profile, then what `rewind` asks about its last steps. The program is
+// stopped early after `step_limit` instructions or once `timeout` has
+// elapsed.
+pub fn profile_program(
+    file_path: &str,
+    inputs: Vec<i64>,
+    annotate: bool,
+    step_limit: Option<u64>,
+    timeout: Option<Duration>,
+    rewind: Option<Rewind>,
+) {
+    let mut c = Computer::new(read_codes(file_path));
+    c.with_input(inputs);
+    if let Some(step_limit) = step_limit {
+        c.with_step_limit(step_limit);
+    }
+    if let Some(timeout) = timeout {
+        c.with_deadline(Instant::now() + timeout);
+    }
+    c.enable_profiling();
+    if let Some(rewind) = &rewind {
+        c.enable_history(rewind.capacity);
+    }
+    if let Err(err) = c.run_until_halt() {
+        println!("program stopped: {}", err);
+    }
+    println!("outputs: {:?}\n", c.outputs);
+    if let Some(profile) = c.profile() {
+        print!("{}", profile.report(&c, 20, annotate));
+    }
+    if let Some(rewind) = rewind {
+        print!("\n{}", rewind.report(&mut c));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::instruction_set::{Effect, InstructionSet, Role};
+    use super::super::ComputerError;
+    use super::*;
+
+    #[test]
+    fn test_profile_counts() {
+        // counts down from 3 to 0: the loop body is 2 instructions
+        let codes = vec![1101, 0, 3, 20, 1001, 20, -1, 20, 1005, 20, 4, 99];
+        let mut c = Computer::new(codes);
+        c.enable_profiling();
+        c.run_until_halt().unwrap();
+        let profile = c.profile().unwrap();
+
+        assert_eq!(profile.count_at(0), 1);
+        assert_eq!(profile.count_at(4), 3);
+        assert_eq!(profile.count_at(8), 3);
+        assert_eq!(profile.count_at(11), 1);
+        assert_eq!(profile.hot_addresses()[..2], [(4, 3), (8, 3)]);
+
+        let blocks: Vec<(usize, u64, u64)> = profile
+            .blocks()
+            .iter()
+            .map(|(leader, stats)| (*leader, stats.entries, stats.instructions))
+            .collect();
+        // the loop body is a block of its own from the first pass on
+        assert_eq!(blocks, vec![(0, 1, 1), (4, 3, 6), (11, 1, 1)]);
+    }
+
+    #[test]
+    fn test_profile_static_leaders() {
+        let codes = vec![1101, 0, 3, 20, 1001, 20, -1, 20, 1005, 20, 4, 99];
+        assert_eq!(
+            Computer::new(codes).block_leaders(),
+            vec![0, 4, 11].into_iter().collect()
+        );
+
+        // jumps through memory: the target is only known once taken
+        let codes = vec![1105, 1, 7, 99, 1106, 0, 10, 105, 1, 11, 99, 4];
+        let mut c = Computer::new(codes);
+        assert_eq!(c.block_leaders(), vec![0, 3, 7, 10].into_iter().collect());
+        c.enable_profiling();
+        c.run_until_halt().unwrap();
+        let profile = c.profile().unwrap();
+        assert!(profile.leaders().contains(&4));
+        let leaders: Vec<usize> = profile.blocks().keys().cloned().collect();
+        assert_eq!(leaders, vec![0, 4, 7, 10]);
+    }
+
+    #[test]
+    fn test_profile_custom_jumps() {
+        fn jump(_code: i64, args: &[i64]) -> Result<Effect, ComputerError> {
+            Ok(Effect::Jump(args[0]))
+        }
+
+        // the jumps are the opcodes registered as such, whatever their number
+        let mut set = InstructionSet::default();
+        set.register_jump(20, "jmp", &[Role::Read], 0, jump)
+            .register(6, "goto", &[Role::Read], jump);
+        // goto 4, then jmp 8 over the first output
+        let codes = vec![106, 4, 99, 99, 1120, 8, 104, 1, 104, 2, 99];
+        let mut c = Computer::new(codes);
+        c.with_instruction_set(set);
+        assert_eq!(c.block_leaders(), vec![0, 6, 8].into_iter().collect());
+        c.enable_profiling();
+        c.run_until_halt().unwrap();
+        assert_eq!(c.outputs, vec![2]);
+        let blocks: Vec<(usize, u64)> = c
+            .profile()
+            .unwrap()
+            .blocks()
+            .iter()
+            .map(|(leader, stats)| (*leader, stats.instructions))
+            .collect();
+        assert_eq!(blocks, vec![(0, 2), (8, 2)]);
+    }
+
+    #[test]
+    fn test_profile_stops_on_error() {
+        // loops forever
+        let mut c = Computer::new(vec![1105, 1, 0]);
+        c.with_step_limit(10);
+        c.enable_profiling();
+        assert!(c.run_until_halt().is_err());
+        let profile = c.profile().unwrap();
+        assert!(profile.since.is_none());
+        assert_eq!(profile.blocks()[&0].instructions, 10);
+    }
+
+    #[test]
+    fn test_profile_report() {
+        let codes = vec![1101, 0, 3, 20, 1001, 20, -1, 20, 1005, 20, 4, 99];
+        let mut c = Computer::new(codes);
+        c.enable_profiling();
+        c.run_until_halt().unwrap();
+        let report = c.profile().unwrap().report(&c, 2, true);
+        assert!(report.starts_with("8 instructions executed"));
+        assert!(report.contains("add [20], -1, [20]"));
+        assert!(report.contains("jnz [20], 4"));
+        assert!(report.contains("basic blocks"));
+    }
+}
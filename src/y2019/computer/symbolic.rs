@@ -0,0 +1,359 @@
+// Symbolic execution.
+//
+// Some memory cells hold unknowns instead of numbers, and the program runs
+// on expressions: the opcodes registered with a symbolic handler, like the
+// built-in additions and multiplications, build polynomials out of unknowns,
+// everything else goes through the usual opcode handlers. The
+// program must only need concrete values to decide what to do: opcodes,
+// addresses written to, jump conditions, comparisons. Whatever it computes on
+// the side can depend on the unknowns.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::instruction_set::{Effect, Role, MAX_PARAMS};
+use super::{get_mode, Computer, ComputerError, Mode};
+
+// a polynomial with integer coefficients, as a map from each product of
+// unknowns (sorted by name, repeated for powers) to its non zero coefficient
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expr {
+    terms: BTreeMap<Vec<String>, i64>,
+}
+
+impl Expr {
+    pub fn constant(value: i64) -> Expr {
+        let mut terms = BTreeMap::new();
+        if value != 0 {
+            terms.insert(Vec::new(), value);
+        }
+        Expr { terms }
+    }
+
+    pub fn symbol(name: &str) -> Expr {
+        let mut terms = BTreeMap::new();
+        terms.insert(vec![name.to_string()], 1);
+        Expr { terms }
+    }
+
+    pub fn as_constant(&self) -> Option<i64> {
+        if self.degree() == 0 {
+            Some(self.coefficient(&[]))
+        } else {
+            None
+        }
+    }
+
+    // coefficient of the given product of unknowns, `&[]` for the constant
+    pub fn coefficient(&self, names: &[&str]) -> i64 {
+        let mut key: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+        key.sort();
+        self.terms.get(&key).cloned().unwrap_or(0)
+    }
+
+    pub fn degree(&self) -> usize {
+        self.terms.keys().map(|k| k.len()).max().unwrap_or(0)
+    }
+
+    // None on overflow
+    pub fn checked_add(&self, other: &Expr) -> Option<Expr> {
+        let mut terms = self.terms.clone();
+        for (k, c) in &other.terms {
+            let sum = terms.get(k).cloned().unwrap_or(0).checked_add(*c)?;
+            if sum == 0 {
+                terms.remove(k);
+            } else {
+                terms.insert(k.clone(), sum);
+            }
+        }
+        Some(Expr { terms })
+    }
+
+    // None on overflow
+    pub fn checked_mul(&self, other: &Expr) -> Option<Expr> {
+        let mut result = Expr::constant(0);
+        for (k1, c1) in &self.terms {
+            for (k2, c2) in &other.terms {
+                let mut key = k1.clone();
+                key.extend(k2.iter().cloned());
+                key.sort();
+                let mut terms = BTreeMap::new();
+                terms.insert(key, c1.checked_mul(*c2)?);
+                result = result.checked_add(&Expr { terms })?;
+            }
+        }
+        Some(result)
+    }
+
+    // the value with the given values for the unknowns, None on overflow or
+    // if an unknown has no value
+    pub fn evaluate<F: Fn(&str) -> Option<i64>>(&self, value: F) -> Option<i64> {
+        self.terms.iter().try_fold(0i64, |sum, (k, c)| {
+            let term = k
+                .iter()
+                .try_fold(*c, |product, name| product.checked_mul(value(name)?))?;
+            sum.checked_add(term)
+        })
+    }
+}
+
+impl fmt::Display for Expr {
+    // highest degrees first, the constant last
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.terms.is_empty() {
+            return write!(f, "0");
+        }
+        let mut terms: Vec<(&Vec<String>, i64)> = self.terms.iter().map(|(k, c)| (k, *c)).collect();
+        terms.sort_by_key(|(k, _)| (Reverse(k.len()), *k));
+        for (i, (k, c)) in terms.into_iter().enumerate() {
+            match (i, c < 0) {
+                (0, true) => write!(f, "-")?,
+                (0, false) => {}
+                (_, true) => write!(f, " - ")?,
+                (_, false) => write!(f, " + ")?,
+            }
+            let c = c.unsigned_abs();
+            if k.is_empty() {
+                write!(f, "{}", c)?;
+            } else {
+                if c != 1 {
+                    write!(f, "{}*", c)?;
+                }
+                write!(f, "{}", k.join("*"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Computer {
+    // run the program from its current state, with the given cells replaced
+    // by unknowns, until it halts, and return the final memory. Inputs and
+    // outputs aren't supported.
+    pub fn run_symbolic(&self, symbols: &[(usize, &str)]) -> Result<Vec<Expr>, ComputerError> {
+        let mut memory: Vec<Expr> = self.codes.iter().map(|&c| Expr::constant(c)).collect();
+        for &(addr, name) in symbols {
+            self.to_addr(addr as i64)?;
+            if addr >= memory.len() {
+                memory.resize(addr + 1, Expr::constant(0));
+            }
+            memory[addr] = Expr::symbol(name);
+        }
+
+        let mut ip = self.instruction_idx;
+        let mut relative_base = self.relative_base;
+        let mut steps = 0;
+        loop {
+            if let Some(limit) = self.step_limit {
+                if steps >= limit {
+                    return Err(ComputerError::StepLimitExceeded { steps });
+                }
+            }
+            steps += 1;
+
+            let symbolic = ComputerError::SymbolicValue { addr: ip };
+            let concrete = |memory: &[Expr], addr: usize| -> Result<i64, ComputerError> {
+                self.to_addr(addr as i64)?;
+                match memory.get(addr) {
+                    Some(e) => e.as_constant().ok_or_else(|| symbolic.clone()),
+                    None => Ok(0),
+                }
+            };
+            let code = concrete(&memory, ip)?;
+            let op = *self
+                .instruction_set
+                .get(code % 100)
+                .ok_or(ComputerError::UnknownCode { code: code % 100 })?;
+
+            let mut args = Vec::with_capacity(MAX_PARAMS);
+            for (i, role) in op.params().iter().enumerate() {
+                let nth_param = i + 1;
+                let addr = match get_mode(code, nth_param)? {
+                    Mode::Immediate if *role == Role::Write => {
+                        return Err(ComputerError::InvalidMode { code, nth_param })
+                    }
+                    Mode::Immediate => ip + nth_param,
+                    // reading through an unknown pointer gives yet another
+                    // unknown, named after the pointer
+                    Mode::Position if *role == Role::Read => match memory.get(ip + nth_param) {
+                        Some(pointer) if pointer.as_constant().is_none() => {
+                            args.push(Expr::symbol(&format!("[{}]", pointer)));
+                            continue;
+                        }
+                        _ => self.to_addr(concrete(&memory, ip + nth_param)?)?,
+                    },
+                    Mode::Position => self.to_addr(concrete(&memory, ip + nth_param)?)?,
+                    Mode::Relative => {
+                        let addr = concrete(&memory, ip + nth_param)?
+                            .checked_add(relative_base)
+                            .ok_or(ComputerError::Overflow { code })?;
+                        self.to_addr(addr)?
+                    }
+                };
+                args.push(match role {
+                    Role::Read => memory
+                        .get(addr)
+                        .cloned()
+                        .unwrap_or_else(|| Expr::constant(0)),
+                    Role::Write => Expr::constant(addr as i64),
+                });
+            }
+
+            let effect = match args
+                .iter()
+                .map(|a| a.as_constant())
+                .collect::<Option<Vec<i64>>>()
+            {
+                Some(values) => (op.handler)(code, &values)?,
+                None => {
+                    let operation = op.symbolic.ok_or(symbolic)?;
+                    let value =
+                        operation(&args[0], &args[1]).ok_or(ComputerError::Overflow { code })?;
+                    // the destination is always concrete
+                    let addr = args[2].as_constant().unwrap() as usize;
+                    if addr >= memory.len() {
+                        memory.resize(addr + 1, Expr::constant(0));
+                    }
+                    memory[addr] = value;
+                    ip += op.size();
+                    continue;
+                }
+            };
+            match effect {
+                Effect::Continue => ip += op.size(),
+                Effect::Write { addr, value } => {
+                    let addr = self.to_addr(addr)?;
+                    if addr >= memory.len() {
+                        memory.resize(addr + 1, Expr::constant(0));
+                    }
+                    memory[addr] = Expr::constant(value);
+                    ip += op.size();
+                }
+                Effect::Jump(target) => ip = self.to_addr(target)?,
+                Effect::AdjustRelativeBase(delta) => {
+                    relative_base = relative_base
+                        .checked_add(delta)
+                        .ok_or(ComputerError::Overflow { code })?;
+                    ip += op.size();
+                }
+                Effect::Input { .. } | Effect::Output(_) => return Err(symbolic),
+                Effect::Halt => return Ok(memory),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_expr() {
+        let x = Expr::symbol("x");
+        let y = Expr::symbol("y");
+        let sum = x.checked_add(&Expr::constant(-3)).unwrap();
+        let e = sum.checked_mul(&sum).unwrap().checked_add(&y).unwrap();
+        assert_eq!(e.to_string(), "x*x - 6*x + y + 9");
+        assert_eq!(e.degree(), 2);
+        assert_eq!(e.coefficient(&["x"]), -6);
+        assert_eq!(
+            e.evaluate(|n| if n == "x" { Some(5) } else { Some(10) }),
+            Some(14)
+        );
+        assert_eq!(e.evaluate(|n| if n == "x" { Some(5) } else { None }), None);
+        assert_eq!(e.as_constant(), None);
+
+        let zero = x.checked_add(&x.checked_mul(&Expr::constant(-1)).unwrap());
+        assert_eq!(zero, Some(Expr::constant(0)));
+        assert_eq!(Expr::constant(0).to_string(), "0");
+        assert_eq!(
+            Expr::constant(i64::MAX).checked_add(&Expr::constant(1)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_run_symbolic() {
+        // the example from day 2
+        let computer = Computer::new(vec![1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50]);
+        let memory = computer.run_symbolic(&[(9, "a"), (10, "b")]).unwrap();
+        assert_eq!(memory[0].to_string(), "50*a + 50*b");
+        assert_eq!(memory[3].to_string(), "a + b");
+
+        // without unknowns, same as a normal run
+        let mut concrete = computer.clone();
+        concrete.run().unwrap();
+        let memory = computer.run_symbolic(&[]).unwrap();
+        assert_eq!(memory[0].as_constant(), Some(concrete.codes[0]));
+
+        // comparisons and jumps on concrete values still work
+        let computer = Computer::new(vec![
+            1107, 1, 2, 15, 1005, 15, 8, 99, 2, 16, 16, 0, 99, 0, 0, 0, 7,
+        ]);
+        let memory = computer.run_symbolic(&[(16, "x")]).unwrap();
+        assert_eq!(memory[0].to_string(), "x*x");
+
+        // reading through an unknown pointer
+        let computer = Computer::new(vec![1, 0, 0, 0, 99]);
+        let memory = computer.run_symbolic(&[(1, "p")]).unwrap();
+        assert_eq!(memory[0].to_string(), "[p] + 1");
+    }
+
+    fn sum(code: i64, args: &[i64]) -> Result<Effect, ComputerError> {
+        let value = args[0]
+            .checked_add(args[1])
+            .ok_or(ComputerError::Overflow { code })?;
+        Ok(Effect::Write {
+            addr: args[2],
+            value,
+        })
+    }
+
+    #[test]
+    fn test_run_symbolic_custom_set() {
+        // only the opcodes registered with a symbolic handler take unknowns,
+        // whatever their concrete handler does
+        use self::Role::{Read, Write};
+        use super::super::instruction_set::InstructionSet;
+        let mut set = InstructionSet::default();
+        set.register_arithmetic(42, "plus", sum, Expr::checked_add)
+            .register(43, "sum", &[Read, Read, Write], sum);
+        let mut computer = Computer::new(vec![1, 9, 10, 3, 42, 3, 11, 0, 99, 30, 40, 50]);
+        computer.with_instruction_set(set.clone());
+        let memory = computer.run_symbolic(&[(9, "a"), (10, "b")]).unwrap();
+        assert_eq!(memory[0].to_string(), "a + b + 50");
+
+        let mut computer = Computer::new(vec![43, 5, 6, 0, 99, 1, 2]);
+        computer.with_instruction_set(set.clone());
+        assert_eq!(
+            computer.run_symbolic(&[(5, "x")]),
+            Err(ComputerError::SymbolicValue { addr: 0 })
+        );
+
+        // replacing a built-in opcode drops its symbolic handler
+        set.register(1, "add", &[Read, Read, Write], sum);
+        let mut computer = Computer::new(vec![1, 5, 6, 0, 99, 1, 2]);
+        computer.with_instruction_set(set);
+        assert_eq!(
+            computer.run_symbolic(&[(5, "x")]),
+            Err(ComputerError::SymbolicValue { addr: 0 })
+        );
+    }
+
+    #[test]
+    fn test_run_symbolic_errors() {
+        // the unknown is used as an address to write to
+        let computer = Computer::new(vec![1, 0, 0, 0, 99]);
+        assert_eq!(
+            computer.run_symbolic(&[(3, "p")]),
+            Err(ComputerError::SymbolicValue { addr: 0 })
+        );
+        // ... or compared
+        let computer = Computer::new(vec![8, 5, 6, 0, 99, 1, 2]);
+        assert_eq!(
+            computer.run_symbolic(&[(5, "x")]),
+            Err(ComputerError::SymbolicValue { addr: 0 })
+        );
+    }
+}
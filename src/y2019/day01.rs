@@ -1,31 +1,91 @@
-use std::cmp;
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use std::fs;
+use y2019::rocket::{self, Equation, ModuleFuel};
 
 pub fn answer1() {
-    let res: i32 = read_modules().map(|x| (x / 3) - 2).sum();
-    println!("{}", res);
+    print_sum(breakdown().iter().map(|m| m.fuel));
 }
 
 pub fn answer2() {
-    let res: i32 = read_modules().map(fuel).sum();
-    println!("{}", res);
+    print_sum(breakdown().iter().map(|m| m.total));
 }
 
-fn fuel(module_mass: i32) -> i32 {
-    let mut mass = module_mass;
-    let mut required_fuel = 0;
-    while mass > 0 {
-        mass = cmp::max(mass / 3 - 2, 0);
-        required_fuel += mass;
+// the fuel for a single mass and all its fuel, with the equation given as
+// "<divisor>/<subtraction>"
+pub fn fuel_for(mass: &str, equation: &str) {
+    let mass: u128 = mass.trim().parse().unwrap_or_else(|_| {
+        println!("invalid mass: {}", mass);
+        std::process::exit(1);
+    });
+    let equation = parse_equation(equation).unwrap_or_else(|| {
+        println!("invalid equation: {}", equation);
+        std::process::exit(1);
+    });
+    match equation.total_fuel(mass) {
+        Ok(total) => println!("{}", total),
+        Err(err) => {
+            println!("{}", err);
+            std::process::exit(1);
+        }
     }
-    required_fuel
 }
 
-fn read_modules() -> impl Iterator<Item = i32> {
-    let f = File::open("data/2019/day01.txt").unwrap();
-    let fd = BufReader::new(f);
-    fd.lines()
-        .map(|x| i32::from_str_radix(&x.unwrap(), 10).unwrap())
+// none if it is malformed or never converges
+pub fn parse_equation(s: &str) -> Option<Equation> {
+    let mut parts = s.split('/');
+    let divisor = parts.next()?.trim().parse().ok()?;
+    let subtraction = parts.next()?.trim().parse().ok()?;
+    if parts.next().is_some() || !Equation::converges(divisor, subtraction) {
+        return None;
+    }
+    Some(Equation::new(divisor, subtraction))
+}
+
+fn breakdown() -> Vec<ModuleFuel> {
+    Equation::SANTA
+        .breakdown(&read_modules())
+        .unwrap_or_else(|err| {
+            println!("{}", err);
+            std::process::exit(1);
+        })
+}
+
+fn print_sum<I: Iterator<Item = u128>>(mut fuels: I) {
+    match fuels.try_fold(0u128, |sum, f| sum.checked_add(f)) {
+        Some(sum) => println!("{}", sum),
+        None => {
+            println!("the total fuel overflows");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn read_modules() -> Vec<u128> {
+    let file_path = "data/2019/day01.txt";
+    let text = fs::read_to_string(file_path).unwrap();
+    match rocket::parse_masses(&text) {
+        Ok(masses) => masses,
+        Err(errors) => {
+            println!("invalid module masses {}", file_path);
+            for err in errors {
+                println!("  {}", err);
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_equation() {
+        assert_eq!(parse_equation("3/2"), Some(Equation::SANTA));
+        assert_eq!(parse_equation(" 1 / 4 "), Some(Equation::new(1, 4)));
+        assert_eq!(parse_equation("1/0"), None);
+        assert_eq!(parse_equation("0/5"), None);
+        assert_eq!(parse_equation("3"), None);
+        assert_eq!(parse_equation("3/2/1"), None);
+        assert_eq!(parse_equation("three/2"), None);
+    }
 }
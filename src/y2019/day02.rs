@@ -1,38 +1,134 @@
+use combinatorics;
 use y2019::computer;
+use y2019::computer::instruction_set::InstructionSet;
+use y2019::computer::symbolic::Expr;
+
+const TARGET: i64 = 19690720;
 
 pub fn answer1() {
-    let mut codes = computer::read_codes("data/2019/day02.txt");
+    let codes = computer::read_codes("data/2019/day02.txt");
+    println!("{}", run(&codes, 12, 2));
+}
 
-    codes[1] = 12;
-    codes[2] = 2;
+pub fn answer2() {
+    let codes = computer::read_codes("data/2019/day02.txt");
+    match solve(&output_formula(&codes), TARGET) {
+        Some((noun, verb)) => println!("{}", noun * 100 + verb),
+        None => panic!("no solution found!"),
+    }
+}
 
+// the value left at address 0 with the given noun and verb
+fn run(codes: &[i64], noun: i64, verb: i64) -> i64 {
+    let mut codes = codes.to_vec();
+    codes[1] = noun;
+    codes[2] = verb;
+    let size = codes.len();
     let mut computer = computer::Computer::new(codes);
+    computer.with_instruction_set(InstructionSet::day02());
+    // the program only ever writes over itself
+    computer.with_memory_limit(size);
     match computer.run() {
-        Ok(_) => println!("{}", computer.codes[0]),
-        Err(err) => panic!("error: {:?}", err),
+        Ok(_) => computer.codes[0],
+        Err(err) => panic!("error with noun: {}, verb {}: {:?}", noun, verb, err),
     }
 }
 
-pub fn answer2() {
-    let codes = computer::read_codes("data/2019/day02.txt");
+// the value left at address 0, as a function of the noun and the verb
+fn output_formula(codes: &[i64]) -> Expr {
+    let mut computer = computer::Computer::new(codes.to_vec());
+    computer.with_instruction_set(InstructionSet::day02());
+    computer.with_memory_limit(codes.len());
+    match computer.run_symbolic(&[(1, "noun"), (2, "verb")]) {
+        Ok(memory) => memory[0].clone(),
+        Err(err) => panic!("cannot run the program symbolically: {}", err),
+    }
+}
+
+// the first noun and verb, both in 0..=99, for which the formula gives the
+// target. There is none if the formula depends on anything else.
+fn solve(formula: &Expr, target: i64) -> Option<(i64, i64)> {
+    let values: Vec<i64> = (0..=99).collect();
+    let solution = |nv: &[i64]| {
+        formula.evaluate(|name| match name {
+            "noun" => Some(nv[0]),
+            "verb" => Some(nv[1]),
+            _ => None,
+        }) == Some(target)
+    };
+    if formula.degree() > 1 {
+        return combinatorics::cartesian_product(&[values.clone(), values])
+            .find(|nv| solution(nv))
+            .map(|nv| (nv[0], nv[1]));
+    }
 
-    let target = 19690720;
-    for noun in 0..99 {
-        for verb in 0..99 {
-            let mut current_codes = codes.clone();
-            current_codes[1] = noun;
-            current_codes[2] = verb;
-            let mut computer = computer::Computer::new(current_codes);
-            match computer.run() {
-                Ok(_) => {
-                    if computer.codes[0] == target {
-                        println!("{}", noun * 100 + verb);
-                        return;
-                    }
-                }
-                Err(err) => panic!("error with noun: {}, verb {}: {:?}", noun, verb, err),
-            }
+    // a * noun + b * verb + c: given the noun, the verb is known
+    let (a, b, c) = (
+        formula.coefficient(&["noun"]),
+        formula.coefficient(&["verb"]),
+        formula.coefficient(&[]),
+    );
+    values.iter().find_map(|&noun| {
+        // no solution with this noun if the verb doesn't fit an i64
+        let rest = target.checked_sub(c)?.checked_sub(a.checked_mul(noun)?)?;
+        let verb = match b {
+            0 => 0,
+            b if rest.checked_rem(b)? == 0 => rest.checked_div(b)?,
+            _ => return None,
+        };
+        if solution(&[noun, verb]) && values.contains(&verb) {
+            Some((noun, verb))
+        } else {
+            None
         }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_output_formula() {
+        let codes = computer::read_codes("data/2019/day02.txt");
+        let formula = output_formula(&codes);
+        assert_eq!(formula.degree(), 1);
+        for &(noun, verb) in &[(12, 2), (0, 0), (99, 99), (62, 55)] {
+            let value = formula.evaluate(|name| Some(if name == "noun" { noun } else { verb }));
+            assert_eq!(value, Some(run(&codes, noun, verb)));
+        }
+        assert_eq!(solve(&formula, TARGET), Some((62, 55)));
+    }
+
+    #[test]
+    fn test_solve() {
+        let formula = |text: &[(i64, &[&str])]| {
+            text.iter().fold(Expr::constant(0), |sum, &(c, names)| {
+                let term = names.iter().fold(Expr::constant(c), |p, n| {
+                    p.checked_mul(&Expr::symbol(n)).unwrap()
+                });
+                sum.checked_add(&term).unwrap()
+            })
+        };
+        // only the verb matters, and 99 is a valid value
+        let f = formula(&[(3, &["verb"]), (1, &[])]);
+        assert_eq!(solve(&f, 298), Some((0, 99)));
+        assert_eq!(solve(&f, 299), None);
+        // only the noun matters
+        let f = formula(&[(2, &["noun"])]);
+        assert_eq!(solve(&f, 40), Some((20, 0)));
+        // not linear
+        let f = formula(&[(1, &["noun", "verb"]), (1, &["verb"])]);
+        assert_eq!(solve(&f, 99 * 100), Some((99, 99)));
+        // depends on the memory the noun points to
+        let f = formula(&[(1, &["[noun]"]), (1, &["verb"])]);
+        assert_eq!(solve(&f, 10), None);
+        // large coefficients
+        let f = formula(&[(i64::MAX, &["noun"]), (1, &["verb"])]);
+        assert_eq!(solve(&f, -5), None);
+        assert_eq!(solve(&f, i64::MAX), Some((1, 0)));
+        let f = formula(&[(-1, &["verb"]), (i64::MIN + 1, &[])]);
+        assert_eq!(solve(&f, i64::MIN), Some((0, 1)));
+        assert_eq!(solve(&f, i64::MAX), None);
     }
-    panic!("no solution found!")
 }
@@ -1,18 +1,16 @@
+use grid::{Direction, Point};
 use regex::Regex;
-use std::collections::BTreeSet;
-use std::ops::Add;
+use std::collections::{BTreeMap, HashMap};
 
+use std::fs;
 use std::fs::File;
 use std::io::BufRead;
 use std::io::BufReader;
 
-#[derive(Debug, PartialEq)]
-enum Direction {
-    Up(i32),
-    Left(i32),
-    Down(i32),
-    Right(i32),
-}
+mod render;
+
+// a straight line of the wire: which way and how many steps
+type Move = (Direction, i32);
 
 pub fn answer1() {
     let (w1, w2) = read_wires();
@@ -24,150 +22,343 @@ pub fn answer2() {
     println!("{}", shortest_intersection(w1, w2));
 }
 
-fn closest_intersection(wire1: Vec<Direction>, wire2: Vec<Direction>) -> (i32, i32) {
-    let p1 = walk_path(wire1);
-    let p2 = walk_path(wire2);
-
-    let s1: BTreeSet<(i32, i32)> = p1.into_iter().collect();
-    let s2: BTreeSet<(i32, i32)> = p2.into_iter().collect();
-
-    let mut all_intersections: Vec<(i32, i32)> = s1.intersection(&s2).cloned().collect();
-    all_intersections.sort_by(|x, y| distance(*x).cmp(&distance(*y)));
+// draw the wires from the input with their intersections, as an svg file and
+// on the terminal
+pub fn render(svg_path: &str) {
+    let wires = read_all_wires();
+    let crossings = crossings(&wires);
+    let closest = closest_crossing(&crossings);
+    let shortest = shortest_crossing(&crossings);
+    let segments: Vec<Vec<Segment>> = wires.iter().map(|w| segments(w)).collect();
+
+    let svg = render::svg(&segments, &crossings, closest, shortest);
+    fs::write(svg_path, svg).unwrap();
+    print!(
+        "{}",
+        render::ascii(&segments, &crossings, closest, shortest, 100, 50)
+    );
+    if let Some(c) = closest {
+        println!("closest: {} at distance {}", c.point, distance(c.point));
+    }
+    if let Some(c) = shortest {
+        println!("shortest: {} after {} steps", c.point, c.steps);
+    }
+}
 
-    // skip the first element since it's (0,0)
-    all_intersections[1]
+fn closest_intersection(wire1: Vec<Move>, wire2: Vec<Move>) -> Point {
+    closest_crossing(&crossings(&[wire1, wire2]))
+        .expect("the wires never cross")
+        .point
 }
 
-fn shortest_intersection(wire1: Vec<Direction>, wire2: Vec<Direction>) -> usize {
-    let p1 = walk_path(wire1);
-    let p2 = walk_path(wire2);
+fn shortest_intersection(wire1: Vec<Move>, wire2: Vec<Move>) -> usize {
+    shortest_crossing(&crossings(&[wire1, wire2]))
+        .expect("the wires never cross")
+        .steps
+}
 
-    let s1: BTreeSet<(i32, i32)> = p1.clone().into_iter().collect();
-    let s2: BTreeSet<(i32, i32)> = p2.clone().into_iter().collect();
+// ties are broken by position so that the result doesn't depend on the order
+// of the crossings
+fn closest_crossing(crossings: &[Crossing]) -> Option<&Crossing> {
+    crossings
+        .iter()
+        .min_by_key(|c| (distance(c.point), c.point))
+}
 
-    let all_intersections: Vec<(i32, i32)> = s1.intersection(&s2).cloned().collect();
+fn shortest_crossing(crossings: &[Crossing]) -> Option<&Crossing> {
+    crossings.iter().min_by_key(|c| (c.steps, c.point))
+}
 
-    let mut step1 = 0;
-    let mut step2 = 0;
-    let mut min_step = 0;
+// a straight part of a wire, and how many steps it took to reach its start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Segment {
+    start: Point,
+    end: Point,
+    steps: usize,
+}
 
-    for intersection in all_intersections {
-        if intersection == (0, 0) {
-            continue;
-        }
-        for (i, p) in (*p1).into_iter().enumerate() {
-            if *p == intersection {
-                step1 = i;
-                break;
+impl Segment {
+    // all the points shared by both segments, with the steps taken along each
+    // of them to get there. Perpendicular segments share at most one point,
+    // overlapping parallel ones can share many.
+    fn intersect(&self, other: &Segment) -> Vec<(Point, usize, usize)> {
+        let (x_min, x_max) = overlap((self.start.x, self.end.x), (other.start.x, other.end.x));
+        let (y_min, y_max) = overlap((self.start.y, self.end.y), (other.start.y, other.end.y));
+        let mut result = Vec::new();
+        for x in x_min..=x_max {
+            for y in y_min..=y_max {
+                let p = Point::new(x, y);
+                result.push((p, self.steps_to(p), other.steps_to(p)));
             }
         }
+        result
+    }
 
-        for (i, p) in (*p2).into_iter().enumerate() {
-            if *p == intersection {
-                step2 = i;
-                break;
-            }
-        }
+    fn steps_to(&self, p: Point) -> usize {
+        self.steps + self.start.manhattan_distance(p) as usize
+    }
 
-        if min_step == 0 || step1 + step2 < min_step {
-            min_step = step1 + step2;
-        }
+    // a segment of length 0 counts as horizontal
+    fn is_vertical(&self) -> bool {
+        self.start.x == self.end.x && self.start.y != self.end.y
     }
 
-    min_step
+    fn xs(&self) -> (i32, i32) {
+        (self.start.x.min(self.end.x), self.start.x.max(self.end.x))
+    }
+
+    fn ys(&self) -> (i32, i32) {
+        (self.start.y.min(self.end.y), self.start.y.max(self.end.y))
+    }
+}
+
+// intersection of two ranges given by their bounds in any order, empty when
+// the lower bound is greater than the upper one
+fn overlap((a1, a2): (i32, i32), (b1, b2): (i32, i32)) -> (i32, i32) {
+    (a1.min(a2).max(b1.min(b2)), a1.max(a2).min(b1.max(b2)))
+}
+
+fn segments(path: &[Move]) -> Vec<Segment> {
+    let mut result = Vec::with_capacity(path.len());
+    let mut start = Point::ORIGIN;
+    let mut steps = 0;
+    for &(dir, n) in path {
+        let end = start + dir.delta() * n;
+        result.push(Segment { start, end, steps });
+        start = end;
+        steps += n as usize;
+    }
+    result
+}
+
+// a point where two different wires cross
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Crossing {
+    point: Point,
+    wires: (usize, usize),
+    // fewest combined steps for both wires to reach that point
+    steps: usize,
 }
 
-fn walk_path(path: Vec<Direction>) -> Vec<(i32, i32)> {
-    let mut s = Vec::new();
-    let mut x = (0, 0);
-    for dir in path {
-        let (new_x, mut new_s) = walk_direction(x, dir);
-        s.append(&mut new_s);
-        x = new_x
+// every point, other than the central port, where at least two of the wires
+// cross. A point crossed by more than two wires appears once per pair. For
+// each pair of wires this takes O((n + k) log n) time, for n segments of which
+// k pairs touch.
+fn crossings(wires: &[Vec<Move>]) -> Vec<Crossing> {
+    let wires: Vec<Vec<Segment>> = wires.iter().map(|w| segments(w)).collect();
+    let mut best: BTreeMap<(Point, usize, usize), (usize, usize)> = BTreeMap::new();
+    for (i, segments1) in wires.iter().enumerate() {
+        for (j, segments2) in wires.iter().enumerate().skip(i + 1) {
+            for (s1, s2) in touching(segments1, segments2) {
+                for (p, steps1, steps2) in segments1[s1].intersect(&segments2[s2]) {
+                    if p == Point::ORIGIN {
+                        continue;
+                    }
+                    // a wire can go through the same point several times,
+                    // only the first one counts
+                    let entry = best.entry((p, i, j)).or_insert((steps1, steps2));
+                    entry.0 = entry.0.min(steps1);
+                    entry.1 = entry.1.min(steps2);
+                }
+            }
+        }
     }
-    s
+    best.into_iter()
+        .map(|((point, i, j), (steps1, steps2))| Crossing {
+            point,
+            wires: (i, j),
+            steps: steps1 + steps2,
+        })
+        .collect()
 }
 
-fn walk_direction(start_point: (i32, i32), dir: Direction) -> ((i32, i32), Vec<(i32, i32)>) {
-    let (step, n) = match dir {
-        Direction::Up(n) => ((1, 0), n),
-        Direction::Left(n) => ((0, -1), n),
-        Direction::Down(n) => ((-1, 0), n),
-        Direction::Right(n) => ((0, 1), n),
+// the indices of the segments, one of each wire, sharing at least one point.
+// Perpendicular segments are found by sweeping a vertical line from left to
+// right, parallel ones can only touch when they are on the same line.
+fn touching(wire1: &[Segment], wire2: &[Segment]) -> Vec<(usize, usize)> {
+    let split = |wire: &[Segment]| -> (Vec<usize>, Vec<usize>) {
+        (0..wire.len()).partition(|&i| !wire[i].is_vertical())
     };
-    let mut v = Vec::new();
-    let mut x = start_point;
-    for _ in 0..n {
-        v.push(x);
-        x = add(x, step);
+    let (horizontal1, vertical1) = split(wire1);
+    let (horizontal2, vertical2) = split(wire2);
+
+    let mut result = crossing(wire1, &horizontal1, wire2, &vertical2);
+    let flipped = crossing(wire2, &horizontal2, wire1, &vertical1);
+    result.extend(flipped.into_iter().map(|(j, i)| (i, j)));
+
+    // parallel segments, grouped by the line they are on
+    let mut lines: HashMap<(bool, i32), (Vec<Span>, Vec<Span>)> = HashMap::new();
+    for (wire, indices, second) in &[
+        (wire1, &horizontal1, false),
+        (wire1, &vertical1, false),
+        (wire2, &horizontal2, true),
+        (wire2, &vertical2, true),
+    ] {
+        for &i in indices.iter() {
+            let s = &wire[i];
+            let (line, (lo, hi)) = if s.is_vertical() {
+                ((true, s.start.x), s.ys())
+            } else {
+                ((false, s.start.y), s.xs())
+            };
+            let spans = lines.entry(line).or_default();
+            let spans = if *second { &mut spans.1 } else { &mut spans.0 };
+            spans.push((lo, hi, i));
+        }
+    }
+    for (spans1, spans2) in lines.values() {
+        result.extend(overlapping(spans1, spans2));
     }
+    result
+}
 
-    (x, v)
+// the bounds of a segment along its line, and its index
+type Span = (i32, i32, usize);
+
+// pairs of horizontal and vertical segments which cross
+fn crossing(
+    wire1: &[Segment],
+    horizontal: &[usize],
+    wire2: &[Segment],
+    vertical: &[usize],
+) -> Vec<(usize, usize)> {
+    // at the same x, horizontal segments start before the vertical ones are
+    // looked at, and end after
+    let mut events: Vec<(i32, u8, usize)> = Vec::new();
+    for &i in horizontal {
+        let (x1, x2) = wire1[i].xs();
+        events.push((x1, 0, i));
+        events.push((x2, 2, i));
+    }
+    for &j in vertical {
+        events.push((wire2[j].start.x, 1, j));
+    }
+    events.sort_unstable();
+
+    // horizontal segments under the sweep line, by their y
+    let mut active: BTreeMap<i32, Vec<usize>> = BTreeMap::new();
+    let mut result = Vec::new();
+    for (_, kind, index) in events {
+        match kind {
+            0 => active.entry(wire1[index].start.y).or_default().push(index),
+            1 => {
+                let (y1, y2) = wire2[index].ys();
+                for (_, hs) in active.range(y1..=y2) {
+                    result.extend(hs.iter().map(|&i| (i, index)));
+                }
+            }
+            _ => {
+                let y = wire1[index].start.y;
+                let hs = active.get_mut(&y).unwrap();
+                hs.retain(|&i| i != index);
+                if hs.is_empty() {
+                    active.remove(&y);
+                }
+            }
+        }
+    }
+    result
 }
 
-fn add<T: Add<Output = T>>(a: (T, T), b: (T, T)) -> (T, T) {
-    let (ax, ay) = a;
-    let (bx, by) = b;
-    (ax + bx, ay + by)
+// pairs of spans of the same line, one from each wire, which overlap
+fn overlapping(spans1: &[Span], spans2: &[Span]) -> Vec<(usize, usize)> {
+    // a span overlaps the ones still open when it starts, starts come before
+    // ends at the same position
+    let mut events: Vec<(i32, bool, bool, usize)> = Vec::new();
+    for (spans, second) in &[(spans1, false), (spans2, true)] {
+        for &(lo, hi, i) in spans.iter() {
+            events.push((lo, false, *second, i));
+            events.push((hi, true, *second, i));
+        }
+    }
+    events.sort_unstable();
+
+    let mut open: [Vec<usize>; 2] = [Vec::new(), Vec::new()];
+    let mut result = Vec::new();
+    for (_, end, second, index) in events {
+        let wire = second as usize;
+        if end {
+            open[wire].retain(|&i| i != index);
+            continue;
+        }
+        for &other in &open[1 - wire] {
+            result.push(if second {
+                (other, index)
+            } else {
+                (index, other)
+            });
+        }
+        open[wire].push(index);
+    }
+    result
 }
 
-fn distance((a, b): (i32, i32)) -> i32 {
-    i32::abs(a) + i32::abs(b)
+fn distance(p: Point) -> i32 {
+    p.manhattan_distance(Point::ORIGIN)
 }
 
-fn parse_path(path: &str) -> Result<Vec<Direction>, String> {
+fn parse_path(path: &str) -> Result<Vec<Move>, String> {
     path.split(',').map(parse_direction).collect()
 }
 
-fn parse_direction(raw: &str) -> Result<Direction, String> {
+fn parse_direction(raw: &str) -> Result<Move, String> {
     lazy_static! {
         static ref RE: Regex = Regex::new(r"^([ULDR])(\d+)$").unwrap();
     }
-    let caps = RE.captures(raw).unwrap();
+    let caps = RE
+        .captures(raw)
+        .ok_or(format!("Invalid direction: {}", raw))?;
 
     let raw_num = caps
         .get(2)
         .ok_or(format!("No number matched for: {}", raw))?
         .as_str();
-    let num = i32::from_str_radix(raw_num, 10).unwrap();
-
-    match caps.get(1).unwrap().as_str() {
-        "U" => Ok(Direction::Up(num)),
-        "L" => Ok(Direction::Left(num)),
-        "D" => Ok(Direction::Down(num)),
-        "R" => Ok(Direction::Right(num)),
-        d => Err(format!("Unknown direction: {}", d)),
+    let num = raw_num
+        .parse()
+        .map_err(|e| format!("Invalid number {}: {}", raw_num, e))?;
+
+    let d = caps.get(1).unwrap().as_str();
+    match d.chars().next().and_then(Direction::from_char) {
+        Some(dir) => Ok((dir, num)),
+        None => Err(format!("Unknown direction: {}", d)),
     }
 }
 
-fn read_wires() -> (Vec<Direction>, Vec<Direction>) {
-    let f = File::open("data/2019/day03.txt").unwrap();
-    let fd = BufReader::new(f);
-    let lines: Vec<String> = fd.lines().map(|x| x.unwrap()).collect();
-
-    let wire1 = parse_path(&(lines[0])[..]).unwrap();
-    let wire2 = parse_path(&(lines[1])[..]).unwrap();
+fn read_wires() -> (Vec<Move>, Vec<Move>) {
+    let mut wires = read_all_wires().into_iter();
+    let wire1 = wires.next().unwrap();
+    let wire2 = wires.next().unwrap();
 
     (wire1, wire2)
 }
 
+fn read_all_wires() -> Vec<Vec<Move>> {
+    let f = File::open("data/2019/day03.txt").unwrap();
+    let fd = BufReader::new(f);
+    fd.lines()
+        .map(|x| x.unwrap())
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| parse_path(l.trim()).unwrap())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_parse_direction() {
-        assert_eq!(parse_direction("U12"), Ok(Direction::Up(12)));
-        assert_eq!(parse_direction("L1"), Ok(Direction::Left(1)));
-        assert_eq!(parse_direction("D123"), Ok(Direction::Down(123)));
-        assert_eq!(parse_direction("R42"), Ok(Direction::Right(42)));
+        assert_eq!(parse_direction("U12"), Ok((Direction::Up, 12)));
+        assert_eq!(parse_direction("L1"), Ok((Direction::Left, 1)));
+        assert_eq!(parse_direction("D123"), Ok((Direction::Down, 123)));
+        assert_eq!(parse_direction("R42"), Ok((Direction::Right, 42)));
     }
 
     #[test]
     fn test_parse_path() {
         assert_eq!(
             parse_path("U7,R6"),
-            Ok(vec![Direction::Up(7), Direction::Right(6)])
+            Ok(vec![(Direction::Up, 7), (Direction::Right, 6)])
         )
     }
 
@@ -236,4 +427,89 @@ mod tests {
             410
         );
     }
+
+    #[test]
+    fn test_segments() {
+        let wire = parse_path("R8,U5").unwrap();
+        assert_eq!(
+            segments(&wire),
+            vec![
+                Segment {
+                    start: Point::new(0, 0),
+                    end: Point::new(8, 0),
+                    steps: 0
+                },
+                Segment {
+                    start: Point::new(8, 0),
+                    end: Point::new(8, -5),
+                    steps: 8
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlapping_segments() {
+        // both wires run along the same line between x=2 and x=4
+        let crossings = crossings(&[
+            parse_path("R4,U2").unwrap(),
+            parse_path("U1,R2,D1,R5").unwrap(),
+        ]);
+        let points: Vec<Point> = crossings.iter().map(|c| c.point).collect();
+        assert_eq!(
+            points,
+            vec![Point::new(2, 0), Point::new(3, 0), Point::new(4, 0)]
+        );
+        assert_eq!(crossings[0].steps, 2 + 4);
+    }
+
+    #[test]
+    fn test_touching_matches_all_pairs() {
+        // random wires, short enough to cross and overlap a lot
+        let mut state = 0x2545_f491_u64;
+        let mut random = |n: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % n
+        };
+        for _ in 0..200 {
+            let mut wire = || -> Vec<Segment> {
+                let moves: Vec<Move> = (0..random(30))
+                    .map(|_| (Direction::ALL[random(4) as usize], random(6) as i32))
+                    .collect();
+                segments(&moves)
+            };
+            let (wire1, wire2) = (wire(), wire());
+            let mut expected = Vec::new();
+            for (i, s1) in wire1.iter().enumerate() {
+                for (j, s2) in wire2.iter().enumerate() {
+                    if !s1.intersect(s2).is_empty() {
+                        expected.push((i, j));
+                    }
+                }
+            }
+            let mut pairs = touching(&wire1, &wire2);
+            pairs.sort();
+            assert_eq!(pairs, expected);
+        }
+    }
+
+    #[test]
+    fn test_crossings_many_wires() {
+        let wires = vec![
+            parse_path("R8,U5,L5,D3").unwrap(),
+            parse_path("U7,R6,D4,L4").unwrap(),
+            parse_path("L2,U2,R10").unwrap(),
+        ];
+        let crossings = crossings(&wires);
+        assert_eq!(crossings.len(), 5);
+        assert!(crossings.iter().any(|c| c.wires == (0, 1)));
+        assert!(crossings.iter().any(|c| c.wires == (0, 2)));
+        assert!(crossings.iter().any(|c| c.wires == (1, 2)));
+
+        let closest = crossings.iter().min_by_key(|c| distance(c.point)).unwrap();
+        assert_eq!(closest.point, Point::new(0, -2));
+        assert_eq!(closest.wires, (1, 2));
+    }
 }
@@ -0,0 +1,220 @@
+// Pictures of the wires, to check the intersections by eye.
+//
+// Both renderings use the same coordinates as the puzzle: x grows to the
+// right and y grows downward, so a wire going up decreases y.
+
+use std::fmt::Write;
+
+use super::{Crossing, Segment};
+use grid::{Grid, Point};
+
+const COLORS: [&str; 6] = [
+    "#1f77b4", "#ff7f0e", "#9467bd", "#8c564b", "#e377c2", "#17becf",
+];
+const WIRE_CHARS: [char; 6] = ['#', '*', '%', '@', '&', '$'];
+
+// smallest and largest coordinates reached by any wire, the central port
+// included
+fn bounds(wires: &[Vec<Segment>]) -> (Point, Point) {
+    let mut min = Point::ORIGIN;
+    let mut max = Point::ORIGIN;
+    for s in wires.iter().flat_map(|w| w.iter()) {
+        for p in &[s.start, s.end] {
+            min = Point::new(min.x.min(p.x), min.y.min(p.y));
+            max = Point::new(max.x.max(p.x), max.y.max(p.y));
+        }
+    }
+    (min, max)
+}
+
+pub fn svg(
+    wires: &[Vec<Segment>],
+    crossings: &[Crossing],
+    closest: Option<&Crossing>,
+    shortest: Option<&Crossing>,
+) -> String {
+    let (min, max) = bounds(wires);
+    let size = (max.x - min.x).max(max.y - min.y).max(1);
+    let margin = size / 50 + 1;
+    let stroke = (size as f64 / 1000.0).max(0.1);
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+        min.x - margin,
+        min.y - margin,
+        max.x - min.x + 2 * margin,
+        max.y - min.y + 2 * margin
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<rect x="{}" y="{}" width="{}" height="{}" fill="white"/>"#,
+        min.x - margin,
+        min.y - margin,
+        max.x - min.x + 2 * margin,
+        max.y - min.y + 2 * margin
+    )
+    .unwrap();
+
+    for (i, wire) in wires.iter().enumerate() {
+        let mut points = vec![Point::ORIGIN];
+        points.extend(wire.iter().map(|s| s.end));
+        let points: Vec<String> = points.iter().map(|p| format!("{},{}", p.x, p.y)).collect();
+        writeln!(
+            out,
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+            points.join(" "),
+            COLORS[i % COLORS.len()],
+            stroke
+        )
+        .unwrap();
+    }
+
+    let mut circle = |p: Point, radius: f64, color: &str| {
+        writeln!(
+            out,
+            r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+            p.x, p.y, radius, color
+        )
+        .unwrap();
+    };
+    for c in crossings {
+        circle(c.point, stroke * 3.0, "black");
+    }
+    circle(Point::ORIGIN, stroke * 6.0, "black");
+    if let Some(c) = closest {
+        circle(c.point, stroke * 8.0, "red");
+    }
+    if let Some(c) = shortest {
+        circle(c.point, stroke * 8.0, "green");
+    }
+
+    writeln!(out, "</svg>").unwrap();
+    out
+}
+
+// the wires scaled down to fit in `max_width` by `max_height` characters.
+// Each wire has its own character, `+` is where several of them share a
+// cell, `x` a crossing, `o` the central port, `C` the closest crossing, `S`
+// the one with the fewest steps and `*` if both are the same.
+pub fn ascii(
+    wires: &[Vec<Segment>],
+    crossings: &[Crossing],
+    closest: Option<&Crossing>,
+    shortest: Option<&Crossing>,
+    max_width: usize,
+    max_height: usize,
+) -> String {
+    let (min, max) = bounds(wires);
+    let span = max - min + Point::new(1, 1);
+    let ceil_div = |a: i32, b: usize| (a as usize).div_ceil(b.max(1));
+    let scale = ceil_div(span.x, max_width)
+        .max(ceil_div(span.y, max_height))
+        .max(1) as i32;
+    let to_cell = |p: Point| Point::new((p.x - min.x) / scale, (p.y - min.y) / scale);
+    let size = to_cell(max) + Point::new(1, 1);
+
+    // which wires go through each cell
+    let mut masks: Grid<u32> = Grid::new(size.x as usize, size.y as usize, 0);
+    for (i, wire) in wires.iter().enumerate() {
+        for s in wire {
+            let (from, to) = (to_cell(s.start), to_cell(s.end));
+            let step = Point::new((to.x - from.x).signum(), (to.y - from.y).signum());
+            let mut p = from;
+            masks[p] |= 1 << (i % 32);
+            while p != to {
+                p += step;
+                masks[p] |= 1 << (i % 32);
+            }
+        }
+    }
+
+    let mut picture = masks.map(|mask| match mask.count_ones() {
+        0 => ' ',
+        1 => WIRE_CHARS[mask.trailing_zeros() as usize % WIRE_CHARS.len()],
+        _ => '+',
+    });
+    for c in crossings {
+        picture[to_cell(c.point)] = 'x';
+    }
+    picture[to_cell(Point::ORIGIN)] = 'o';
+    if let Some(c) = closest {
+        picture[to_cell(c.point)] = 'C';
+    }
+    if let Some(c) = shortest {
+        let cell = to_cell(c.point);
+        picture[cell] = if picture[cell] == 'C' { '*' } else { 'S' };
+    }
+    picture.render(|c| *c)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{closest_crossing, crossings, parse_path, segments, shortest_crossing};
+    use super::*;
+
+    fn example() -> (Vec<Vec<Segment>>, Vec<Crossing>) {
+        let wires = vec![
+            parse_path("R8,U5,L5,D3").unwrap(),
+            parse_path("U7,R6,D4,L4").unwrap(),
+        ];
+        let segments = wires.iter().map(|w| segments(w)).collect();
+        (segments, crossings(&wires))
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        let (wires, crossings) = example();
+        let picture = ascii(
+            &wires,
+            &crossings,
+            closest_crossing(&crossings),
+            shortest_crossing(&crossings),
+            80,
+            40,
+        );
+        let lines: Vec<&str> = picture.lines().map(|l| l.trim_end()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "*******",
+                "*     *",
+                "*  ###S##",
+                "*  #  * #",
+                "* *C*** #",
+                "*  #    #",
+                "*       #",
+                "o########",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_ascii_scaled() {
+        let (wires, crossings) = example();
+        let picture = ascii(&wires, &crossings, None, None, 4, 4);
+        // 9 by 8 cells, three times smaller
+        assert_eq!(picture.lines().count(), 3);
+        assert!(picture.lines().all(|l| l.chars().count() == 3));
+        assert!(picture.contains('o'));
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let (wires, crossings) = example();
+        let svg = svg(
+            &wires,
+            &crossings,
+            closest_crossing(&crossings),
+            shortest_crossing(&crossings),
+        );
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<polyline").count(), 2);
+        assert!(svg.contains(r#"points="0,0 8,0 8,-5 3,-5 3,-2""#));
+        assert!(svg.contains(r#"<circle cx="3" cy="-3" r="0.8" fill="red"/>"#));
+        assert!(svg.contains(r#"<circle cx="6" cy="-5" r="0.8" fill="green"/>"#));
+    }
+}
@@ -1,33 +1,188 @@
-const MIN_VAL: u32 = 273025;
-const MAX_VAL: u32 = 767253;
+use std::cmp;
+use std::collections::HashMap;
+use std::fs;
 
-pub fn answer1() {
-    let stuff: Vec<u32> = (MIN_VAL..MAX_VAL + 1)
-        .into_iter()
-        .filter(|x| is_potential_password1(*x))
-        .collect();
+// Passwords never decrease from left to right, so equal digits are always
+// next to each other and the rules only look at the runs of identical
+// digits. The numbers of the range are counted without listing them, digit by
+// digit from the left: how many ways there are to complete some digits only
+// depends on the last one and on the runs so far, which is memoised.
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub enum Rule {
+    // two adjacent digits are the same
+    Pair,
+    // two adjacent digits are the same, and not part of a larger group
+    ExactPair,
+    // no group of identical digits is longer than that
+    MaxRun(usize),
+    // some group of identical digits is at least that long
+    MinRun(usize),
+}
+
+impl Rule {
+    fn check(&self, runs: Runs) -> bool {
+        match *self {
+            Rule::Pair => runs.longest >= 2,
+            Rule::ExactPair => runs.pair,
+            Rule::MaxRun(n) => runs.longest <= n,
+            Rule::MinRun(n) => runs.longest >= n,
+        }
+    }
+}
 
-    println!("{}", stuff.len());
+// what the rules need to know about the runs of identical digits: the length
+// of the last one, the longest one before it and whether one of those was a
+// pair
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+struct Runs {
+    current: usize,
+    longest: usize,
+    pair: bool,
+}
+
+impl Runs {
+    fn start() -> Runs {
+        Runs {
+            current: 1,
+            ..Runs::default()
+        }
+    }
+
+    // one more digit, the same as the previous one or not
+    fn push(self, same: bool) -> Runs {
+        if same {
+            Runs {
+                current: self.current + 1,
+                ..self
+            }
+        } else {
+            Runs {
+                current: 1,
+                ..self.ended()
+            }
+        }
+    }
+
+    // once the last run is over
+    fn ended(self) -> Runs {
+        Runs {
+            current: 0,
+            longest: cmp::max(self.longest, self.current),
+            pair: self.pair || self.current == 2,
+        }
+    }
+}
+
+const RULES1: [Rule; 1] = [Rule::Pair];
+const RULES2: [Rule; 1] = [Rule::ExactPair];
+
+pub fn answer1() {
+    let (min, max) = read_range();
+    println!("{}", count_passwords(min, max, &RULES1));
 }
 
 pub fn answer2() {
-    let stuff: Vec<u32> = (MIN_VAL..MAX_VAL + 1)
-        .into_iter()
-        .filter(|x| is_potential_password2(*x))
-        .collect();
+    let (min, max) = read_range();
+    println!("{}", count_passwords(min, max, &RULES2));
+}
+
+fn read_range() -> (u32, u32) {
+    let raw = fs::read_to_string("data/2019/day04.txt").unwrap();
+    parse_range(&raw).unwrap()
+}
+
+fn parse_range(raw: &str) -> Result<(u32, u32), String> {
+    let mut bounds = raw.trim().splitn(2, '-').map(|b| {
+        b.trim()
+            .parse::<u32>()
+            .map_err(|e| format!("invalid bound {:?}: {}", b, e))
+    });
+    match (bounds.next(), bounds.next()) {
+        (Some(min), Some(max)) => {
+            let (min, max) = (min?, max?);
+            if min > max {
+                Err(format!("empty range {}-{}", min, max))
+            } else {
+                Ok((min, max))
+            }
+        }
+        _ => Err(format!("expected MIN-MAX, got {:?}", raw.trim())),
+    }
+}
+
+fn count_passwords(min: u32, max: u32, rules: &[Rule]) -> usize {
+    let below = if min == 0 {
+        0
+    } else {
+        count_up_to(min - 1, rules)
+    };
+    count_up_to(max, rules) - below
+}
 
-    println!("{}", stuff.len());
+// how many numbers in 0..=max have non decreasing digits following the rules
+fn count_up_to(max: u32, rules: &[Rule]) -> usize {
+    let bound: Vec<u8> = max.to_string().bytes().map(|b| b - b'0').collect();
+    let mut memo = HashMap::new();
+    count_from(&bound, 0, None, Runs::default(), true, rules, &mut memo)
 }
 
+type Memo = HashMap<(usize, Option<u8>, Runs), usize>;
+
+// numbers completing the digits placed before `pos`. `last` is the previous
+// digit, none while there are only leading zeros, and `tight` tells if the
+// digits so far are the ones of the bound, which limits the next one.
+fn count_from(
+    bound: &[u8],
+    pos: usize,
+    last: Option<u8>,
+    runs: Runs,
+    tight: bool,
+    rules: &[Rule],
+    memo: &mut Memo,
+) -> usize {
+    if pos == bound.len() {
+        // 0 is written with a single digit
+        let runs = if last.is_none() { Runs::start() } else { runs };
+        return rules.iter().all(|r| r.check(runs.ended())) as usize;
+    }
+    if !tight {
+        if let Some(&count) = memo.get(&(pos, last, runs)) {
+            return count;
+        }
+    }
+
+    let limit = if tight { bound[pos] } else { 9 };
+    let mut count = 0;
+    for d in last.unwrap_or(0)..=limit {
+        let (next, next_runs) = match last {
+            None if d == 0 => (None, runs),
+            None => (Some(d), Runs::start()),
+            Some(l) => (Some(d), runs.push(d == l)),
+        };
+        let tight = tight && d == limit;
+        count += count_from(bound, pos + 1, next, next_runs, tight, rules, memo);
+    }
+    if !tight {
+        memo.insert((pos, last, runs), count);
+    }
+    count
+}
+
+// the original brute force checks, which the counts are tested against
+#[cfg(test)]
 fn is_potential_password1(number: u32) -> bool {
     has_increasing_digits(number) && has_two_consecutive_identical_digits(number)
 }
 
+#[cfg(test)]
 fn is_potential_password2(number: u32) -> bool {
     has_increasing_digits(number)
         && not_part_larget_group(number)
 }
 
+#[cfg(test)]
 fn has_increasing_digits(number: u32) -> bool {
     if number < 10 {
         return true;
@@ -42,11 +197,12 @@ fn has_increasing_digits(number: u32) -> bool {
             return false;
         }
         last_digit = d;
-        n = n / 10;
+        n /= 10;
     }
     true
 }
 
+#[cfg(test)]
 fn has_two_consecutive_identical_digits(number: u32) -> bool {
     if number < 10 {
         return false;
@@ -61,11 +217,12 @@ fn has_two_consecutive_identical_digits(number: u32) -> bool {
             return true;
         }
         last_digit = d;
-        n = n / 10;
+        n /= 10;
     }
     false
 }
 
+#[cfg(test)]
 fn not_part_larget_group(number: u32) -> bool {
     if number < 10 {
         return true;
@@ -78,7 +235,7 @@ fn not_part_larget_group(number: u32) -> bool {
     while n != 0 {
         let d = n % 10;
         if last_digit == d {
-            seq_len = seq_len + 1
+            seq_len += 1
         } else {
             if seq_len == 2 {
                 return true;
@@ -86,12 +243,13 @@ fn not_part_larget_group(number: u32) -> bool {
             seq_len = 1;
             last_digit = d;
         }
-        n = n / 10;
+        n /= 10;
     }
     seq_len == 2
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
@@ -130,4 +288,40 @@ mod tests {
         assert_eq!(not_part_larget_group(126666), false);
         assert_eq!(not_part_larget_group(116666), true);
     }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("273025-767253\n"), Ok((273025, 767253)));
+        assert!(parse_range("12").is_err());
+        assert!(parse_range("12-x").is_err());
+        assert!(parse_range("20-10").is_err());
+    }
+
+    #[test]
+    fn test_rules() {
+        let check = |n: u32, rules: &[Rule]| count_passwords(n, n, rules) == 1;
+        assert!(check(111111, &RULES1));
+        assert!(!check(223450, &RULES1));
+        assert!(!check(123789, &RULES1));
+        assert!(check(112233, &RULES2));
+        assert!(!check(123444, &RULES2));
+        assert!(check(111122, &RULES2));
+        assert!(check(111123, &[Rule::MinRun(4), Rule::MaxRun(4)]));
+        assert!(!check(111113, &[Rule::MaxRun(4)]));
+        assert!(check(0, &[Rule::MaxRun(1)]));
+        assert!(!check(0, &[Rule::Pair]));
+    }
+
+    #[test]
+    fn test_count_matches_brute_force() {
+        for &(min, max) in &[(10, 2000), (9_000, 12_345), (273_025, 300_000), (99, 100)] {
+            let expected1 = (min..=max).filter(|&n| is_potential_password1(n)).count();
+            let expected2 = (min..=max).filter(|&n| is_potential_password2(n)).count();
+            assert_eq!(count_passwords(min, max, &RULES1), expected1);
+            assert_eq!(count_passwords(min, max, &RULES2), expected2);
+        }
+        assert_eq!(count_passwords(0, u32::MAX, &[]), 89375);
+        assert_eq!(count_passwords(273025, 767253, &RULES1), 910);
+        assert_eq!(count_passwords(273025, 767253, &RULES2), 598);
+    }
 }
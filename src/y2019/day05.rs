@@ -1,4 +1,5 @@
 use y2019::computer as computer;
+use y2019::computer::instruction_set::InstructionSet;
 
 pub fn answer1() {
     println!("{:?}", solve(1));
@@ -10,7 +11,11 @@ pub fn answer2() {
 
 fn solve(input: i64) -> i64 {
     let codes = computer::read_codes("data/2019/day05.txt");
+    // the diagnostic program only works in its own memory
+    let size = codes.len();
     let mut computer = computer::Computer::new(codes);
+    computer.with_instruction_set(InstructionSet::day05());
+    computer.with_memory_limit(size);
     computer.with_input(vec![input]);
     computer.run_until_halt().unwrap();
     *computer.outputs.last().unwrap()
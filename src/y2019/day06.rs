@@ -1,131 +1,298 @@
+use graph::{Graph, NodeId, Tree};
 use regex::Regex;
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
 use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::fs;
 use std::fs::File;
 use std::io::BufRead;
 use std::io::BufReader;
 
+mod export;
+
+const ROOT: &str = "COM";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrbitError {
+    Empty,
+    MalformedLine {
+        line: usize,
+        content: String,
+    },
+    DuplicateParent {
+        body: String,
+        line: usize,
+        first_line: usize,
+    },
+    Cycle {
+        bodies: Vec<String>,
+    },
+    MissingRoot {
+        root: String,
+    },
+    MultipleRoots {
+        roots: Vec<String>,
+    },
+    Unreachable {
+        root: String,
+        bodies: Vec<String>,
+    },
+}
+
+impl fmt::Display for OrbitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrbitError::Empty => write!(f, "there are no orbits"),
+            OrbitError::MalformedLine { line, content } => {
+                write!(f, "line {}: expected CENTER)BODY, got {:?}", line, content)
+            }
+            OrbitError::DuplicateParent {
+                body,
+                line,
+                first_line,
+            } => write!(
+                f,
+                "line {}: {} already orbits around something else since line {}",
+                line, body, first_line
+            ),
+            OrbitError::Cycle { bodies } => {
+                write!(f, "orbits form a cycle: {}", bodies.join(" -> "))
+            }
+            OrbitError::MissingRoot { root } => write!(f, "there is no {} in the map", root),
+            OrbitError::MultipleRoots { roots } => write!(
+                f,
+                "several bodies don't orbit around anything: {}",
+                roots.join(", ")
+            ),
+            OrbitError::Unreachable { root, bodies } => write!(
+                f,
+                "{} bodies are not orbiting around {}: {}",
+                bodies.len(),
+                root,
+                bodies.join(", ")
+            ),
+        }
+    }
+}
+
+impl error::Error for OrbitError {}
+
 pub fn answer1() {
-    let edges = read_edges("data/2019/day06.txt");
-    let orbits = build_tree(edges);
-    let result = count_orbits(&orbits, &"COM".to_string(), 0);
+    let orbits = load("data/2019/day06.txt");
+    let result = count_orbits(&orbits, ROOT);
     println!("{}", result);
 }
 
 pub fn answer2() {
-    let edges = read_edges("data/2019/day06.txt");
-    let graph = build_graph(edges);
-    let paths = shortest_path(&graph, &"YOU".to_string());
-    println!("{}", paths.get("SAN").unwrap() - 2);
-}
-
-#[allow(dead_code)]
-fn read_edges(file_path: &str) -> Vec<(String, String)> {
-    let f = File::open(file_path).unwrap();
-    let fd = BufReader::new(f);
-    let re: Regex = Regex::new(r"^([^\)]+)\)(.+)$").unwrap();
-    fd.lines()
-        .map(|l| {
-            let raw = l.unwrap().clone();
-            let caps = re.captures(&raw).unwrap();
-            (
-                caps.get(1).unwrap().as_str().to_string(),
-                caps.get(2).unwrap().as_str().to_string(),
-            )
-        })
-        .collect()
-}
-
-fn build_tree(edges: Vec<(String, String)>) -> HashMap<String, Vec<String>> {
-    let mut m: HashMap<String, Vec<String>> = HashMap::new();
-    for (center, orb) in edges.iter() {
-        let orbits = m.entry(center.to_string()).or_insert(vec![]);
-        orbits.push(orb.to_string());
-    }
-    m
-}
-
-// same as build_tree, but also add the edges child -> parent
-fn build_graph(edges: Vec<(String, String)>) -> HashMap<String, Vec<String>> {
-    let all_edges: Vec<(String, String)> = edges
-        .iter()
-        .flat_map(|(src, dest)| {
-            let a: String = src.to_string();
-            let b: String = dest.to_string();
-            vec![(a.clone(), b.clone()), (b, a)]
-        })
-        .collect();
-    build_tree(all_edges)
-}
-
-fn count_orbits(orbits: &HashMap<String, Vec<String>>, source: &String, current: u32) -> u32 {
-    match orbits.get(source) {
-        None => current,
-        Some(others) => {
-            let x: u32 = others
-                .iter()
-                .map(|o| count_orbits(orbits, &o, current + 1))
-                .sum();
-            x + current
+    let orbits = load("data/2019/day06.txt");
+    let tree = orbit_tree(&orbits);
+    println!("{}", transfers(&orbits, &tree, "YOU", "SAN").unwrap());
+}
+
+// answer the queries of the file, one per line with words separated by
+// spaces: `A B` asks for the orbital transfers from A to B, and
+// `within A K` for the bodies A can be orbiting after at most K transfers
+pub fn answer_queries(query_path: &str) {
+    let orbits = load("data/2019/day06.txt");
+    let tree = orbit_tree(&orbits);
+    let f = File::open(query_path).unwrap();
+    for line in BufReader::new(f).lines() {
+        let line = line.unwrap();
+        let bodies: Vec<&str> = line.split_whitespace().collect();
+        match bodies[..] {
+            [] => continue,
+            [a, b] => match transfers(&orbits, &tree, a, b) {
+                Some(n) => println!("{} {} {}", a, b, n),
+                None => println!("{} {} unreachable", a, b),
+            },
+            ["within", a, k] => match k.parse() {
+                Ok(k) => match reachable(&orbits, &tree, a, k) {
+                    Some(bodies) => println!("within {} {} {}", a, k, bodies.join(" ")),
+                    None => println!("within {} {} unreachable", a, k),
+                },
+                Err(_) => println!("invalid query: {}", line),
+            },
+            _ => println!("invalid query: {}", line),
         }
     }
 }
 
-#[derive(Clone, Eq, PartialEq)]
-struct State {
-    cost: usize,
-    node: String,
+// print the orbit map as graphviz or json. With `highlight`, the path from
+// YOU to SAN stands out in the graphviz output.
+pub fn export(format: &str, highlight: bool) {
+    let orbits = load("data/2019/day06.txt");
+    let tree = orbit_tree(&orbits);
+    match format {
+        "dot" => {
+            let path = if highlight {
+                transfer_path(&orbits, &tree, "YOU", "SAN")
+            } else {
+                None
+            };
+            print!("{}", export::dot(&orbits, path.as_deref()));
+        }
+        "json" => println!("{}", export::json(&orbits, &tree)),
+        _ => println!("Unknown export format: {}", format),
+    }
 }
 
-// implement Ord so that the heap becomes a min-heap instead of a max-heap
-impl Ord for State {
-    fn cmp(&self, other: &State) -> Ordering {
-        other
-            .cost
-            .cmp(&self.cost)
-            .then_with(|| self.node.cmp(&other.node))
+// the orbit map, or a report of everything wrong with it
+fn load(file_path: &str) -> Graph<String> {
+    match read_orbit_map(file_path) {
+        Ok(orbits) => orbits,
+        Err(errors) => {
+            println!("invalid orbit map {}", file_path);
+            for err in errors {
+                println!("  {}", err);
+            }
+            std::process::exit(1);
+        }
     }
 }
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &State) -> Option<Ordering> {
-        Some(self.cmp(other))
+fn read_orbit_map(file_path: &str) -> Result<Graph<String>, Vec<OrbitError>> {
+    let text = fs::read_to_string(file_path).unwrap();
+    let orbits = build_tree(parse_edges(&text)?);
+    let errors = check_tree(&orbits, ROOT);
+    if errors.is_empty() {
+        Ok(orbits)
+    } else {
+        Err(errors)
     }
 }
 
-#[allow(dead_code)]
-fn shortest_path(graph: &HashMap<String, Vec<String>>, source: &String) -> HashMap<String, usize> {
-    let mut heap = BinaryHeap::new();
-    let mut dists: HashMap<String, usize> = HashMap::new();
-    dists.insert(source.clone(), 0);
-    heap.push(State {
-        cost: 0,
-        node: source.to_string(),
-    });
-
-    while let Some(State { cost, node }) = heap.pop() {
-        if let Some(d) = dists.get(&node) {
-            if &cost > d {
+// one orbit per line, skipping the empty ones. Each body orbits directly
+// around a single center.
+fn parse_edges(text: &str) -> Result<Vec<(String, String)>, Vec<OrbitError>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^([^)\s]+)\)([^)\s]+)$").unwrap();
+    }
+    let mut edges = Vec::new();
+    let mut errors = Vec::new();
+    let mut parent_lines: HashMap<&str, usize> = HashMap::new();
+    for (i, raw) in text.lines().enumerate() {
+        let line = i + 1;
+        let raw = raw.trim();
+        if raw.is_empty() {
+            continue;
+        }
+        let caps = match RE.captures(raw) {
+            Some(caps) => caps,
+            None => {
+                errors.push(OrbitError::MalformedLine {
+                    line,
+                    content: raw.to_string(),
+                });
                 continue;
             }
+        };
+        let (center, body) = (caps.get(1).unwrap().as_str(), caps.get(2).unwrap().as_str());
+        if let Some(&first_line) = parent_lines.get(body) {
+            errors.push(OrbitError::DuplicateParent {
+                body: body.to_string(),
+                line,
+                first_line,
+            });
+            continue;
         }
+        parent_lines.insert(body, line);
+        edges.push((center.to_string(), body.to_string()));
+    }
+    if errors.is_empty() {
+        Ok(edges)
+    } else {
+        Err(errors)
+    }
+}
 
-        // for each node reachable from the current one, see if there is a shorter
-        // path going through this node
-        for neighbour in graph.get(&node).unwrap() {
-            let next = State {
-                cost: cost + 1,
-                node: neighbour.to_string(),
-            };
-            if next.cost < *dists.get(&next.node).unwrap_or(&std::usize::MAX) {
-                dists.insert(next.node.to_string(), next.cost);
-                heap.push(next);
+// problems preventing the orbits from being a single tree around `root`
+fn check_tree(orbits: &Graph<String>, root: &str) -> Vec<OrbitError> {
+    let names = |ids: Vec<usize>| -> Vec<String> {
+        let mut names: Vec<String> = ids.into_iter().map(|n| orbits.name(n).clone()).collect();
+        names.sort();
+        names
+    };
+    if orbits.is_empty() {
+        return vec![OrbitError::Empty];
+    }
+    let mut errors = Vec::new();
+    if let Some(cycle) = orbits.find_cycle() {
+        errors.push(OrbitError::Cycle {
+            bodies: cycle.into_iter().map(|n| orbits.name(n).clone()).collect(),
+        });
+    }
+    let roots = orbits.roots();
+    if roots.len() > 1 {
+        errors.push(OrbitError::MultipleRoots {
+            roots: names(roots),
+        });
+    }
+    match orbits.id(root) {
+        None => errors.push(OrbitError::MissingRoot {
+            root: root.to_string(),
+        }),
+        Some(id) => {
+            let depths = orbits.bfs(id);
+            let unreachable: Vec<usize> = orbits.nodes().filter(|&n| depths[n].is_none()).collect();
+            if !unreachable.is_empty() {
+                errors.push(OrbitError::Unreachable {
+                    root: root.to_string(),
+                    bodies: names(unreachable),
+                });
             }
         }
     }
+    errors
+}
+
+// edges go from the center to the body orbiting around it
+fn build_tree(edges: Vec<(String, String)>) -> Graph<String> {
+    let mut g = Graph::directed();
+    for (center, orb) in edges {
+        g.add_edge(center, orb);
+    }
+    g
+}
+
+// each body orbits directly around its center, and indirectly around all the
+// centers leading to the root: its depth
+fn count_orbits(orbits: &Graph<String>, root: &str) -> usize {
+    match orbits.id(root) {
+        None => 0,
+        Some(root) => orbits.bfs(root).into_iter().flatten().sum(),
+    }
+}
+
+// rooted at the universal center of mass
+fn orbit_tree(orbits: &Graph<String>) -> Tree {
+    Tree::from_graph(orbits, orbits.id(ROOT).expect("no root in the orbit map"))
+}
+
+// orbital transfers needed to go from the object `a` orbits to the one `b`
+// orbits, `None` if one of them is unknown or is the root
+fn transfers(orbits: &Graph<String>, tree: &Tree, a: &str, b: &str) -> Option<usize> {
+    let a = tree.parent(orbits.id(a)?)?;
+    let b = tree.parent(orbits.id(b)?)?;
+    tree.distance(a, b)
+}
+
+// the bodies which the object `a` can orbit around after at most `k`
+// transfers, sorted by name
+fn reachable(orbits: &Graph<String>, tree: &Tree, a: &str, k: usize) -> Option<Vec<String>> {
+    let a = orbits.id(a)?;
+    let mut bodies: Vec<String> = tree
+        .within(tree.parent(a)?, k)
+        .into_iter()
+        .filter(|&(n, _)| n != a)
+        .map(|(n, _)| orbits.name(n).clone())
+        .collect();
+    bodies.sort();
+    Some(bodies)
+}
 
-    dists
+// the bodies from `a` to `b`, both included
+fn transfer_path(orbits: &Graph<String>, tree: &Tree, a: &str, b: &str) -> Option<Vec<NodeId>> {
+    tree.path(orbits.id(a)?, orbits.id(b)?)
 }
 
 #[cfg(test)]
@@ -134,16 +301,122 @@ mod test {
 
     #[test]
     fn test_count_orbits_simple() {
-        let edges = read_edges("data/2019/day06_test.txt");
-        let orbits = build_tree(edges);
-        assert_eq!(count_orbits(&orbits, &"COM".to_string(), 0), 42);
+        let orbits = read_orbit_map("data/2019/day06_test.txt").unwrap();
+        assert_eq!(count_orbits(&orbits, "COM"), 42);
     }
 
     #[test]
     fn test_orbit_transfer() {
-        let edges = read_edges("data/2019/day06_test2.txt");
-        let graph = build_graph(edges);
-        let paths = shortest_path(&graph, &"YOU".to_string());
-        assert_eq!(paths.get("SAN"), Some(&6));
+        let orbits = read_orbit_map("data/2019/day06_test2.txt").unwrap();
+        let tree = orbit_tree(&orbits);
+        let you = orbits.id("YOU").unwrap();
+        let san = orbits.id("SAN").unwrap();
+        assert_eq!(tree.distance(you, san), Some(6));
+        assert_eq!(transfers(&orbits, &tree, "YOU", "SAN"), Some(4));
+        assert_eq!(transfers(&orbits, &tree, "SAN", "YOU"), Some(4));
+        assert_eq!(transfers(&orbits, &tree, "YOU", "L"), Some(0));
+        assert_eq!(transfers(&orbits, &tree, "YOU", "J"), Some(2));
+        assert_eq!(transfers(&orbits, &tree, "YOU", "COM"), None);
+        assert_eq!(transfers(&orbits, &tree, "YOU", "nowhere"), None);
+    }
+
+    #[test]
+    fn test_bodies_within() {
+        let orbits = read_orbit_map("data/2019/day06_test2.txt").unwrap();
+        let tree = orbit_tree(&orbits);
+        let mut near: Vec<&str> = tree
+            .within(orbits.id("D").unwrap(), 1)
+            .into_iter()
+            .map(|(n, _)| orbits.name(n).as_str())
+            .collect();
+        near.sort();
+        assert_eq!(near, vec!["C", "D", "E", "I"]);
+
+        assert_eq!(
+            reachable(&orbits, &tree, "YOU", 0),
+            Some(vec!["K".to_string()])
+        );
+        assert_eq!(
+            reachable(&orbits, &tree, "YOU", 1),
+            Some(vec!["J".to_string(), "K".to_string(), "L".to_string()])
+        );
+        let four = reachable(&orbits, &tree, "YOU", 4).unwrap();
+        assert!(four.contains(&"I".to_string()));
+        assert!(!four.contains(&"H".to_string()));
+        assert_eq!(reachable(&orbits, &tree, "COM", 1), None);
+        assert_eq!(reachable(&orbits, &tree, "nowhere", 1), None);
+    }
+
+    fn check(text: &str) -> Vec<OrbitError> {
+        match parse_edges(text) {
+            Ok(edges) => check_tree(&build_tree(edges), ROOT),
+            Err(errors) => errors,
+        }
+    }
+
+    #[test]
+    fn test_valid_orbit_map() {
+        assert_eq!(check("COM)B\nB)C\n\nB)D\n"), vec![]);
+    }
+
+    #[test]
+    fn test_malformed_lines() {
+        assert_eq!(
+            check("COM)B\nB C\nB)C)D\nC)\n"),
+            vec![
+                OrbitError::MalformedLine {
+                    line: 2,
+                    content: "B C".to_string()
+                },
+                OrbitError::MalformedLine {
+                    line: 3,
+                    content: "B)C)D".to_string()
+                },
+                OrbitError::MalformedLine {
+                    line: 4,
+                    content: "C)".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_parent() {
+        assert_eq!(
+            check("COM)B\nCOM)C\nB)D\nC)D\n"),
+            vec![OrbitError::DuplicateParent {
+                body: "D".to_string(),
+                line: 4,
+                first_line: 3
+            }]
+        );
+    }
+
+    #[test]
+    fn test_structural_errors() {
+        let strings =
+            |names: &[&str]| -> Vec<String> { names.iter().map(|n| n.to_string()).collect() };
+        assert_eq!(
+            check("COM)B\nB)C\nX)Y\nP)Q\nQ)R\nR)P\n"),
+            vec![
+                OrbitError::Cycle {
+                    bodies: strings(&["P", "Q", "R"])
+                },
+                OrbitError::MultipleRoots {
+                    roots: strings(&["COM", "X"])
+                },
+                OrbitError::Unreachable {
+                    root: "COM".to_string(),
+                    bodies: strings(&["P", "Q", "R", "X", "Y"])
+                },
+            ]
+        );
+        assert_eq!(
+            check("A)B\n"),
+            vec![OrbitError::MissingRoot {
+                root: "COM".to_string()
+            }]
+        );
+        assert_eq!(check("\n"), vec![OrbitError::Empty]);
     }
 }
@@ -0,0 +1,164 @@
+// The orbit map in formats other tools understand: Graphviz to look at it,
+// JSON to inspect it.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use graph::{Graph, NodeId, Tree};
+
+// one edge from each center to the bodies orbiting around it, drawn once
+// without arrows if the orbits were loaded undirected. The bodies and orbits
+// along `highlight`, if any, are drawn in red.
+pub fn dot(orbits: &Graph<String>, highlight: Option<&[NodeId]>) -> String {
+    let path = highlight.unwrap_or(&[]);
+    let on_path: HashSet<NodeId> = path.iter().cloned().collect();
+    let path_edges: HashSet<(NodeId, NodeId)> = path
+        .windows(2)
+        .flat_map(|w| vec![(w[0], w[1]), (w[1], w[0])])
+        .collect();
+
+    let (kind, arrow) = if orbits.is_directed() {
+        ("digraph", "->")
+    } else {
+        ("graph", "--")
+    };
+    let mut out = String::new();
+    writeln!(out, "{} orbits {{", kind).unwrap();
+    writeln!(out, "    rankdir=LR;").unwrap();
+    for node in orbits.nodes() {
+        if on_path.contains(&node) {
+            writeln!(
+                out,
+                "    {} [color=red, fontcolor=red];",
+                quote(orbits.name(node))
+            )
+            .unwrap();
+        }
+    }
+    for center in orbits.nodes() {
+        for body in orbits.neighbours(center) {
+            // undirected edges are listed from both ends
+            if !orbits.is_directed() && body < center {
+                continue;
+            }
+            write!(
+                out,
+                "    {} {} {}",
+                quote(orbits.name(center)),
+                arrow,
+                quote(orbits.name(body))
+            )
+            .unwrap();
+            if path_edges.contains(&(center, body)) {
+                write!(out, " [color=red, penwidth=3]").unwrap();
+            }
+            writeln!(out, ";").unwrap();
+        }
+    }
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+// each body with the ones orbiting around it:
+// {"name":"COM","children":[{"name":"B","children":[]}]}
+pub fn json(orbits: &Graph<String>, tree: &Tree) -> String {
+    let mut out = String::new();
+    write_json(&mut out, orbits, tree, tree.root());
+    out
+}
+
+fn write_json(out: &mut String, orbits: &Graph<String>, tree: &Tree, node: NodeId) {
+    write!(
+        out,
+        "{{\"name\":{},\"children\":[",
+        quote(orbits.name(node))
+    )
+    .unwrap();
+    for (i, &child) in tree.children(node).iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_json(out, orbits, tree, child);
+    }
+    out.push_str("]}");
+}
+
+// a double quoted string, valid in both formats
+fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{build_tree, orbit_tree, parse_edges, transfer_path};
+    use super::*;
+
+    fn example() -> Graph<String> {
+        build_tree(parse_edges("COM)B\nB)C\nB)YOU\nC)SAN\n").unwrap())
+    }
+
+    #[test]
+    fn test_export_dot() {
+        let orbits = example();
+        assert_eq!(
+            dot(&orbits, None),
+            "digraph orbits {
+    rankdir=LR;
+    \"COM\" -> \"B\";
+    \"B\" -> \"C\";
+    \"B\" -> \"YOU\";
+    \"C\" -> \"SAN\";
+}
+"
+        );
+
+        let tree = orbit_tree(&orbits);
+        let path = transfer_path(&orbits, &tree, "YOU", "SAN").unwrap();
+        let highlighted = dot(&orbits, Some(&path));
+        assert!(highlighted.contains("\"B\" [color=red, fontcolor=red];"));
+        assert!(highlighted.contains("\"C\" [color=red, fontcolor=red];"));
+        assert!(highlighted.contains("\"YOU\" [color=red, fontcolor=red];"));
+        assert!(!highlighted.contains("\"COM\" [color=red"));
+        assert!(highlighted.contains("\"B\" -> \"C\" [color=red, penwidth=3];"));
+        assert!(highlighted.contains("\"B\" -> \"YOU\" [color=red, penwidth=3];"));
+        assert!(highlighted.contains("\"COM\" -> \"B\";"));
+
+        let mut undirected = Graph::undirected();
+        undirected.add_edge("COM".to_string(), "B".to_string());
+        undirected.add_edge("B".to_string(), "C".to_string());
+        assert_eq!(
+            dot(&undirected, None),
+            "graph orbits {
+    rankdir=LR;
+    \"COM\" -- \"B\";
+    \"B\" -- \"C\";
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_export_json() {
+        let orbits = example();
+        let tree = orbit_tree(&orbits);
+        assert_eq!(
+            json(&orbits, &tree),
+            r#"{"name":"COM","children":[{"name":"B","children":[{"name":"C","children":[{"name":"SAN","children":[]}]},{"name":"YOU","children":[]}]}]}"#
+        );
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote(r#"a"b\c"#), r#""a\"b\\c""#);
+    }
+}
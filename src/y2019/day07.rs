@@ -1,5 +1,10 @@
+use combinatorics;
+use search;
 use y2019::computer;
 
+// each amplifier runs with a different phase setting
+const AMPLIFIERS: usize = 5;
+
 pub fn answer1() {
     println!("{}", solve1());
 }
@@ -10,54 +15,23 @@ pub fn answer2() {
 
 fn solve1() -> i64 {
     let codes = computer::read_codes("data/2019/day07.txt");
-    permutations(0, 5)
-        .iter()
-        .map(|p| run_sequence(&codes, p))
-        .max()
+    let phases = combinatorics::k_permutations(&[0, 1, 2, 3, 4], AMPLIFIERS);
+    search::max_by_key(phases, |p| run_sequence(&codes, p))
         .unwrap()
+        .1
 }
 
 fn solve2() -> i64 {
     let codes = computer::read_codes("data/2019/day07.txt");
-    permutations(5, 10)
-        .iter()
-        .map(|p| run_sequence2(&codes, p))
-        .max()
+    let phases = combinatorics::k_permutations(&[5, 6, 7, 8, 9], AMPLIFIERS);
+    search::max_by_key(phases, |p| run_sequence2(&codes, p))
         .unwrap()
+        .1
 }
 
-fn permutations(start: i64, end: i64) -> Vec<Vec<i64>> {
-    // gruiiiiik
-    let mut res = Vec::new();
-    for a in start..end {
-        for b in start..end {
-            if b == a {
-                continue;
-            }
-            for c in start..end {
-                if c == b || c == a {
-                    continue;
-                }
-                for d in start..end {
-                    if d == c || d == b || d == a {
-                        continue;
-                    }
-                    for e in start..end {
-                        if e == d || e == c || e == b || e == a {
-                            continue;
-                        }
-                        res.push(vec![a, b, c, d, e]);
-                    }
-                }
-            }
-        }
-    }
-    res
-}
-
-fn run_sequence(codes: &Vec<i64>, sequence: &Vec<i64>) -> i64 {
+fn run_sequence(codes: &[i64], sequence: &[i64]) -> i64 {
     sequence.iter().fold(0, |input, phase_setting| {
-        let mut computer = computer::Computer::new(codes.clone());
+        let mut computer = computer::Computer::new(codes.to_vec());
         let res = computer
             .run_with_inputs(vec![*phase_setting, input])
             .unwrap();
@@ -69,11 +43,11 @@ fn run_sequence(codes: &Vec<i64>, sequence: &Vec<i64>) -> i64 {
     })
 }
 
-fn run_sequence2(codes: &Vec<i64>, sequence: &Vec<i64>) -> i64 {
+fn run_sequence2(codes: &[i64], sequence: &[i64]) -> i64 {
     let mut computers: Vec<Box<computer::Computer>> = sequence
         .iter()
         .map(|i| {
-            let mut c = computer::Computer::new(codes.clone());
+            let mut c = computer::Computer::new(codes.to_vec());
             c.with_input(vec![*i]);
             Box::new(c)
         })
@@ -103,6 +77,7 @@ fn run_sequence2(codes: &Vec<i64>, sequence: &Vec<i64>) -> i64 {
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod test {
     use super::*;
 
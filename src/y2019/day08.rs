@@ -1,114 +1,77 @@
+use grid::Grid;
+use ocr;
 use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use y2019::sif::{animation, export};
+use y2019::sif::{Color, Image};
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-enum Color {
-    Black,
-    White,
-    Transparent,
-}
-
-impl Color {
-    fn from_char(c: char) -> Option<Color> {
-        match c {
-            '0' => Some(Color::Black),
-            '1' => Some(Color::White),
-            '2' => Some(Color::Transparent),
-            _ => None,
-        }
-    }
+const FRAME_DELAY: Duration = Duration::from_millis(80);
 
-    fn to_pretty_char(&self) -> char {
-        match self {
-            Color::Black => ' ',
-            Color::White => '■',
-            Color::Transparent => '_',
-        }
-    }
-}
-
-pub fn answer1() {
-    let (width, height) = (25, 6);
-    let layer_len = width * height;
-    let pixels = read_data();
-    assert!(
-        pixels.len() % layer_len == 0,
-        format!(
-            "incomplete layer ({}, {}) with {} total pixels",
-            width,
-            height,
-            pixels.len()
-        )
-    );
-    let number_of_layers = pixels.len() / layer_len;
-    let l = (0..number_of_layers)
-        .map(|i| &pixels[i * layer_len..(i + 1) * layer_len])
-        .min_by(|x, y| number_of(Color::Black, x).cmp(&number_of(Color::Black, y)))
+pub fn answer1(width: usize, height: usize) {
+    let image = read_image(width, height);
+    let l = image
+        .layer_stats()
+        .into_iter()
+        .min_by_key(|l| l.count(Color::Black))
         .unwrap();
-    let result = number_of(Color::White, l) * number_of(Color::Transparent, l);
+    let result = l.count(Color::White) * l.count(Color::Transparent);
     println!("{}", result);
 }
 
-pub fn answer2() {
-    let (width, height) = (25, 6);
-    let pixels = read_data();
-    let final_image = merge_image(width, height, &pixels);
-
-    for y in 0..height {
-        for x in 0..width {
-            print!("{}", final_image[y * width + x].to_pretty_char());
+pub fn answer2(width: usize, height: usize) {
+    let image = read_image(width, height);
+    let final_image = image.merge_image();
+    match ocr::recognize(&final_image, |c| *c == Color::White) {
+        Ok(text) => println!("{}", text),
+        Err(err) => {
+            print!("{}", final_image.render(|c| c.to_pretty_char()));
+            println!("{}", err);
         }
-        print!("\n");
     }
-    // RCYKR
-}
-
-fn number_of<T: Eq>(target: T, xs: &[T]) -> usize {
-    xs.iter().filter(|x| **x == target).count()
 }
 
-fn merge_image<'a>(width: usize, height: usize, pixels: &'a Vec<Color>) -> Vec<Color> {
-    let layer_len = width * height;
-    assert!(
-        pixels.len() % layer_len == 0,
-        format!(
-            "incomplete layer ({}, {}) with {} total pixels",
-            width,
-            height,
-            pixels.len()
-        )
-    );
-    let number_of_layers = pixels.len() / layer_len;
-    let layers = (0..number_of_layers)
-        .map(|i| &pixels[i * layer_len..(i + 1) * layer_len])
-        .collect();
-    let mut final_image = Vec::with_capacity(layer_len);
-    for y in 0..height {
-        for x in 0..width {
-            let idx = y * width + x;
-            final_image.push(pixel_color(&layers, idx).clone());
-        }
+// save the decoded message, each pixel `scale` times larger. With `animate`,
+// the file is an animated png or gif showing the layers being stacked.
+pub fn write_image(path: &str, scale: usize, animate: bool, width: usize, height: usize) {
+    let image = read_image(width, height);
+    let result = if animate {
+        let frames: Vec<Grid<Color>> = image.compositions().collect();
+        export::write_animation(&frames, scale, FRAME_DELAY, Path::new(path))
+    } else {
+        export::write_image(&image.merge_image(), scale, Path::new(path))
+    };
+    if let Err(err) = result {
+        println!("cannot write {}: {}", path, err);
+        std::process::exit(1);
     }
+}
 
-    final_image
+// show in the terminal how the layers are stacked
+pub fn animate(width: usize, height: usize) {
+    animation::play(&read_image(width, height), FRAME_DELAY);
 }
 
-fn pixel_color<'a>(layers: &'a Vec<&[Color]>, i: usize) -> &'a Color {
-    layers.iter().fold(&Color::Transparent, |current, l| {
-        let c = &l[i];
-        match current {
-            Color::Transparent => c,
-            _ => current,
-        }
-    })
+// layer size given as "<width>x<height>"
+pub fn parse_size(s: &str) -> Option<(usize, usize)> {
+    let mut parts = s.split('x');
+    let width = parts.next()?.trim().parse().ok()?;
+    let height = parts.next()?.trim().parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((width, height))
 }
 
-fn read_data() -> Vec<Color> {
-    fs::read_to_string("data/2019/day08.txt")
-        .unwrap()
-        .trim()
-        .chars()
-        .flat_map(Color::from_char)
-        .collect()
+fn read_image(width: usize, height: usize) -> Image {
+    let data = fs::read_to_string("data/2019/day08.txt").unwrap();
+    match Image::parse(&data, width, height) {
+        Ok(image) => image,
+        Err(err) => {
+            println!("invalid image: {}", err);
+            std::process::exit(1);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -117,27 +80,34 @@ mod test {
 
     #[test]
     fn test_layer_color() {
-        let pixels = vec![
-            Color::Black,
-            Color::Transparent,
-            Color::Transparent,
-            Color::Transparent,
-            Color::White,
-            Color::White,
-            Color::Transparent,
-            Color::Transparent,
-            Color::Transparent,
-            Color::Transparent,
-            Color::White,
-            Color::Transparent,
-            Color::Black,
-            Color::Black,
-            Color::Black,
-            Color::Black,
-        ];
+        let image = Image::parse("0222112222120000", 2, 2).unwrap();
+        assert_eq!(
+            image.merge_image(),
+            Grid::from_vec(
+                2,
+                2,
+                vec![Color::Black, Color::White, Color::White, Color::Black]
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("25x6"), Some((25, 6)));
+        assert_eq!(parse_size("3x2"), Some((3, 2)));
+        assert_eq!(parse_size("25"), None);
+        assert_eq!(parse_size("25x6x1"), None);
+        assert_eq!(parse_size("x6"), None);
+        assert_eq!(parse_size("25x-6"), None);
+    }
+
+    #[test]
+    fn test_message() {
+        let message = read_image(25, 6).merge_image();
         assert_eq!(
-            merge_image(2, 2, &pixels),
-            [Color::Black, Color::White, Color::White, Color::Black]
+            ocr::recognize(&message, |c| *c == Color::White),
+            Ok("RCYKR".to_string())
         );
     }
 }
@@ -7,7 +7,7 @@ pub fn answer1() {
     let mut c = computer::Computer::new(codes);
     c.with_input(vec![1]);
     loop {
-        match c.run().unwrap() {
+        match c.run_predecoded().unwrap() {
             computer::RunResult::Done => break,
             computer::RunResult::AwaitInput => panic!("await input but shouldn't happen"),
             computer::RunResult::Output(_) => (),
@@ -17,7 +17,16 @@ pub fn answer1() {
 }
 
 pub fn answer2() {
-    println!("coucou");
+    println!("{:?}", boost(2));
+}
+
+// the BOOST program, in test mode with 1 or sensor boost mode with 2
+fn boost(mode: i64) -> Vec<i64> {
+    let codes = computer::read_codes("data/2019/day09.txt");
+    let mut c = computer::Computer::new(codes);
+    c.with_input(vec![mode]);
+    c.run_until_halt().unwrap();
+    c.outputs
 }
 
 #[cfg(test)]
@@ -31,4 +40,9 @@ mod test {
         c.run_until_halt().unwrap();
         assert_eq!(c.outputs, codes);
     }
+
+    #[test]
+    fn test_boost() {
+        assert_eq!(boost(2), vec![50158]);
+    }
 }
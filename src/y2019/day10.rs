@@ -0,0 +1,161 @@
+use grid::Point;
+use input;
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+
+pub fn answer1() {
+    let asteroids = read_asteroids();
+    println!("{}", best_station(&asteroids).1);
+}
+
+pub fn answer2() {
+    let asteroids = read_asteroids();
+    let (station, _) = best_station(&asteroids);
+    let p = vaporization_order(&asteroids, station)[199];
+    println!("{}", p.x * 100 + p.y);
+}
+
+fn read_asteroids() -> Vec<Point> {
+    parse_asteroids(&input::read_to_string("data/2019/day10.txt"))
+}
+
+fn parse_asteroids(text: &str) -> Vec<Point> {
+    text.lines()
+        .enumerate()
+        .flat_map(|(y, line)| {
+            line.trim()
+                .chars()
+                .enumerate()
+                .filter(|(_, c)| *c == '#')
+                .map(move |(x, _)| Point::new(x as i32, y as i32))
+        })
+        .collect()
+}
+
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// the direction from `from` to `to`, as the smallest step on the grid
+fn direction(from: Point, to: Point) -> Point {
+    let delta = to - from;
+    let d = gcd(delta.x, delta.y);
+    Point::new(delta.x / d, delta.y / d)
+}
+
+// the other asteroids, grouped by direction from `station`, the closest first
+fn lines_of_sight(asteroids: &[Point], station: Point) -> BTreeMap<Point, Vec<Point>> {
+    let mut lines: BTreeMap<Point, Vec<Point>> = BTreeMap::new();
+    for &a in asteroids.iter().filter(|&&a| a != station) {
+        lines.entry(direction(station, a)).or_default().push(a);
+    }
+    for line in lines.values_mut() {
+        line.sort_by_key(|a| a.manhattan_distance(station));
+    }
+    lines
+}
+
+// the asteroid which sees the most others, and how many
+fn best_station(asteroids: &[Point]) -> (Point, usize) {
+    asteroids
+        .iter()
+        .map(|&a| (a, lines_of_sight(asteroids, a).len()))
+        .max_by_key(|&(_, seen)| seen)
+        .unwrap()
+}
+
+// the laser starts pointing up and turns clockwise, destroying the first
+// asteroid in each direction at each turn
+fn vaporization_order(asteroids: &[Point], station: Point) -> Vec<Point> {
+    // the y axis points down, so this is clockwise from up
+    let angle = |d: &Point| {
+        let a = f64::from(d.x).atan2(-f64::from(d.y));
+        if a < 0.0 {
+            a + 2.0 * PI
+        } else {
+            a
+        }
+    };
+    let mut order: Vec<(usize, f64, Point)> = lines_of_sight(asteroids, station)
+        .into_iter()
+        .flat_map(|(d, line)| {
+            let angle = angle(&d);
+            line.into_iter()
+                .enumerate()
+                .map(move |(turn, a)| (turn, angle, a))
+        })
+        .collect();
+    order.sort_by(|(t1, a1, _), (t2, a2, _)| t1.cmp(t2).then(a1.partial_cmp(a2).unwrap()));
+    order.into_iter().map(|(_, _, a)| a).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LARGE_EXAMPLE: &str = ".#..##.###...#######
+##.############..##.
+.#.######.########.#
+.###.#######.####.#.
+#####.##.#.##.###.##
+..#####..#.#########
+####################
+#.####....###.#.#.##
+##.#################
+#####.##.###..####..
+..######..##.#######
+####.##.####...##..#
+.#####..#.######.###
+##...#.##########...
+#.##########.#######
+.####.#.###.###.#.##
+....##.##.###..#####
+.#.#.###########.###
+#.#.#.#####.####.###
+###.##.####.##.#..##
+";
+
+    #[test]
+    fn test_best_station() {
+        let asteroids = parse_asteroids(".#..#\n.....\n#####\n....#\n...##\n");
+        assert_eq!(best_station(&asteroids), (Point::new(3, 4), 8));
+        let asteroids = parse_asteroids(
+            "......#.#.
+#..#.#....
+..#######.
+.#.#.###..
+.#..#.....
+..#....#.#
+#..#....#.
+.##.#..###
+##...#..#.
+.#....####
+",
+        );
+        assert_eq!(best_station(&asteroids), (Point::new(5, 8), 33));
+        let asteroids = parse_asteroids(LARGE_EXAMPLE);
+        assert_eq!(best_station(&asteroids), (Point::new(11, 13), 210));
+    }
+
+    #[test]
+    fn test_vaporization_order() {
+        let asteroids = parse_asteroids(LARGE_EXAMPLE);
+        let order = vaporization_order(&asteroids, Point::new(11, 13));
+        assert_eq!(order[0], Point::new(11, 12));
+        assert_eq!(order[1], Point::new(12, 1));
+        assert_eq!(order[2], Point::new(12, 2));
+        assert_eq!(order[9], Point::new(12, 8));
+        assert_eq!(order[19], Point::new(16, 0));
+        assert_eq!(order[49], Point::new(16, 9));
+        assert_eq!(order[99], Point::new(10, 16));
+        assert_eq!(order[198], Point::new(9, 6));
+        assert_eq!(order[199], Point::new(8, 2));
+        assert_eq!(order[200], Point::new(10, 9));
+        assert_eq!(order[298], Point::new(11, 1));
+        assert_eq!(order.len(), 299);
+    }
+}
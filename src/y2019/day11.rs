@@ -0,0 +1,117 @@
+use grid::{Direction, Grid, Point};
+use ocr;
+use std::collections::HashSet;
+use y2019::computer;
+use y2019::computer::{Computer, RunResult};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Robot {
+    position: Point,
+    // one step forward
+    heading: Point,
+    white: HashSet<Point>,
+    painted: HashSet<Point>,
+}
+
+impl Robot {
+    fn new(start_on_white: bool) -> Robot {
+        let mut white = HashSet::new();
+        if start_on_white {
+            white.insert(Point::ORIGIN);
+        }
+        Robot {
+            position: Point::ORIGIN,
+            heading: Direction::Up.delta(),
+            white,
+            painted: HashSet::new(),
+        }
+    }
+
+    // what the camera sees: 0 for black, 1 for white
+    fn camera(&self) -> i64 {
+        self.white.contains(&self.position) as i64
+    }
+
+    // paint the panel (0 for black, 1 for white), turn (0 for left, 1 for
+    // right) and move forward
+    fn act(&mut self, color: i64, turn: i64) {
+        if color == 1 {
+            self.white.insert(self.position);
+        } else {
+            self.white.remove(&self.position);
+        }
+        self.painted.insert(self.position);
+        self.heading = if turn == 0 {
+            self.heading.rotate_left()
+        } else {
+            self.heading.rotate_right()
+        };
+        self.position += self.heading;
+    }
+
+    // the white panels, cropped
+    fn hull(&self) -> Grid<bool> {
+        let min_x = self.white.iter().map(|p| p.x).min().unwrap_or(0);
+        let max_x = self.white.iter().map(|p| p.x).max().unwrap_or(0);
+        let min_y = self.white.iter().map(|p| p.y).min().unwrap_or(0);
+        let max_y = self.white.iter().map(|p| p.y).max().unwrap_or(0);
+        let offset = Point::new(min_x, min_y);
+        Grid::from_fn(
+            (max_x - min_x + 1) as usize,
+            (max_y - min_y + 1) as usize,
+            |p| self.white.contains(&(p + offset)),
+        )
+    }
+}
+
+pub fn answer1() {
+    println!("{}", paint(false).painted.len());
+}
+
+pub fn answer2() {
+    let hull = paint(true).hull();
+    match ocr::recognize(&hull, |white| *white) {
+        Ok(text) => println!("{}", text),
+        Err(err) => {
+            print!("{}", hull.render(|&white| if white { '#' } else { '.' }));
+            println!("{}", err);
+        }
+    }
+}
+
+fn paint(start_on_white: bool) -> Robot {
+    let codes = computer::read_codes("data/2019/day11.txt");
+    let mut brain = Computer::new(codes);
+    let mut robot = Robot::new(start_on_white);
+    loop {
+        brain.push_inputs(&[robot.camera()]);
+        let (outputs, result) = brain.run_until_blocked().unwrap();
+        for instruction in outputs.chunks(2) {
+            robot.act(instruction[0], instruction[1]);
+        }
+        if result == RunResult::Done {
+            return robot;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_robot() {
+        // the example from the puzzle
+        let mut robot = Robot::new(false);
+        for &(color, turn) in [(1, 0), (0, 0), (1, 0), (1, 0), (0, 1), (1, 0), (1, 0)].iter() {
+            robot.act(color, turn);
+        }
+        assert_eq!(robot.painted.len(), 6);
+        assert_eq!(robot.position, Point::new(0, -1));
+        assert_eq!(robot.heading, Direction::Left.delta());
+        assert_eq!(
+            robot.hull().render(|&w| if w { '#' } else { '.' }),
+            "..#\n..#\n##.\n"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use cycle;
+use input;
+use regex::Regex;
+
+// positions and velocities along one axis, the axes being independent
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Axis {
+    positions: Vec<i64>,
+    velocities: Vec<i64>,
+}
+
+impl Axis {
+    fn step(&mut self) {
+        for i in 0..self.positions.len() {
+            let p = self.positions[i];
+            let pull: i64 = self.positions.iter().map(|q| (q - p).signum()).sum();
+            self.velocities[i] += pull;
+        }
+        for (p, v) in self.positions.iter_mut().zip(self.velocities.iter()) {
+            *p += v;
+        }
+    }
+
+    // Each state has a single previous state, so the first one to come back
+    // is the initial one: the cycle starts right away.
+    fn period(&self) -> u64 {
+        let next = |axis: &Axis| {
+            let mut next = axis.clone();
+            next.step();
+            next
+        };
+        cycle::floyd(self, next).length as u64
+    }
+}
+
+pub fn answer1() {
+    let mut axes = read_moons();
+    for _ in 0..1000 {
+        axes.iter_mut().for_each(Axis::step);
+    }
+    println!("{}", energy(&axes));
+}
+
+pub fn answer2() {
+    println!("{}", period(&read_moons()));
+}
+
+fn read_moons() -> Vec<Axis> {
+    parse_moons(&input::read_to_string("data/2019/day12.txt"))
+}
+
+// "<x=-1, y=0, z=2>" for each moon, split by axis
+fn parse_moons(text: &str) -> Vec<Axis> {
+    lazy_static! {
+        static ref MOON: Regex = Regex::new(r"^<x=(-?\d+), y=(-?\d+), z=(-?\d+)>$").unwrap();
+    }
+    let moons: Vec<[i64; 3]> = text
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .map(|line| {
+            let caps = MOON
+                .captures(line)
+                .unwrap_or_else(|| panic!("invalid moon {:?}", line));
+            let n = |i: usize| caps[i].parse::<i64>().unwrap();
+            [n(1), n(2), n(3)]
+        })
+        .collect();
+    (0..3)
+        .map(|axis| Axis {
+            positions: moons.iter().map(|m| m[axis]).collect(),
+            velocities: vec![0; moons.len()],
+        })
+        .collect()
+}
+
+// the sum over the moons of potential times kinetic energy
+fn energy(axes: &[Axis]) -> i64 {
+    (0..axes[0].positions.len())
+        .map(|i| {
+            let potential: i64 = axes.iter().map(|a| a.positions[i].abs()).sum();
+            let kinetic: i64 = axes.iter().map(|a| a.velocities[i].abs()).sum();
+            potential * kinetic
+        })
+        .sum()
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// the whole system repeats when all the axes do at the same time
+fn period(axes: &[Axis]) -> u64 {
+    axes.iter()
+        .map(Axis::period)
+        .fold(1, |lcm, p| lcm / gcd(lcm, p) * p)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE1: &str =
+        "<x=-1, y=0, z=2>\n<x=2, y=-10, z=-7>\n<x=4, y=-8, z=8>\n<x=3, y=5, z=-1>\n";
+    const EXAMPLE2: &str =
+        "<x=-8, y=-10, z=0>\n<x=5, y=5, z=10>\n<x=2, y=-7, z=3>\n<x=9, y=-8, z=-3>\n";
+
+    fn energy_after(text: &str, steps: usize) -> i64 {
+        let mut axes = parse_moons(text);
+        for _ in 0..steps {
+            axes.iter_mut().for_each(Axis::step);
+        }
+        energy(&axes)
+    }
+
+    #[test]
+    fn test_energy() {
+        assert_eq!(energy_after(EXAMPLE1, 10), 179);
+        assert_eq!(energy_after(EXAMPLE2, 100), 1940);
+    }
+
+    #[test]
+    fn test_period() {
+        assert_eq!(period(&parse_moons(EXAMPLE1)), 2772);
+        assert_eq!(period(&parse_moons(EXAMPLE2)), 4686774924);
+    }
+}
@@ -0,0 +1,104 @@
+use grid::Point;
+use std::collections::HashMap;
+use y2019::computer;
+use y2019::computer::{Computer, RunResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Empty,
+    Wall,
+    Block,
+    Paddle,
+    Ball,
+}
+
+// what the game has drawn so far
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Screen {
+    tiles: HashMap<Point, Tile>,
+    score: i64,
+}
+
+impl Screen {
+    // outputs go by three: x, y and the tile, or -1, 0 and the score
+    fn draw(&mut self, outputs: &[i64]) {
+        for triple in outputs.chunks(3) {
+            match *triple {
+                [-1, 0, score] => self.score = score,
+                [x, y, tile] => {
+                    let tile = match tile {
+                        0 => Tile::Empty,
+                        1 => Tile::Wall,
+                        2 => Tile::Block,
+                        3 => Tile::Paddle,
+                        4 => Tile::Ball,
+                        _ => panic!("unknown tile {}", tile),
+                    };
+                    self.tiles.insert(Point::new(x as i32, y as i32), tile);
+                }
+                _ => panic!("incomplete output {:?}", triple),
+            }
+        }
+    }
+
+    fn find(&self, tile: Tile) -> Option<Point> {
+        self.tiles.iter().find(|(_, &t)| t == tile).map(|(&p, _)| p)
+    }
+
+    fn count(&self, tile: Tile) -> usize {
+        self.tiles.values().filter(|&&t| t == tile).count()
+    }
+
+    // the paddle follows the ball
+    fn joystick(&self) -> i64 {
+        match (self.find(Tile::Ball), self.find(Tile::Paddle)) {
+            (Some(ball), Some(paddle)) => i64::from((ball.x - paddle.x).signum()),
+            _ => 0,
+        }
+    }
+}
+
+pub fn answer1() {
+    let mut game = Computer::new(computer::read_codes("data/2019/day13.txt"));
+    let mut screen = Screen::default();
+    let (outputs, _) = game.run_until_blocked().unwrap();
+    screen.draw(&outputs);
+    println!("{}", screen.count(Tile::Block));
+}
+
+pub fn answer2() {
+    let mut codes = computer::read_codes("data/2019/day13.txt");
+    // free play
+    codes[0] = 2;
+    let mut game = Computer::new(codes);
+    let mut screen = Screen::default();
+    loop {
+        let (outputs, result) = game.run_until_blocked().unwrap();
+        screen.draw(&outputs);
+        if result == RunResult::Done {
+            break;
+        }
+        game.push_inputs(&[screen.joystick()]);
+    }
+    println!("{}", screen.score);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_screen() {
+        let mut screen = Screen::default();
+        screen.draw(&[1, 2, 3, 6, 5, 4]);
+        assert_eq!(screen.find(Tile::Paddle), Some(Point::new(1, 2)));
+        assert_eq!(screen.find(Tile::Ball), Some(Point::new(6, 5)));
+        assert_eq!(screen.joystick(), 1);
+
+        screen.draw(&[6, 5, 0, 0, 4, 4, 2, 2, 2, -1, 0, 12345]);
+        assert_eq!(screen.joystick(), -1);
+        assert_eq!(screen.count(Tile::Block), 1);
+        assert_eq!(screen.count(Tile::Empty), 1);
+        assert_eq!(screen.score, 12345);
+    }
+}
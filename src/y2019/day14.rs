@@ -0,0 +1,157 @@
+use graph::Graph;
+use input;
+use std::collections::HashMap;
+
+const ORE: &str = "ORE";
+const FUEL: &str = "FUEL";
+const CARGO: u64 = 1_000_000_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Reaction {
+    quantity: u64,
+    inputs: Vec<(u64, String)>,
+}
+
+struct Nanofactory {
+    // by output chemical
+    reactions: HashMap<String, Reaction>,
+    // each chemical before the ones it's made of, so from FUEL to ORE
+    order: Vec<String>,
+}
+
+pub fn answer1() {
+    println!("{}", read_factory().ore_for(1));
+}
+
+pub fn answer2() {
+    println!("{}", read_factory().max_fuel(CARGO));
+}
+
+fn read_factory() -> Nanofactory {
+    Nanofactory::parse(&input::read_to_string("data/2019/day14.txt"))
+}
+
+// "7 A"
+fn parse_quantity(text: &str) -> (u64, String) {
+    let mut parts = text.split_whitespace();
+    let quantity = parts.next().and_then(|q| q.parse().ok());
+    match (quantity, parts.next()) {
+        (Some(q), Some(chemical)) => (q, chemical.to_string()),
+        _ => panic!("invalid quantity {:?}", text),
+    }
+}
+
+impl Nanofactory {
+    // "7 A, 1 B => 1 C" on each line
+    fn parse(text: &str) -> Nanofactory {
+        let mut reactions = HashMap::new();
+        let mut graph = Graph::directed();
+        for line in text.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+            let mut sides = line.split(" => ");
+            let (inputs, output) = match (sides.next(), sides.next()) {
+                (Some(i), Some(o)) => (i, o),
+                _ => panic!("invalid reaction {:?}", line),
+            };
+            let (quantity, output) = parse_quantity(output);
+            let inputs: Vec<(u64, String)> = inputs.split(", ").map(parse_quantity).collect();
+            for (_, input) in &inputs {
+                graph.add_edge(output.clone(), input.clone());
+            }
+            reactions.insert(output, Reaction { quantity, inputs });
+        }
+        let order = graph
+            .topological_sort()
+            .expect("chemicals made of themselves")
+            .into_iter()
+            .map(|id| graph.name(id).clone())
+            .collect();
+        Nanofactory { reactions, order }
+    }
+
+    // Everything needed of a chemical is known once all the chemicals using
+    // it have been made, which is the case in topological order. So each
+    // reaction only runs once, as many times as needed.
+    fn ore_for(&self, fuel: u64) -> u64 {
+        let mut needed: HashMap<&str, u64> = HashMap::new();
+        needed.insert(FUEL, fuel);
+        for chemical in &self.order {
+            let quantity = needed.get(chemical.as_str()).cloned().unwrap_or(0);
+            let reaction = match self.reactions.get(chemical) {
+                Some(r) => r,
+                None => continue,
+            };
+            let times = quantity.div_ceil(reaction.quantity);
+            for (q, input) in &reaction.inputs {
+                *needed.entry(input).or_insert(0) += times * q;
+            }
+        }
+        needed.get(ORE).cloned().unwrap_or(0)
+    }
+
+    // the most fuel which can be made from that much ore
+    fn max_fuel(&self, ore: u64) -> u64 {
+        // the answer is in [low, high)
+        let mut low = 0;
+        let mut high = 1;
+        while self.ore_for(high) <= ore {
+            low = high;
+            high *= 2;
+        }
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+            if self.ore_for(middle) <= ore {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        low
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE3: &str = "157 ORE => 5 NZVS
+165 ORE => 6 DCFZ
+44 XJWVT, 5 KHKGT, 1 QDVJ, 29 NZVS, 9 GPVTF, 48 HKGWZ => 1 FUEL
+12 HKGWZ, 1 GPVTF, 8 PSHF => 9 QDVJ
+179 ORE => 7 PSHF
+177 ORE => 5 HKGWZ
+7 DCFZ, 7 PSHF => 2 XJWVT
+165 ORE => 2 GPVTF
+3 DCFZ, 7 NZVS, 5 HKGWZ, 10 PSHF => 8 KHKGT
+";
+
+    #[test]
+    fn test_ore_for() {
+        let factory = Nanofactory::parse(
+            "10 ORE => 10 A
+1 ORE => 1 B
+7 A, 1 B => 1 C
+7 A, 1 C => 1 D
+7 A, 1 D => 1 E
+7 A, 1 E => 1 FUEL
+",
+        );
+        assert_eq!(factory.ore_for(1), 31);
+        let factory = Nanofactory::parse(
+            "9 ORE => 2 A
+8 ORE => 3 B
+7 ORE => 5 C
+3 A, 4 B => 1 AB
+5 B, 7 C => 1 BC
+4 C, 1 A => 1 CA
+2 AB, 3 BC, 4 CA => 1 FUEL
+",
+        );
+        assert_eq!(factory.ore_for(1), 165);
+        assert_eq!(Nanofactory::parse(EXAMPLE3).ore_for(1), 13312);
+    }
+
+    #[test]
+    fn test_max_fuel() {
+        assert_eq!(Nanofactory::parse(EXAMPLE3).max_fuel(CARGO), 82892753);
+    }
+}
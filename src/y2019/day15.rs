@@ -0,0 +1,196 @@
+use graph::Graph;
+use grid::{Direction, Point};
+use std::collections::HashSet;
+use y2019::computer;
+use y2019::computer::Computer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Wall,
+    Moved,
+    FoundOxygen,
+}
+
+// something which moves around the area, and reports what it bumps into
+trait Droid {
+    fn try_move(&mut self, direction: Direction) -> Status;
+}
+
+struct RepairDroid {
+    computer: Computer,
+}
+
+impl Droid for RepairDroid {
+    fn try_move(&mut self, direction: Direction) -> Status {
+        let command = match direction {
+            Direction::Up => 1,
+            Direction::Down => 2,
+            Direction::Left => 3,
+            Direction::Right => 4,
+        };
+        self.computer.push_inputs(&[command]);
+        let (outputs, _) = self.computer.run_until_blocked().unwrap();
+        match outputs[..] {
+            [0] => Status::Wall,
+            [1] => Status::Moved,
+            [2] => Status::FoundOxygen,
+            _ => panic!("unexpected status {:?}", outputs),
+        }
+    }
+}
+
+// the open positions and how they connect, from the droid's start
+struct Area {
+    map: Graph<Point>,
+    oxygen: Option<Point>,
+}
+
+impl Area {
+    // the fewest moves from `from` to each open position
+    fn distances(&self, from: Point) -> Vec<usize> {
+        self.map
+            .dijkstra(self.map.id(&from).unwrap())
+            .into_iter()
+            .map(|d| d.unwrap() as usize)
+            .collect()
+    }
+
+    // heading towards the oxygen: no path is shorter than the manhattan
+    // distance
+    fn distance_to_oxygen(&self) -> usize {
+        let oxygen = self.oxygen.unwrap();
+        let start = self.map.id(&Point::ORIGIN).unwrap();
+        let goal = self.map.id(&oxygen).unwrap();
+        let heuristic = |n| self.map.name(n).manhattan_distance(oxygen) as u64;
+        self.map.astar(start, goal, heuristic).unwrap().0 as usize
+    }
+
+    // oxygen spreads to the neighbouring positions every minute
+    fn minutes_to_fill(&self) -> usize {
+        self.distances(self.oxygen.unwrap())
+            .into_iter()
+            .max()
+            .unwrap()
+    }
+}
+
+pub fn answer1() {
+    println!("{}", read_area().distance_to_oxygen());
+}
+
+pub fn answer2() {
+    println!("{}", read_area().minutes_to_fill());
+}
+
+fn read_area() -> Area {
+    let computer = Computer::new(computer::read_codes("data/2019/day15.txt"));
+    explore(&mut RepairDroid { computer })
+}
+
+// Depth first: the droid tries every direction it doesn't know yet, and
+// backs up once there's none left.
+fn explore<D: Droid>(droid: &mut D) -> Area {
+    let mut map = Graph::undirected();
+    map.intern(Point::ORIGIN);
+    let mut oxygen = None;
+    let mut known = HashSet::new();
+    known.insert(Point::ORIGIN);
+    let mut position = Point::ORIGIN;
+    let mut path: Vec<Direction> = Vec::new();
+    loop {
+        let unknown = Direction::ALL
+            .iter()
+            .cloned()
+            .find(|d| !known.contains(&(position + d.delta())));
+        match unknown {
+            Some(direction) => {
+                let next = position + direction.delta();
+                known.insert(next);
+                let status = droid.try_move(direction);
+                if status == Status::Wall {
+                    continue;
+                }
+                if status == Status::FoundOxygen {
+                    oxygen = Some(next);
+                }
+                map.add_edge(position, next);
+                position = next;
+                path.push(direction);
+            }
+            None => match path.pop() {
+                Some(direction) => {
+                    droid.try_move(direction.opposite());
+                    position = position - direction.delta();
+                }
+                None => return Area { map, oxygen },
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // a droid on a known map, where anything but '.' and 'O' is a wall
+    struct MapDroid {
+        open: HashSet<Point>,
+        oxygen: Point,
+        position: Point,
+    }
+
+    impl MapDroid {
+        fn new(map: &str, start: Point) -> MapDroid {
+            let mut open = HashSet::new();
+            let mut oxygen = None;
+            for (y, line) in map.lines().enumerate() {
+                for (x, c) in line.chars().enumerate() {
+                    let p = Point::new(x as i32, y as i32) - start;
+                    match c {
+                        '.' => {
+                            open.insert(p);
+                        }
+                        'O' => {
+                            open.insert(p);
+                            oxygen = Some(p);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            MapDroid {
+                open,
+                oxygen: oxygen.unwrap(),
+                position: Point::ORIGIN,
+            }
+        }
+    }
+
+    impl Droid for MapDroid {
+        fn try_move(&mut self, direction: Direction) -> Status {
+            let next = self.position + direction.delta();
+            if !self.open.contains(&next) {
+                return Status::Wall;
+            }
+            self.position = next;
+            if next == self.oxygen {
+                Status::FoundOxygen
+            } else {
+                Status::Moved
+            }
+        }
+    }
+
+    #[test]
+    fn test_explore() {
+        // the example from part 2, starting on the top left
+        let map = " ##   \n#..## \n#.#..#\n#.O.# \n ###  \n";
+        let mut droid = MapDroid::new(map, Point::new(1, 1));
+        let area = explore(&mut droid);
+        assert_eq!(droid.position, Point::ORIGIN);
+        assert_eq!(area.map.len(), 8);
+        assert_eq!(area.oxygen, Some(Point::new(1, 2)));
+        assert_eq!(area.distance_to_oxygen(), 3);
+        assert_eq!(area.minutes_to_fill(), 4);
+    }
+}
@@ -0,0 +1,110 @@
+use input;
+
+const PHASES: usize = 100;
+const REPEATS: usize = 10_000;
+
+pub fn answer1() {
+    let signal = read_signal();
+    println!("{}", first_digits(&fft(signal, PHASES), 0));
+}
+
+pub fn answer2() {
+    println!("{}", real_message(&read_signal()));
+}
+
+fn read_signal() -> Vec<i32> {
+    parse_signal(&input::read_to_string("data/2019/day16.txt"))
+}
+
+fn parse_signal(text: &str) -> Vec<i32> {
+    text.trim()
+        .chars()
+        .map(|c| c.to_digit(10).expect("not a digit") as i32)
+        .collect()
+}
+
+// the 8 digits from `offset`, as a number
+fn first_digits(signal: &[i32], offset: usize) -> String {
+    signal[offset..offset + 8]
+        .iter()
+        .map(|d| d.to_string())
+        .collect()
+}
+
+// The pattern for the `i`th digit is `0, 1, 0, -1`, each repeated `i + 1`
+// times and shifted by one: the digit is a difference of sums over ranges
+// of the signal, which the prefix sums give directly. There are `n / (i + 1)`
+// ranges for each digit, so a phase takes `n log n`.
+fn phase(signal: &[i32]) -> Vec<i32> {
+    let n = signal.len();
+    let mut prefix = vec![0; n + 1];
+    for (i, d) in signal.iter().enumerate() {
+        prefix[i + 1] = prefix[i] + d;
+    }
+    let range = |start: usize, end: usize| prefix[end.min(n)] - prefix[start.min(n)];
+    (0..n)
+        .map(|i| {
+            let width = i + 1;
+            let mut total = 0;
+            let mut start = i;
+            while start < n {
+                total += range(start, start + width);
+                total -= range(start + 2 * width, start + 3 * width);
+                start += 4 * width;
+            }
+            (total % 10).abs()
+        })
+        .collect()
+}
+
+fn fft(signal: Vec<i32>, phases: usize) -> Vec<i32> {
+    (0..phases).fold(signal, |signal, _| phase(&signal))
+}
+
+// The message is in the second half of the repeated signal, where the
+// pattern is all zeros before the digit and all ones after: each digit is
+// the sum of the ones after it.
+fn real_message(signal: &[i32]) -> String {
+    let offset: usize = first_digits(signal, 0)[..7].parse().unwrap();
+    let length = signal.len() * REPEATS;
+    assert!(offset >= length / 2, "the message isn't in the second half");
+    let mut tail: Vec<i32> = (offset..length).map(|i| signal[i % signal.len()]).collect();
+    for _ in 0..PHASES {
+        let mut sum = 0;
+        for d in tail.iter_mut().rev() {
+            sum = (sum + *d) % 10;
+            *d = sum;
+        }
+    }
+    first_digits(&tail, 0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_phase() {
+        let mut signal = parse_signal("12345678");
+        for &expected in ["48226158", "34040438", "03415518", "01029498"].iter() {
+            signal = phase(&signal);
+            assert_eq!(first_digits(&signal, 0), expected);
+        }
+    }
+
+    #[test]
+    fn test_fft() {
+        let digits = |text| first_digits(&fft(parse_signal(text), PHASES), 0);
+        assert_eq!(digits("80871224585914546619083218645595"), "24176176");
+        assert_eq!(digits("19617804207202209144916044189917"), "73745418");
+        assert_eq!(digits("69317163492948606335995924319873"), "52432133");
+    }
+
+    #[test]
+    fn test_real_message() {
+        let message = |text| real_message(&parse_signal(text));
+        assert_eq!(message("03036732577212944063491565474664"), "84462026");
+        assert_eq!(message("02935109699940807407585447034323"), "78725270");
+        assert_eq!(message("03081770884921959731165446850517"), "53553731");
+    }
+}
@@ -0,0 +1,244 @@
+use grid::{Direction, Grid, Point};
+use std::fmt;
+use y2019::computer;
+use y2019::computer::Computer;
+
+// the longest a movement function or the main routine can be, without the
+// final newline
+const MAX_LENGTH: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Move {
+    turn: char,
+    steps: usize,
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.turn, self.steps)
+    }
+}
+
+fn join<T: fmt::Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(|i| i.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// the main routine, calling the functions by index, and the functions
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Program {
+    main: Vec<usize>,
+    functions: Vec<Vec<Move>>,
+}
+
+impl Program {
+    // the lines to type, the last one saying no to the video feed
+    fn ascii(&self) -> Vec<i64> {
+        let main: Vec<char> = self
+            .main
+            .iter()
+            .map(|&i| (b'A' + i as u8) as char)
+            .collect();
+        let mut lines = vec![join(&main)];
+        for f in &self.functions {
+            lines.push(join(f));
+        }
+        lines.push("n".to_string());
+        lines
+            .iter()
+            .flat_map(|l| l.bytes().chain(Some(b'\n')))
+            .map(i64::from)
+            .collect()
+    }
+}
+
+pub fn answer1() {
+    println!("{}", alignment(&camera()));
+}
+
+pub fn answer2() {
+    let moves = path(&camera());
+    let program = compress(&moves, 3).expect("the path can't be compressed");
+    let mut codes = computer::read_codes("data/2019/day17.txt");
+    // wake up the robot
+    codes[0] = 2;
+    let mut robot = Computer::new(codes);
+    robot.push_inputs(&program.ascii());
+    let (outputs, _) = robot.run_until_blocked().unwrap();
+    println!("{}", outputs.last().unwrap());
+}
+
+fn camera() -> Grid<char> {
+    let mut robot = Computer::new(computer::read_codes("data/2019/day17.txt"));
+    let (outputs, _) = robot.run_until_blocked().unwrap();
+    let text: String = outputs.iter().map(|&c| c as u8 as char).collect();
+    parse_view(&text)
+}
+
+fn parse_view(text: &str) -> Grid<char> {
+    Grid::parse(text.trim(), |c| match c {
+        '.' | '#' | '^' | 'v' | '<' | '>' | 'X' => Some(c),
+        _ => None,
+    })
+    .unwrap()
+}
+
+fn is_scaffold(view: &Grid<char>, p: Point) -> bool {
+    view.get(p).is_some_and(|&c| c != '.' && c != 'X')
+}
+
+// the sum of x * y over the intersections
+fn alignment(view: &Grid<char>) -> i32 {
+    view.points()
+        .filter(|&p| is_scaffold(view, p) && view.neighbours4(p).all(|n| is_scaffold(view, n)))
+        .map(|p| p.x * p.y)
+        .sum()
+}
+
+// Turn wherever the scaffold goes, then go as far as possible: that goes
+// straight through the intersections and visits the whole scaffold.
+fn path(view: &Grid<char>) -> Vec<Move> {
+    let (mut position, mut direction) = view
+        .iter()
+        .find_map(|(p, &c)| Direction::from_char(c).map(|d| (p, d)))
+        .expect("no robot in view");
+    let mut moves = Vec::new();
+    loop {
+        let (turn, next) = if is_scaffold(view, position + direction.turn_left().delta()) {
+            ('L', direction.turn_left())
+        } else if is_scaffold(view, position + direction.turn_right().delta()) {
+            ('R', direction.turn_right())
+        } else {
+            return moves;
+        };
+        direction = next;
+        let mut steps = 0;
+        while is_scaffold(view, position + direction.delta()) {
+            position += direction.delta();
+            steps += 1;
+        }
+        moves.push(Move { turn, steps });
+    }
+}
+
+// The moves as a main routine calling at most `count` functions. The first
+// moves not covered by the functions so far must start a new function: every
+// length for it is tried, depth first.
+fn compress(moves: &[Move], count: usize) -> Option<Program> {
+    fn search<'a>(
+        rest: &'a [Move],
+        count: usize,
+        functions: &mut Vec<&'a [Move]>,
+        main: &mut Vec<usize>,
+    ) -> bool {
+        if rest.is_empty() {
+            return true;
+        }
+        // each call takes two characters, with the comma
+        if 2 * main.len() + 1 > MAX_LENGTH {
+            return false;
+        }
+        for i in 0..functions.len() {
+            if rest.starts_with(functions[i]) {
+                main.push(i);
+                if search(&rest[functions[i].len()..], count, functions, main) {
+                    return true;
+                }
+                main.pop();
+            }
+        }
+        if functions.len() < count {
+            for length in 1..=rest.len() {
+                if join(&rest[..length]).len() > MAX_LENGTH {
+                    break;
+                }
+                main.push(functions.len());
+                functions.push(&rest[..length]);
+                if search(&rest[length..], count, functions, main) {
+                    return true;
+                }
+                functions.pop();
+                main.pop();
+            }
+        }
+        false
+    }
+
+    let mut functions = Vec::new();
+    let mut main = Vec::new();
+    if search(moves, count, &mut functions, &mut main) {
+        Some(Program {
+            main,
+            functions: functions.into_iter().map(|f| f.to_vec()).collect(),
+        })
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_alignment() {
+        let view = parse_view(
+            "..#..........
+..#..........
+#######...###
+#.#...#...#.#
+#############
+..#...#...#..
+..#####...^..
+",
+        );
+        assert_eq!(alignment(&view), 76);
+    }
+
+    #[test]
+    fn test_path() {
+        let view = parse_view(
+            "#######...#####
+#.....#...#...#
+#.....#...#...#
+......#...#...#
+......#...###.#
+......#.....#.#
+^########...#.#
+......#.#...#.#
+......#########
+........#...#..
+....#########..
+....#...#......
+....#...#......
+....#...#......
+....#####......
+",
+        );
+        let moves = path(&view);
+        assert_eq!(
+            join(&moves),
+            "R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2"
+        );
+
+        let program = compress(&moves, 3).unwrap();
+        let expanded: Vec<Move> = program
+            .main
+            .iter()
+            .flat_map(|&i| program.functions[i].clone())
+            .collect();
+        assert_eq!(expanded, moves);
+        assert!(program
+            .functions
+            .iter()
+            .all(|f| join(f).len() <= MAX_LENGTH));
+        assert_eq!(compress(&moves, 1), None);
+
+        let ascii: String = program.ascii().iter().map(|&c| c as u8 as char).collect();
+        assert!(ascii.starts_with("A,"));
+        assert!(ascii.ends_with("\nn\n"));
+    }
+}
@@ -0,0 +1,285 @@
+use grid::{Grid, Point};
+use input;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+// the shortest way from somewhere to a key: the doors on the way, and the
+// other keys picked up on the way, one bit per letter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Route {
+    key: usize,
+    distance: usize,
+    doors: u32,
+    keys: u32,
+}
+
+// The vault reduced to the routes between the robots and the keys: the
+// robots are numbered first, then the keys in the order of the letters.
+struct Vault {
+    robots: usize,
+    routes: Vec<Vec<Route>>,
+    all_keys: u32,
+}
+
+pub fn answer1() {
+    let vault = Vault::new(&read_map());
+    println!("{}", vault.shortest_collection());
+}
+
+pub fn answer2() {
+    let mut map = read_map();
+    split_entrance(&mut map);
+    println!("{}", Vault::new(&map).shortest_collection());
+}
+
+fn read_map() -> Grid<char> {
+    parse_map(&input::read_to_string("data/2019/day18.txt"))
+}
+
+fn parse_map(text: &str) -> Grid<char> {
+    Grid::parse(text.trim(), |c| match c {
+        '#' | '.' | '@' | 'a'..='z' | 'A'..='Z' => Some(c),
+        _ => None,
+    })
+    .unwrap()
+}
+
+// the four robots version, if the map only has one entrance
+fn split_entrance(map: &mut Grid<char>) {
+    let entrances: Vec<Point> = map
+        .iter()
+        .filter(|(_, &c)| c == '@')
+        .map(|(p, _)| p)
+        .collect();
+    if let [center] = entrances[..] {
+        for (dy, row) in ["@#@", "###", "@#@"].iter().enumerate() {
+            for (dx, c) in row.chars().enumerate() {
+                map[center + Point::new(dx as i32 - 1, dy as i32 - 1)] = c;
+            }
+        }
+    }
+}
+
+fn bit(c: char) -> u32 {
+    1 << (c.to_ascii_lowercase() as u32 - 'a' as u32)
+}
+
+impl Vault {
+    fn new(map: &Grid<char>) -> Vault {
+        let mut places: Vec<(Point, char)> = map
+            .iter()
+            .filter(|(_, &c)| c == '@' || c.is_ascii_lowercase())
+            .map(|(p, &c)| (p, c))
+            .collect();
+        places.sort_by_key(|&(_, c)| c);
+        let robots = places.iter().filter(|&&(_, c)| c == '@').count();
+        let routes = places
+            .iter()
+            .map(|&(start, _)| routes_from(map, start, robots))
+            .collect();
+        let all_keys = places[robots..]
+            .iter()
+            .fold(0, |keys, &(_, c)| keys | bit(c));
+        Vault {
+            robots,
+            routes,
+            all_keys,
+        }
+    }
+
+    // Dijkstra on where the robots are and the keys they have, moving one
+    // robot to a new key at a time
+    fn shortest_collection(&self) -> usize {
+        let start: Vec<usize> = (0..self.robots).collect();
+        let mut distances: HashMap<(Vec<usize>, u32), usize> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+        distances.insert((start.clone(), 0), 0);
+        queue.push(Reverse((0, start, 0)));
+        while let Some(Reverse((distance, positions, keys))) = queue.pop() {
+            if keys == self.all_keys {
+                return distance;
+            }
+            if distances[&(positions.clone(), keys)] < distance {
+                continue;
+            }
+            for (robot, &place) in positions.iter().enumerate() {
+                for route in &self.routes[place] {
+                    let key = 1 << (route.key - self.robots);
+                    // going through another key first is another move
+                    if keys & key != 0 || route.doors & !keys != 0 || route.keys & !keys & !key != 0
+                    {
+                        continue;
+                    }
+                    let mut next = positions.clone();
+                    next[robot] = route.key;
+                    let state = (next, keys | key);
+                    let d = distance + route.distance;
+                    if distances.get(&state).is_none_or(|&old| d < old) {
+                        distances.insert(state.clone(), d);
+                        queue.push(Reverse((d, state.0, state.1)));
+                    }
+                }
+            }
+        }
+        panic!("some keys can't be reached");
+    }
+}
+
+// breadth first from `start` to every key, the keys being numbered after
+// the robots
+fn routes_from(map: &Grid<char>, start: Point, robots: usize) -> Vec<Route> {
+    let mut routes = Vec::new();
+    let mut seen = Grid::new(map.width(), map.height(), false);
+    let mut queue = VecDeque::new();
+    seen[start] = true;
+    queue.push_back((start, 0, 0, 0));
+    while let Some((p, distance, doors, keys)) = queue.pop_front() {
+        let (mut doors, mut keys) = (doors, keys);
+        match map[p] {
+            c @ 'A'..='Z' => doors |= bit(c),
+            c @ 'a'..='z' if p != start => {
+                routes.push(Route {
+                    key: robots + (c as usize - 'a' as usize),
+                    distance,
+                    doors,
+                    keys,
+                });
+                keys |= bit(c);
+            }
+            _ => {}
+        }
+        for n in map.neighbours4(p) {
+            if map[n] != '#' && !seen[n] {
+                seen[n] = true;
+                queue.push_back((n, distance + 1, doors, keys));
+            }
+        }
+    }
+    routes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn shortest(text: &str, split: bool) -> usize {
+        let mut map = parse_map(text);
+        if split {
+            split_entrance(&mut map);
+        }
+        Vault::new(&map).shortest_collection()
+    }
+
+    #[test]
+    fn test_one_robot() {
+        assert_eq!(shortest("#########\n#b.A.@.a#\n#########\n", false), 8);
+        assert_eq!(
+            shortest(
+                "########################
+#f.D.E.e.C.b.A.@.a.B.c.#
+######################.#
+#d.....................#
+########################
+",
+                false
+            ),
+            86
+        );
+        assert_eq!(
+            shortest(
+                "########################
+#...............b.C.D.f#
+#.######################
+#.....@.a.B.c.d.A.e.F.g#
+########################
+",
+                false
+            ),
+            132
+        );
+        assert_eq!(
+            shortest(
+                "#################
+#i.G..c...e..H.p#
+########.########
+#j.A..b...f..D.o#
+########@########
+#k.E..a...g..B.n#
+########.########
+#l.F..d...h..C.m#
+#################
+",
+                false
+            ),
+            136
+        );
+        assert_eq!(
+            shortest(
+                "########################
+#@..............ac.GI.b#
+###d#e#f################
+###A#B#C################
+###g#h#i################
+########################
+",
+                false
+            ),
+            81
+        );
+    }
+
+    #[test]
+    fn test_four_robots() {
+        assert_eq!(
+            shortest(
+                "#######\n#a.#Cd#\n##...##\n##.@.##\n##...##\n#cB#Ab#\n#######\n",
+                true
+            ),
+            8
+        );
+        assert_eq!(
+            shortest(
+                "###############
+#d.ABC.#.....a#
+######@#@######
+###############
+######@#@######
+#b.....#.....c#
+###############
+",
+                true
+            ),
+            24
+        );
+        assert_eq!(
+            shortest(
+                "#############
+#DcBa.#.GhKl#
+#.###@#@#I###
+#e#d#####j#k#
+###C#@#@###J#
+#fEbA.#.FgHi#
+#############
+",
+                true
+            ),
+            32
+        );
+        assert_eq!(
+            shortest(
+                "#############
+#g#f.D#..h#l#
+#F###e#E###.#
+#dCba@#@BcIJ#
+#############
+#nK.L@#@G...#
+#M###N#H###.#
+#o#m..#i#jk.#
+#############
+",
+                true
+            ),
+            72
+        );
+    }
+}
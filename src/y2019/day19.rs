@@ -0,0 +1,99 @@
+use y2019::computer;
+use y2019::computer::Computer;
+
+const SHIP: i64 = 100;
+
+pub fn answer1() {
+    let beam = drone_beam();
+    println!("{}", affected(&beam, 50));
+}
+
+pub fn answer2() {
+    let beam = drone_beam();
+    let (x, y) = closest_square(&beam, SHIP);
+    println!("{}", x * 10_000 + y);
+}
+
+// whether a position is pulled by the beam, asking a fresh drone each time
+fn drone_beam() -> impl Fn(i64, i64) -> bool {
+    let drone = Computer::new(computer::read_codes("data/2019/day19.txt"));
+    move |x, y| {
+        let mut drone = drone.clone();
+        drone.push_inputs(&[x, y]);
+        let (outputs, _) = drone.run_until_blocked().unwrap();
+        outputs == [1]
+    }
+}
+
+// affected positions in the square of that size at the origin
+fn affected<F: Fn(i64, i64) -> bool>(beam: &F, size: i64) -> usize {
+    (0..size)
+        .flat_map(|y| (0..size).map(move |x| (x, y)))
+        .filter(|&(x, y)| beam(x, y))
+        .count()
+}
+
+// The top left corner of the closest square of that size fully in the beam.
+// Going down the left edge of the beam, the first position whose top right
+// corner is also in the beam is the bottom left of the square. Close to the
+// emitter the beam can skip rows, those are skipped too.
+fn closest_square<F: Fn(i64, i64) -> bool>(beam: &F, size: i64) -> (i64, i64) {
+    let mut left = 0;
+    for y in size - 1.. {
+        let edge = (left..=left + y + 1).find(|&x| beam(x, y));
+        if let Some(x) = edge {
+            left = x;
+            if beam(x + size - 1, y - size + 1) {
+                return (x, y - size + 1);
+            }
+        }
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // the example beam, nothing is pulled outside of the picture
+    fn picture_beam(picture: &'static str) -> impl Fn(i64, i64) -> bool {
+        let rows: Vec<&str> = picture.lines().collect();
+        move |x, y| {
+            rows.get(y as usize)
+                .and_then(|row| row.chars().nth(x as usize))
+                == Some('#')
+        }
+    }
+
+    #[test]
+    fn test_affected() {
+        let beam = picture_beam(
+            "#.........
+.#........
+..##......
+...###....
+....###...
+.....####.
+......####
+......####
+.......###
+........##
+",
+        );
+        assert_eq!(affected(&beam, 10), 27);
+    }
+
+    #[test]
+    fn test_closest_square() {
+        // between the lines y = x and y = 2x, compared to a brute force search
+        let beam = |x: i64, y: i64| x <= y && y <= 2 * x;
+        let size = 10;
+        let fits = |x: i64, y: i64| beam(x, y + size - 1) && beam(x + size - 1, y);
+        let expected = (0..100)
+            .flat_map(|y| (0..100).map(move |x| (x, y)))
+            .filter(|&(x, y)| fits(x, y))
+            .min_by_key(|&(x, y)| (x + y, x))
+            .unwrap();
+        assert_eq!(closest_square(&beam, size), expected);
+    }
+}
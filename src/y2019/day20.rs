@@ -0,0 +1,155 @@
+use grid::{Grid, Point};
+use input;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+const START: &str = "AA";
+const END: &str = "ZZ";
+
+struct Maze {
+    open: Grid<bool>,
+    // from the tile next to a portal, the tile next to the other end, and
+    // the change of level going through: +1 for the inner portals
+    portals: HashMap<Point, (Point, i32)>,
+    start: Point,
+    end: Point,
+}
+
+pub fn answer1() {
+    println!("{}", read_maze().shortest(false).unwrap());
+}
+
+pub fn answer2() {
+    println!("{}", read_maze().shortest(true).unwrap());
+}
+
+fn read_maze() -> Maze {
+    Maze::parse(&input::read_to_string("data/2019/day20.txt"))
+}
+
+impl Maze {
+    // the labels are read from top to bottom or left to right, next to the
+    // open tile of the portal
+    fn parse(text: &str) -> Maze {
+        let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+        let width = lines.iter().map(|l| l.len()).max().unwrap();
+        let padded: Vec<String> = lines
+            .iter()
+            .map(|l| format!("{:w$}", l, w = width))
+            .collect();
+        let cells = Grid::parse(&padded.join("\n"), Some).unwrap();
+
+        let mut labels: HashMap<String, Vec<Point>> = HashMap::new();
+        for (p, &c) in cells.iter() {
+            if !c.is_ascii_uppercase() {
+                continue;
+            }
+            for &step in [Point::new(1, 0), Point::new(0, 1)].iter() {
+                let second = match cells.get(p + step) {
+                    Some(&c) if c.is_ascii_uppercase() => c,
+                    _ => continue,
+                };
+                let before = p - step;
+                let after = p + step * 2;
+                let tile = if cells.get(before) == Some(&'.') {
+                    before
+                } else {
+                    after
+                };
+                labels
+                    .entry(format!("{}{}", c, second))
+                    .or_default()
+                    .push(tile);
+            }
+        }
+
+        let outer = |p: Point| {
+            p.x == 2 || p.y == 2 || p.x == width as i32 - 3 || p.y == cells.height() as i32 - 3
+        };
+        let mut portals = HashMap::new();
+        for tiles in labels.values() {
+            if let [a, b] = tiles[..] {
+                portals.insert(a, (b, if outer(a) { -1 } else { 1 }));
+                portals.insert(b, (a, if outer(b) { -1 } else { 1 }));
+            }
+        }
+        Maze {
+            open: cells.map(|&c| c == '.'),
+            portals,
+            start: labels[START][0],
+            end: labels[END][0],
+        }
+    }
+
+    // Breadth first on the tile and the level. In the recursive maze, the
+    // outer portals are walls on the outermost level, and there's no point
+    // going deeper than there are portals.
+    fn shortest(&self, recursive: bool) -> Option<usize> {
+        let max_level = if recursive {
+            self.portals.len() as i32
+        } else {
+            0
+        };
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+        seen.insert((self.start, 0));
+        queue.push_back((self.start, 0, 0));
+        while let Some((p, level, distance)) = queue.pop_front() {
+            if (p, level) == (self.end, 0) {
+                return Some(distance);
+            }
+            let walks = self
+                .open
+                .neighbours4(p)
+                .filter(|&n| self.open[n])
+                .map(|n| (n, level));
+            let jump = self.portals.get(&p).and_then(|&(to, delta)| {
+                let level = if recursive { level + delta } else { 0 };
+                if (0..=max_level).contains(&level) {
+                    Some((to, level))
+                } else {
+                    None
+                }
+            });
+            for state in walks.chain(jump) {
+                if seen.insert(state) {
+                    queue.push_back((state.0, state.1, distance + 1));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE1: &str = "         A           
+         A           
+  #######.#########  
+  #######.........#  
+  #######.#######.#  
+  #######.#######.#  
+  #######.#######.#  
+  #####  B    ###.#  
+BC...##  C    ###.#  
+  ##.##       ###.#  
+  ##...DE  F  ###.#  
+  #####    G  ###.#  
+  #########.#####.#  
+DE..#######...###.#  
+  #.#########.###.#  
+FG..#########.....#  
+  ###########.#####  
+             Z       
+             Z       
+";
+
+    #[test]
+    fn test_shortest() {
+        let maze = Maze::parse(EXAMPLE1);
+        assert_eq!(maze.portals.len(), 6);
+        assert_eq!(maze.shortest(false), Some(23));
+        assert_eq!(maze.shortest(true), Some(26));
+    }
+}
@@ -0,0 +1,234 @@
+use std::fmt;
+use y2019::computer;
+use y2019::computer::Computer;
+
+// jump if there's a hole in the next 3 tiles and ground to land on
+const WALK_SCRIPT: &str = "NOT A J
+NOT B T
+OR T J
+NOT C T
+OR T J
+AND D J
+";
+
+// same, but only if the droid can go on after landing: walk one tile, or
+// jump again right away
+const RUN_SCRIPT: &str = "NOT A J
+NOT B T
+OR T J
+NOT C T
+OR T J
+AND D J
+NOT E T
+NOT T T
+OR H T
+AND T J
+";
+
+const MAX_INSTRUCTIONS: usize = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    And,
+    Or,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Instruction {
+    op: Op,
+    x: char,
+    y: char,
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match self.op {
+            Op::And => "AND",
+            Op::Or => "OR",
+            Op::Not => "NOT",
+        };
+        write!(f, "{} {} {}", op, self.x, self.y)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    // sees up to D
+    Walk,
+    // sees up to I
+    Run,
+}
+
+impl Mode {
+    fn command(self) -> &'static str {
+        match self {
+            Mode::Walk => "WALK",
+            Mode::Run => "RUN",
+        }
+    }
+
+    fn sensors(self) -> usize {
+        match self {
+            Mode::Walk => 4,
+            Mode::Run => 9,
+        }
+    }
+}
+
+pub fn answer1() {
+    survey(WALK_SCRIPT, Mode::Walk);
+}
+
+pub fn answer2() {
+    survey(RUN_SCRIPT, Mode::Run);
+}
+
+// one instruction per line, checked against the sensors of the mode
+fn parse_script(text: &str, mode: Mode) -> Vec<Instruction> {
+    let script: Vec<Instruction> = text
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            assert_eq!(parts.len(), 3, "invalid instruction {:?}", line);
+            let op = match parts[0] {
+                "AND" => Op::And,
+                "OR" => Op::Or,
+                "NOT" => Op::Not,
+                _ => panic!("unknown instruction {:?}", line),
+            };
+            let register = |i: usize, writable: bool| {
+                let r = parts[i].chars().next().unwrap();
+                let valid = match r {
+                    'T' | 'J' => true,
+                    'A'..='I' => !writable && ((r as u8 - b'A') as usize) < mode.sensors(),
+                    _ => false,
+                };
+                assert!(valid, "invalid register in {:?}", line);
+                r
+            };
+            Instruction {
+                op,
+                x: register(1, false),
+                y: register(2, true),
+            }
+        })
+        .collect();
+    assert!(script.len() <= MAX_INSTRUCTIONS, "the script is too long");
+    script
+}
+
+// the damage to the hull, or the last moments of the droid
+fn survey(script: &str, mode: Mode) {
+    let mut droid = Computer::new(computer::read_codes("data/2019/day21.txt"));
+    let mut input = String::new();
+    for instruction in parse_script(script, mode) {
+        input += &format!("{}\n", instruction);
+    }
+    input += mode.command();
+    input.push('\n');
+    droid.push_inputs(&input.bytes().map(i64::from).collect::<Vec<_>>());
+    let (outputs, _) = droid.run_until_blocked().unwrap();
+    match outputs.last() {
+        Some(&damage) if damage > 127 => println!("{}", damage),
+        _ => print!(
+            "{}",
+            outputs.iter().map(|&c| c as u8 as char).collect::<String>()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // how far each jump goes
+    const JUMP: usize = 4;
+
+    // whether the script decides to jump, with `ground` telling if there's
+    // ground at the given distance
+    fn jumps<F: Fn(usize) -> bool>(script: &[Instruction], ground: F) -> bool {
+        let (mut t, mut j) = (false, false);
+        for ins in script {
+            let x = match ins.x {
+                'T' => t,
+                'J' => j,
+                c => ground((c as u8 - b'A') as usize + 1),
+            };
+            let y = if ins.y == 'T' { &mut t } else { &mut j };
+            *y = match ins.op {
+                Op::And => x && *y,
+                Op::Or => x || *y,
+                Op::Not => !x,
+            };
+        }
+        j
+    }
+
+    // whether the droid gets to the end of the hull, drawn with '#' and '.',
+    // starting on the first tile. Past the end, it's all ground.
+    fn survives(script: &[Instruction], hull: &str) -> bool {
+        let hull: Vec<bool> = hull.chars().map(|c| c == '#').collect();
+        let ground = |p: usize| hull.get(p).cloned().unwrap_or(true);
+        let mut position = 0;
+        while position < hull.len() {
+            if !ground(position) {
+                return false;
+            }
+            position += if jumps(script, |d| ground(position + d)) {
+                JUMP
+            } else {
+                1
+            };
+        }
+        true
+    }
+
+    // the holes the droid usually falls in
+    const HULLS: [&str; 6] = [
+        "#####.###########",
+        "#####...#########",
+        "#####..#.########",
+        "#####.#..########",
+        "#####.#.##..#.###",
+        "#####.#.#...#.###",
+    ];
+
+    #[test]
+    fn test_scripts() {
+        let walk = parse_script(WALK_SCRIPT, Mode::Walk);
+        let run = parse_script(RUN_SCRIPT, Mode::Run);
+        for hull in HULLS[..4].iter() {
+            assert!(survives(&walk, hull), "{}", hull);
+        }
+        for hull in HULLS.iter() {
+            assert!(survives(&run, hull), "{}", hull);
+        }
+        // jumping as early as possible lands too close to the next hole
+        for hull in HULLS[4..].iter() {
+            assert!(!survives(&walk, hull), "{}", hull);
+        }
+    }
+
+    #[test]
+    fn test_jumps() {
+        let script = parse_script("NOT A J\n", Mode::Walk);
+        assert_eq!(script[0].to_string(), "NOT A J");
+        assert!(jumps(&script, |d| d != 1));
+        assert!(!jumps(&script, |_| true));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sensor_out_of_range() {
+        parse_script("NOT E J\n", Mode::Walk);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_write_to_sensor() {
+        parse_script("NOT J A\n", Mode::Run);
+    }
+}
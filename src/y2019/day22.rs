@@ -0,0 +1,162 @@
+use input;
+
+const SMALL_DECK: i128 = 10_007;
+const HUGE_DECK: i128 = 119_315_717_514_047;
+const SHUFFLES: u64 = 101_741_582_076_661;
+
+// A shuffle moves the card at position `p` to `a * p + b`, modulo the size
+// of the deck. Every technique is like that, so any sequence of them too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Shuffle {
+    a: i128,
+    b: i128,
+    size: i128,
+}
+
+impl Shuffle {
+    fn identity(size: i128) -> Shuffle {
+        Shuffle { a: 1, b: 0, size }
+    }
+
+    // "deal into new stack", "cut 3", "deal with increment 7"
+    fn technique(line: &str, size: i128) -> Shuffle {
+        let (a, b) = if line == "deal into new stack" {
+            (-1, -1)
+        } else if let Some(n) = line.strip_prefix("cut ") {
+            (1, -n.parse::<i128>().unwrap())
+        } else if let Some(n) = line.strip_prefix("deal with increment ") {
+            (n.parse().unwrap(), 0)
+        } else {
+            panic!("unknown technique {:?}", line)
+        };
+        Shuffle { a, b, size }.normalized()
+    }
+
+    fn normalized(self) -> Shuffle {
+        Shuffle {
+            a: self.a.rem_euclid(self.size),
+            b: self.b.rem_euclid(self.size),
+            size: self.size,
+        }
+    }
+
+    // this shuffle, then the other one
+    fn then(self, other: Shuffle) -> Shuffle {
+        Shuffle {
+            a: self.a * other.a,
+            b: self.b * other.a + other.b,
+            size: self.size,
+        }
+        .normalized()
+    }
+
+    // the shuffle done `n` times, by squaring
+    fn repeat(self, n: u64) -> Shuffle {
+        let mut result = Shuffle::identity(self.size);
+        let mut square = self;
+        let mut n = n;
+        while n > 0 {
+            if n & 1 == 1 {
+                result = result.then(square);
+            }
+            square = square.then(square);
+            n >>= 1;
+        }
+        result
+    }
+
+    fn position(self, card: i128) -> i128 {
+        (self.a * card + self.b).rem_euclid(self.size)
+    }
+
+    // The card ending at `position`. The deck size must be prime, so that
+    // `a` has an inverse: `a ^ (size - 2)`.
+    fn card(self, position: i128) -> i128 {
+        let inverse = Shuffle {
+            a: self.a,
+            b: 0,
+            size: self.size,
+        }
+        .repeat(self.size as u64 - 2)
+        .a;
+        ((position - self.b) * inverse).rem_euclid(self.size)
+    }
+}
+
+pub fn answer1() {
+    println!("{}", read_shuffle(SMALL_DECK).position(2019));
+}
+
+pub fn answer2() {
+    println!("{}", read_shuffle(HUGE_DECK).repeat(SHUFFLES).card(2020));
+}
+
+fn read_shuffle(size: i128) -> Shuffle {
+    parse_shuffle(&input::read_to_string("data/2019/day22.txt"), size)
+}
+
+fn parse_shuffle(text: &str, size: i128) -> Shuffle {
+    text.lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .fold(Shuffle::identity(size), |s, line| {
+            s.then(Shuffle::technique(line, size))
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // the cards from the top of the deck
+    fn deck(shuffle: Shuffle) -> Vec<i128> {
+        let mut deck = vec![0; shuffle.size as usize];
+        for card in 0..shuffle.size {
+            deck[shuffle.position(card) as usize] = card;
+        }
+        deck
+    }
+
+    #[test]
+    fn test_techniques() {
+        let examples = [
+            (
+                "deal with increment 7\ndeal into new stack\ndeal into new stack",
+                [0, 3, 6, 9, 2, 5, 8, 1, 4, 7],
+            ),
+            (
+                "cut 6\ndeal with increment 7\ndeal into new stack",
+                [3, 0, 7, 4, 1, 8, 5, 2, 9, 6],
+            ),
+            (
+                "deal with increment 7\ndeal with increment 9\ncut -2",
+                [6, 3, 0, 7, 4, 1, 8, 5, 2, 9],
+            ),
+            (
+                "deal into new stack\ncut -2\ndeal with increment 7\ncut 8\ncut -4\n\
+                 deal with increment 7\ncut 3\ndeal with increment 9\ndeal with increment 3\ncut -1",
+                [9, 2, 5, 8, 1, 4, 7, 0, 3, 6],
+            ),
+        ];
+        for (text, expected) in examples.iter() {
+            assert_eq!(deck(parse_shuffle(text, 10)), expected.to_vec());
+        }
+        assert_eq!(
+            deck(Shuffle::technique("cut -4", 10)),
+            vec![6, 7, 8, 9, 0, 1, 2, 3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn test_repeat() {
+        let shuffle = parse_shuffle("cut 6\ndeal with increment 7\ndeal into new stack", 11);
+        let mut repeated = Shuffle::identity(11);
+        for n in 0..30 {
+            assert_eq!(shuffle.repeat(n), repeated);
+            for card in 0..11 {
+                assert_eq!(repeated.card(repeated.position(card)), card);
+            }
+            repeated = repeated.then(shuffle);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::VecDeque;
+use y2019::computer;
+use y2019::computer::Computer;
+
+const NICS: usize = 50;
+const NAT: i64 = 255;
+
+// a network interface: gets its inputs, returns its outputs by three, the
+// address then the packet
+trait Nic {
+    fn receive(&mut self, input: &[i64]) -> Vec<i64>;
+}
+
+impl Nic for Computer {
+    fn receive(&mut self, input: &[i64]) -> Vec<i64> {
+        self.push_inputs(input);
+        self.run_until_blocked().unwrap().0
+    }
+}
+
+// what goes through the NAT: the first Y value sent to it, and the first Y
+// value it delivers twice in a row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct NatLog {
+    first: i64,
+    repeated: i64,
+}
+
+pub fn answer1() {
+    println!("{}", run_network().first);
+}
+
+pub fn answer2() {
+    println!("{}", run_network().repeated);
+}
+
+fn run_network() -> NatLog {
+    let nic = Computer::new(computer::read_codes("data/2019/day23.txt"));
+    run(&mut vec![nic; NICS])
+}
+
+// The NICs take turns, each getting its queued packets, or -1 if there's
+// none. Once they all wait for packets, the NAT wakes up the first one with
+// the last packet it got.
+fn run<N: Nic>(nics: &mut [N]) -> NatLog {
+    let mut queues: Vec<VecDeque<(i64, i64)>> = vec![VecDeque::new(); nics.len()];
+    let mut nat: Option<(i64, i64)> = None;
+    let mut first = None;
+    let mut delivered = None;
+    let mut inputs: Vec<Vec<i64>> = (0..nics.len() as i64).map(|a| vec![a]).collect();
+    loop {
+        let mut sent = false;
+        for (i, nic) in nics.iter_mut().enumerate() {
+            let outputs = nic.receive(&inputs[i]);
+            for packet in outputs.chunks(3) {
+                sent = true;
+                let (address, x, y) = (packet[0], packet[1], packet[2]);
+                if address == NAT {
+                    first = first.or(Some(y));
+                    nat = Some((x, y));
+                } else {
+                    queues[address as usize].push_back((x, y));
+                }
+            }
+        }
+
+        if !sent && queues.iter().all(|q| q.is_empty()) {
+            let (x, y) = nat.expect("the network is idle from the start");
+            if delivered == Some(y) {
+                return NatLog {
+                    first: first.unwrap(),
+                    repeated: y,
+                };
+            }
+            delivered = Some(y);
+            queues[0].push_back((x, y));
+        }
+        for (input, queue) in inputs.iter_mut().zip(queues.iter_mut()) {
+            *input = if queue.is_empty() {
+                vec![-1]
+            } else {
+                queue.drain(..).flat_map(|(x, y)| vec![x, y]).collect()
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // passes the packets it gets to the next one, or to the NAT for the
+    // last one, adding 1 to Y the first time. The first one starts with a
+    // packet of its own.
+    struct Relay {
+        address: i64,
+        last: i64,
+        booted: bool,
+        forwarded: bool,
+    }
+
+    impl Nic for Relay {
+        fn receive(&mut self, input: &[i64]) -> Vec<i64> {
+            if !self.booted {
+                assert_eq!(input, [self.address]);
+                self.booted = true;
+                return if self.address == 0 {
+                    vec![1, 10, 20]
+                } else {
+                    vec![]
+                };
+            }
+            if input == [-1] {
+                return vec![];
+            }
+            let to = if self.address == self.last {
+                NAT
+            } else {
+                self.address + 1
+            };
+            let mut outputs = Vec::new();
+            for packet in input.chunks(2) {
+                let y = if self.forwarded {
+                    packet[1]
+                } else {
+                    packet[1] + 1
+                };
+                self.forwarded = true;
+                outputs.extend_from_slice(&[to, packet[0], y]);
+            }
+            outputs
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        let mut relays: Vec<Relay> = (0..4)
+            .map(|address| Relay {
+                address,
+                last: 3,
+                booted: false,
+                forwarded: false,
+            })
+            .collect();
+        assert_eq!(
+            run(&mut relays),
+            NatLog {
+                first: 23,
+                repeated: 24
+            }
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use cycle;
+use input;
+use std::collections::BTreeMap;
+
+const SIZE: i32 = 5;
+const CENTER: (i32, i32) = (2, 2);
+
+// bugs on a 5 x 5 area, one bit per tile line by line, which happens to be
+// the biodiversity rating
+type Area = u32;
+
+fn bit(x: i32, y: i32) -> Area {
+    1 << (y * SIZE + x)
+}
+
+fn has_bug(area: Area, x: i32, y: i32) -> bool {
+    area & bit(x, y) != 0
+}
+
+// a bug survives with exactly one bug around, and an empty tile gets one
+// with one or two
+fn next_tile(bug: bool, around: usize) -> bool {
+    around == 1 || (!bug && around == 2)
+}
+
+pub fn answer1() {
+    println!("{}", first_repeat(read_area()));
+}
+
+pub fn answer2() {
+    println!("{}", recursive_bugs(read_area(), 200));
+}
+
+fn read_area() -> Area {
+    parse_area(&input::read_to_string("data/2019/day24.txt"))
+}
+
+fn parse_area(text: &str) -> Area {
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .enumerate()
+        .flat_map(|(y, line)| {
+            line.trim()
+                .chars()
+                .enumerate()
+                .filter(|&(_, c)| c == '#')
+                .map(move |(x, _)| bit(x as i32, y as i32))
+        })
+        .fold(0, |area, b| area | b)
+}
+
+fn next_minute(area: Area) -> Area {
+    let mut next = 0;
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let around = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+                .iter()
+                .map(|(dx, dy)| (x + dx, y + dy))
+                .filter(|&(x, y)| (0..SIZE).contains(&x) && (0..SIZE).contains(&y))
+                .filter(|&(x, y)| has_bug(area, x, y))
+                .count();
+            if next_tile(has_bug(area, x, y), around) {
+                next |= bit(x, y);
+            }
+        }
+    }
+    next
+}
+
+fn first_repeat(area: Area) -> Area {
+    let areas = std::iter::successors(Some(area), |&a| Some(next_minute(a)));
+    cycle::first_repeat(areas).unwrap().value
+}
+
+// The neighbours of a tile in the recursive area, as a level and position.
+// Lower levels are outside, higher levels are inside the center tile.
+fn recursive_neighbours(level: i32, x: i32, y: i32) -> Vec<(i32, i32, i32)> {
+    let mut neighbours = Vec::new();
+    for &(dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)].iter() {
+        let (nx, ny) = (x + dx, y + dy);
+        if !(0..SIZE).contains(&nx) || !(0..SIZE).contains(&ny) {
+            neighbours.push((level - 1, CENTER.0 + dx, CENTER.1 + dy));
+        } else if (nx, ny) == CENTER {
+            // the whole side of the inner area facing the tile
+            for i in 0..SIZE {
+                neighbours.push(match (dx, dy) {
+                    (1, 0) => (level + 1, 0, i),
+                    (-1, 0) => (level + 1, SIZE - 1, i),
+                    (0, 1) => (level + 1, i, 0),
+                    _ => (level + 1, i, SIZE - 1),
+                });
+            }
+        } else {
+            neighbours.push((level, nx, ny));
+        }
+    }
+    neighbours
+}
+
+// number of bugs after that many minutes, starting with `area` on level 0
+fn recursive_bugs(area: Area, minutes: usize) -> u32 {
+    let mut levels: BTreeMap<i32, Area> = BTreeMap::new();
+    levels.insert(0, area);
+    for _ in 0..minutes {
+        let lowest = *levels.keys().next().unwrap() - 1;
+        let highest = *levels.keys().last().unwrap() + 1;
+        let bug =
+            |level: i32, x: i32, y: i32| levels.get(&level).is_some_and(|&a| has_bug(a, x, y));
+        let mut next = BTreeMap::new();
+        for level in lowest..=highest {
+            let mut area = 0;
+            for y in 0..SIZE {
+                for x in 0..SIZE {
+                    if (x, y) == CENTER {
+                        continue;
+                    }
+                    let around = recursive_neighbours(level, x, y)
+                        .into_iter()
+                        .filter(|&(l, x, y)| bug(l, x, y))
+                        .count();
+                    if next_tile(bug(level, x, y), around) {
+                        area |= bit(x, y);
+                    }
+                }
+            }
+            if area != 0 {
+                next.insert(level, area);
+            }
+        }
+        levels = next;
+    }
+    levels.values().map(|a| a.count_ones()).sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "....#\n#..#.\n#..##\n..#..\n#....\n";
+
+    #[test]
+    fn test_first_repeat() {
+        let area = parse_area(EXAMPLE);
+        assert_eq!(
+            next_minute(area),
+            parse_area("#..#.\n####.\n###.#\n##.##\n.##..\n")
+        );
+        assert_eq!(first_repeat(area), 2129920);
+    }
+
+    #[test]
+    fn test_recursive_neighbours() {
+        // the examples from the puzzle: tile 19 is inside its level
+        assert_eq!(recursive_neighbours(0, 3, 3).len(), 4);
+        // tile 14 is next to the 5 tiles of the right side of the inner level
+        let neighbours = recursive_neighbours(0, 3, 2);
+        assert_eq!(neighbours.len(), 8);
+        assert!(neighbours.contains(&(1, 4, 4)));
+        // tile E is next to tiles 8 and 14 of the outer level
+        let neighbours = recursive_neighbours(1, 4, 0);
+        assert_eq!(neighbours.len(), 4);
+        assert!(neighbours.contains(&(0, 2, 1)));
+        assert!(neighbours.contains(&(0, 3, 2)));
+    }
+
+    #[test]
+    fn test_recursive_bugs() {
+        assert_eq!(recursive_bugs(parse_area(EXAMPLE), 10), 99);
+    }
+}
@@ -0,0 +1,301 @@
+use combinatorics;
+use std::collections::HashSet;
+use y2019::computer;
+use y2019::computer::Computer;
+
+// taking any of those ends the game one way or another
+const BLACKLIST: [&str; 5] = [
+    "escape pod",
+    "giant electromagnet",
+    "infinite loop",
+    "molten lava",
+    "photons",
+];
+const CHECKPOINT: &str = "Security Checkpoint";
+
+// something which answers commands with text, like the droid
+trait Game {
+    fn send(&mut self, command: &str) -> String;
+}
+
+struct Droid {
+    computer: Computer,
+}
+
+impl Droid {
+    // the droid, and what it says when it wakes up
+    fn start() -> (Droid, String) {
+        let mut computer = Computer::new(computer::read_codes("data/2019/day25.txt"));
+        let (outputs, _) = computer.run_until_blocked().unwrap();
+        (Droid { computer }, ascii(&outputs))
+    }
+}
+
+fn ascii(outputs: &[i64]) -> String {
+    outputs.iter().map(|&c| c as u8 as char).collect()
+}
+
+impl Game for Droid {
+    fn send(&mut self, command: &str) -> String {
+        let input: Vec<i64> = command.bytes().chain(Some(b'\n')).map(i64::from).collect();
+        self.computer.push_inputs(&input);
+        let (outputs, _) = self.computer.run_until_blocked().unwrap();
+        ascii(&outputs)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Room {
+    name: String,
+    doors: Vec<String>,
+    items: Vec<String>,
+}
+
+// The last room described in the text. Its doors and items are listed
+// after a header line, one per line starting with "- ".
+fn parse_room(text: &str) -> Option<Room> {
+    let start = text.rfind("== ")?;
+    let mut lines = text[start..].lines();
+    let name = lines.next()?.trim_matches(|c| c == '=' || c == ' ');
+    let mut room = Room {
+        name: name.to_string(),
+        doors: Vec::new(),
+        items: Vec::new(),
+    };
+    let mut list = None;
+    for line in lines {
+        match line {
+            "Doors here lead:" => list = Some(&mut room.doors),
+            "Items here:" => list = Some(&mut room.items),
+            _ => match (line.strip_prefix("- "), list.as_mut()) {
+                (Some(entry), Some(list)) => list.push(entry.to_string()),
+                _ => list = None,
+            },
+        }
+    }
+    Some(room)
+}
+
+fn opposite(door: &str) -> &'static str {
+    match door {
+        "north" => "south",
+        "south" => "north",
+        "east" => "west",
+        "west" => "east",
+        _ => panic!("unknown door {:?}", door),
+    }
+}
+
+// what the droid learns going around the ship
+#[derive(Debug, Default)]
+struct Survey {
+    visited: HashSet<String>,
+    inventory: Vec<String>,
+    // the way to the checkpoint, and the door to the pressure-sensitive floor
+    checkpoint: Option<(Vec<String>, String)>,
+}
+
+// Depth first, taking every safe item on the way and coming back to where
+// it started. The floor behind the checkpoint sends the droid back if it
+// doesn't have the right items yet, so it's left for later.
+fn explore<G: Game>(game: &mut G, room: &Room, path: &mut Vec<String>, survey: &mut Survey) {
+    survey.visited.insert(room.name.clone());
+    for item in &room.items {
+        if !BLACKLIST.contains(&item.as_str()) {
+            game.send(&format!("take {}", item));
+            survey.inventory.push(item.clone());
+        }
+    }
+    let back = path.last().map(|d| opposite(d));
+    if room.name == CHECKPOINT {
+        let floor = room.doors.iter().find(|&d| Some(d.as_str()) != back);
+        let floor = floor.expect("the checkpoint leads nowhere");
+        survey.checkpoint = Some((path.clone(), floor.clone()));
+        return;
+    }
+    for door in &room.doors {
+        if Some(door.as_str()) == back {
+            continue;
+        }
+        let next = parse_room(&game.send(door)).expect("no room behind the door");
+        if !survey.visited.contains(&next.name) {
+            path.push(door.clone());
+            explore(game, &next, path, survey);
+            path.pop();
+        }
+        game.send(opposite(door));
+    }
+}
+
+// the password, once the droid goes through the checkpoint with the right
+// items, trying every combination of them
+fn password<G: Game>(game: &mut G, intro: &str) -> String {
+    let mut survey = Survey::default();
+    let start = parse_room(intro).expect("the droid is nowhere");
+    explore(game, &start, &mut Vec::new(), &mut survey);
+    let (path, floor) = survey.checkpoint.expect("no security checkpoint");
+    for door in &path {
+        game.send(door);
+    }
+    for item in &survey.inventory {
+        game.send(&format!("drop {}", item));
+    }
+
+    for items in combinatorics::power_set(&survey.inventory) {
+        for item in &items {
+            game.send(&format!("take {}", item));
+        }
+        let answer = game.send(&floor);
+        if !answer.contains("Alert!") {
+            let mut digits = answer.split(|c: char| !c.is_ascii_digit());
+            return digits
+                .rfind(|d| !d.is_empty())
+                .expect("no password")
+                .to_string();
+        }
+        for item in &items {
+            game.send(&format!("drop {}", item));
+        }
+    }
+    panic!("no combination of items gets through");
+}
+
+pub fn answer1() {
+    let (mut droid, intro) = Droid::start();
+    println!("{}", password(&mut droid, &intro));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    const FLOOR: &str = "Pressure-Sensitive Floor";
+
+    // each door of a room, and the room behind it
+    type Doors = Vec<(&'static str, &'static str)>;
+
+    // a small ship, where the floor wants the mug and the spool of cat6
+    struct Ship {
+        rooms: HashMap<&'static str, (Doors, Vec<String>)>,
+        current: &'static str,
+        inventory: Vec<String>,
+    }
+
+    impl Ship {
+        fn new() -> Ship {
+            let mut rooms = HashMap::new();
+            let items = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+            rooms.insert(
+                "Hull Breach",
+                (
+                    vec![("north", "Kitchen"), ("east", CHECKPOINT)],
+                    items(&["mug"]),
+                ),
+            );
+            rooms.insert(
+                "Kitchen",
+                (
+                    vec![("south", "Hull Breach"), ("east", "Stables")],
+                    items(&["infinite loop", "spool of cat6"]),
+                ),
+            );
+            rooms.insert(
+                "Stables",
+                (vec![("west", "Kitchen")], items(&["hypercube"])),
+            );
+            rooms.insert(
+                CHECKPOINT,
+                (vec![("west", "Hull Breach"), ("east", FLOOR)], Vec::new()),
+            );
+            Ship {
+                rooms,
+                current: "Hull Breach",
+                inventory: Vec::new(),
+            }
+        }
+
+        fn describe(&self) -> String {
+            let (doors, items) = &self.rooms[self.current];
+            let mut text = format!(
+                "\n\n\n== {} ==\nSome description.\n\nDoors here lead:\n",
+                self.current
+            );
+            for (door, _) in doors {
+                text += &format!("- {}\n", door);
+            }
+            if !items.is_empty() {
+                text += "\nItems here:\n";
+                for item in items {
+                    text += &format!("- {}\n", item);
+                }
+            }
+            text + "\nCommand?\n"
+        }
+    }
+
+    impl Game for Ship {
+        fn send(&mut self, command: &str) -> String {
+            if let Some(item) = command.strip_prefix("take ") {
+                assert_ne!(item, "infinite loop", "the droid is stuck");
+                let items = &mut self.rooms.get_mut(self.current).unwrap().1;
+                items.retain(|i| i != item);
+                self.inventory.push(item.to_string());
+                return format!("\nYou take the {}.\n\nCommand?\n", item);
+            }
+            if let Some(item) = command.strip_prefix("drop ") {
+                self.inventory.retain(|i| i != item);
+                let items = &mut self.rooms.get_mut(self.current).unwrap().1;
+                items.push(item.to_string());
+                return format!("\nYou drop the {}.\n\nCommand?\n", item);
+            }
+            let (doors, _) = &self.rooms[self.current];
+            let to = doors
+                .iter()
+                .find(|(d, _)| *d == command)
+                .map(|&(_, to)| to)
+                .expect("no such door");
+            if to != FLOOR {
+                self.current = to;
+                return self.describe();
+            }
+            let mut carried = self.inventory.clone();
+            carried.sort();
+            if carried == ["mug", "spool of cat6"] {
+                "\n\n\n== Pressure-Sensitive Floor ==\nAnalysis complete! You may proceed.\n\
+                 \"Oh, hello! You should be able to get in by typing 1090617344 on the \
+                 keypad at the main airlock.\"\n"
+                    .to_string()
+            } else {
+                format!(
+                    "\n\n\n== Pressure-Sensitive Floor ==\nA loud, robotic voice says \
+                     \"Alert! Droids on this ship are heavier than the detected value!\" \
+                     and you are ejected back to the checkpoint.\n{}",
+                    self.describe()
+                )
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_room() {
+        let ship = Ship::new();
+        assert_eq!(
+            parse_room(&ship.describe()),
+            Some(Room {
+                name: "Hull Breach".to_string(),
+                doors: vec!["north".to_string(), "east".to_string()],
+                items: vec!["mug".to_string()],
+            })
+        );
+        assert_eq!(parse_room("\nYou take the mug.\n"), None);
+    }
+
+    #[test]
+    fn test_password() {
+        let mut ship = Ship::new();
+        let intro = ship.describe();
+        assert_eq!(password(&mut ship, &intro), "1090617344");
+        assert_eq!(ship.current, CHECKPOINT);
+    }
+}
@@ -0,0 +1,286 @@
+// The rocket equation, from day 1.
+//
+// A mass needs `mass / divisor - subtraction` fuel (rounded down, and never
+// negative), and that fuel has a mass too, which needs fuel, and so on. Each
+// step divides the mass, so even the largest `u128` only takes a few dozen
+// steps. Masses from the same list quickly end up on the same small amounts
+// of fuel though, so the totals are memoised when computing a whole list.
+// With a divisor of 1 the steps only subtract: the fuels then form an
+// arithmetic series, which is summed directly.
+
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Equation {
+    divisor: u128,
+    subtraction: u128,
+}
+
+// the fuel needed by one module
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModuleFuel {
+    pub mass: u128,
+    // for the module alone
+    pub fuel: u128,
+    // for the module and all its fuel
+    pub total: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MassError {
+    Invalid { line: usize, content: String },
+    Negative { line: usize, content: String },
+}
+
+impl fmt::Display for MassError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MassError::Invalid { line, content } => {
+                write!(f, "line {}: expected a mass, got {:?}", line, content)
+            }
+            MassError::Negative { line, content } => {
+                write!(f, "line {}: negative mass {}", line, content)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MassError {}
+
+// the total fuel of a module doesn't fit in a `u128`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FuelOverflow {
+    pub mass: u128,
+}
+
+impl fmt::Display for FuelOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the fuel for a mass of {} overflows", self.mass)
+    }
+}
+
+impl std::error::Error for FuelOverflow {}
+
+impl Equation {
+    // the one from the puzzle
+    pub const SANTA: Equation = Equation {
+        divisor: 3,
+        subtraction: 2,
+    };
+
+    // the fuel must be lighter than its mass, otherwise the total is infinite
+    pub fn converges(divisor: u128, subtraction: u128) -> bool {
+        divisor > 1 || (divisor == 1 && subtraction > 0)
+    }
+
+    pub fn new(divisor: u128, subtraction: u128) -> Equation {
+        assert!(
+            Equation::converges(divisor, subtraction),
+            "the equation {}/{} never converges",
+            divisor,
+            subtraction
+        );
+        Equation {
+            divisor,
+            subtraction,
+        }
+    }
+
+    pub fn fuel(&self, mass: u128) -> u128 {
+        (mass / self.divisor).saturating_sub(self.subtraction)
+    }
+
+    pub fn total_fuel(&self, mass: u128) -> Result<u128, FuelOverflow> {
+        self.memoised_total(mass, &mut HashMap::new())
+            .ok_or(FuelOverflow { mass })
+    }
+
+    // with a divisor of at least 2 the totals are lighter than the masses, so
+    // summing the breakdown overflows only if the masses themselves add up to
+    // more than a `u128`
+    pub fn breakdown(&self, masses: &[u128]) -> Result<Vec<ModuleFuel>, FuelOverflow> {
+        let mut totals = HashMap::new();
+        masses
+            .iter()
+            .map(|&mass| {
+                let fuel = self.fuel(mass);
+                let total = self
+                    .memoised_total(fuel, &mut totals)
+                    .and_then(|t| t.checked_add(fuel))
+                    .ok_or(FuelOverflow { mass })?;
+                Ok(ModuleFuel { mass, fuel, total })
+            })
+            .collect()
+    }
+
+    // the fuel for the mass, its fuel and so on, none on overflow
+    fn memoised_total(&self, mass: u128, totals: &mut HashMap<u128, u128>) -> Option<u128> {
+        if self.divisor == 1 {
+            return self.series_total(mass);
+        }
+        // walk down until a known mass, then fill in the totals on the way back
+        let mut chain = Vec::new();
+        let mut current = mass;
+        let mut total = loop {
+            if current == 0 {
+                break 0;
+            }
+            if let Some(&total) = totals.get(&current) {
+                break total;
+            }
+            chain.push(current);
+            current = self.fuel(current);
+        };
+        while let Some(m) = chain.pop() {
+            total = total.checked_add(self.fuel(m))?;
+            totals.insert(m, total);
+        }
+        Some(total)
+    }
+
+    // without division, the fuels go down by `subtraction` from
+    // `mass - subtraction` to `mass % subtraction`: there are `mass /
+    // subtraction` of them, which is far too many to walk through
+    fn series_total(&self, mass: u128) -> Option<u128> {
+        let steps = mass / self.subtraction;
+        let smallest = mass % self.subtraction;
+        // steps * (steps - 1) / 2, halving first so that it can't overflow
+        // when the result fits
+        let pairs = if steps.is_multiple_of(2) {
+            (steps / 2).checked_mul(steps.saturating_sub(1))?
+        } else {
+            steps.checked_mul((steps - 1) / 2)?
+        };
+        steps
+            .checked_mul(smallest)?
+            .checked_add(pairs.checked_mul(self.subtraction)?)
+    }
+}
+
+// one mass per line, blank lines are ignored and line numbers start at 1
+pub fn parse_masses(text: &str) -> Result<Vec<u128>, Vec<MassError>> {
+    let mut masses = Vec::new();
+    let mut errors = Vec::new();
+    for (i, raw) in text.lines().enumerate() {
+        let content = raw.trim();
+        if content.is_empty() {
+            continue;
+        }
+        match content.parse::<u128>() {
+            Ok(mass) => masses.push(mass),
+            Err(_) => {
+                let line = i + 1;
+                let content = content.to_string();
+                if content.starts_with('-') && content[1..].parse::<u128>().is_ok() {
+                    errors.push(MassError::Negative { line, content });
+                } else {
+                    errors.push(MassError::Invalid { line, content });
+                }
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(masses)
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fuel() {
+        // the examples from the puzzle
+        let santa = Equation::SANTA;
+        assert_eq!(santa.fuel(12), 2);
+        assert_eq!(santa.fuel(14), 2);
+        assert_eq!(santa.fuel(1969), 654);
+        assert_eq!(santa.fuel(100756), 33583);
+        assert_eq!(santa.total_fuel(14), Ok(2));
+        assert_eq!(santa.total_fuel(1969), Ok(966));
+        assert_eq!(santa.total_fuel(100756), Ok(50346));
+        assert_eq!(santa.fuel(5), 0);
+    }
+
+    #[test]
+    fn test_other_equations() {
+        let halves = Equation::new(2, 0);
+        assert_eq!(halves.total_fuel(64), Ok(32 + 16 + 8 + 4 + 2 + 1));
+        let minus_one = Equation::new(1, 1);
+        assert_eq!(minus_one.total_fuel(4), Ok(3 + 2 + 1));
+        assert_eq!(Equation::new(1, 3).total_fuel(11), Ok(8 + 5 + 2));
+        assert_eq!(Equation::new(1, 3).total_fuel(2), Ok(0));
+        // no overflow, and not too slow either
+        assert!(Equation::SANTA.total_fuel(u128::MAX).unwrap() < u128::MAX / 2);
+    }
+
+    #[test]
+    fn test_huge_masses() {
+        // 2^64 steps, all summed at once
+        let minus_one = Equation::new(1, 1);
+        let mass = 1u128 << 64;
+        assert_eq!(minus_one.total_fuel(mass), Ok(mass / 2 * (mass - 1)));
+        assert_eq!(
+            minus_one.total_fuel(u128::MAX),
+            Err(FuelOverflow { mass: u128::MAX })
+        );
+        let heavy = Equation::new(1, 1 << 100);
+        assert_eq!(
+            heavy.total_fuel((3 << 100) + 5),
+            Ok(((2 << 100) + 5) + ((1 << 100) + 5) + 5)
+        );
+        assert_eq!(
+            minus_one.breakdown(&[4, u128::MAX]),
+            Err(FuelOverflow { mass: u128::MAX })
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_diverging_equation() {
+        Equation::new(1, 0);
+    }
+
+    #[test]
+    fn test_breakdown() {
+        let santa = Equation::SANTA;
+        let masses = [12, 1969, 100756, 1969, 2];
+        let breakdown = santa.breakdown(&masses).unwrap();
+        assert_eq!(
+            breakdown[1],
+            ModuleFuel {
+                mass: 1969,
+                fuel: 654,
+                total: 966
+            }
+        );
+        for (m, b) in masses.iter().zip(breakdown.iter()) {
+            assert_eq!(Ok(b.total), santa.total_fuel(*m));
+        }
+    }
+
+    #[test]
+    fn test_parse_masses() {
+        assert_eq!(parse_masses("12\n\n 1969 \n"), Ok(vec![12, 1969]));
+        assert_eq!(
+            parse_masses("12\n-3\nabc\n340282366920938463463374607431768211456\n"),
+            Err(vec![
+                MassError::Negative {
+                    line: 2,
+                    content: "-3".to_string()
+                },
+                MassError::Invalid {
+                    line: 3,
+                    content: "abc".to_string()
+                },
+                MassError::Invalid {
+                    line: 4,
+                    content: "340282366920938463463374607431768211456".to_string()
+                },
+            ])
+        );
+    }
+}
@@ -0,0 +1,299 @@
+// Space Image Format, from day 8.
+//
+// An image is a string of digits, one per pixel, split into layers of
+// `width * height` pixels which are stacked on top of each other, the first
+// one in front.
+
+use std::fmt;
+use std::slice;
+
+use grid::Grid;
+
+pub mod animation;
+pub mod export;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    Black,
+    White,
+    Transparent,
+    // a digit without any meaning yet, opaque and kept as is
+    Other(u8),
+}
+
+impl Color {
+    pub fn from_char(c: char) -> Option<Color> {
+        match c.to_digit(10)? {
+            0 => Some(Color::Black),
+            1 => Some(Color::White),
+            2 => Some(Color::Transparent),
+            d => Some(Color::Other(d as u8)),
+        }
+    }
+
+    pub fn to_digit(self) -> u8 {
+        match self {
+            Color::Black => 0,
+            Color::White => 1,
+            Color::Transparent => 2,
+            Color::Other(d) => d,
+        }
+    }
+
+    pub fn to_pretty_char(self) -> char {
+        match self {
+            Color::Black => ' ',
+            Color::White => '■',
+            Color::Transparent => '_',
+            Color::Other(d) => (b'0' + d) as char,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SifError {
+    EmptySize,
+    SizeOverflow {
+        width: usize,
+        height: usize,
+    },
+    NoLayer,
+    InvalidDigit {
+        position: usize,
+        c: char,
+    },
+    IncompleteLayer {
+        width: usize,
+        height: usize,
+        pixels: usize,
+    },
+}
+
+impl fmt::Display for SifError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SifError::EmptySize => write!(f, "layers must have at least one pixel"),
+            SifError::SizeOverflow { width, height } => {
+                write!(f, "layers of size ({}, {}) are too large", width, height)
+            }
+            SifError::NoLayer => write!(f, "the image has no layer"),
+            SifError::InvalidDigit { position, c } => {
+                write!(f, "invalid digit {:?} at position {}", c, position)
+            }
+            SifError::IncompleteLayer {
+                width,
+                height,
+                pixels,
+            } => write!(
+                f,
+                "incomplete layer ({}, {}) with {} total pixels",
+                width, height, pixels
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SifError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    layers: Vec<Grid<Color>>,
+}
+
+// number of pixels of each digit in a layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LayerStats {
+    counts: [usize; 10],
+}
+
+impl LayerStats {
+    pub fn count(&self, color: Color) -> usize {
+        self.counts[color.to_digit() as usize]
+    }
+}
+
+impl Image {
+    // surrounding whitespace is ignored, positions in errors are 1-based
+    pub fn parse(text: &str, width: usize, height: usize) -> Result<Image, SifError> {
+        let layer_len = width
+            .checked_mul(height)
+            .ok_or(SifError::SizeOverflow { width, height })?;
+        if layer_len == 0 {
+            return Err(SifError::EmptySize);
+        }
+        let pixels = text
+            .trim()
+            .chars()
+            .enumerate()
+            .map(|(i, c)| Color::from_char(c).ok_or(SifError::InvalidDigit { position: i + 1, c }))
+            .collect::<Result<Vec<Color>, SifError>>()?;
+        if pixels.is_empty() {
+            return Err(SifError::NoLayer);
+        }
+        if !pixels.len().is_multiple_of(layer_len) {
+            return Err(SifError::IncompleteLayer {
+                width,
+                height,
+                pixels: pixels.len(),
+            });
+        }
+        let layers = pixels
+            .chunks(layer_len)
+            .map(|l| Grid::from_vec(width, height, l.to_vec()).unwrap())
+            .collect();
+        Ok(Image {
+            width,
+            height,
+            layers,
+        })
+    }
+
+    #[cfg(test)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[cfg(test)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // from the front to the back
+    pub fn layers(&self) -> slice::Iter<'_, Grid<Color>> {
+        self.layers.iter()
+    }
+
+    pub fn layer_stats(&self) -> Vec<LayerStats> {
+        self.layers
+            .iter()
+            .map(|l| {
+                let mut stats = LayerStats::default();
+                for c in l.cells() {
+                    stats.counts[c.to_digit() as usize] += 1;
+                }
+                stats
+            })
+            .collect()
+    }
+
+    // each pixel shows the color of the first layer which isn't transparent
+    // there
+    pub fn merge_image(&self) -> Grid<Color> {
+        Grid::from_fn(self.width, self.height, |p| {
+            self.layers
+                .iter()
+                .map(|l| l[p])
+                .find(|c| *c != Color::Transparent)
+                .unwrap_or(Color::Transparent)
+        })
+    }
+
+    // the image as seen through the first layer, then the first two, and so
+    // on: the last one is the merged image
+    pub fn compositions<'a>(&'a self) -> impl Iterator<Item = Grid<Color>> + 'a {
+        self.layers.iter().scan(
+            Grid::new(self.width, self.height, Color::Transparent),
+            |seen, layer| {
+                for (p, c) in layer.iter() {
+                    if seen[p] == Color::Transparent {
+                        seen[p] = *c;
+                    }
+                }
+                Some(seen.clone())
+            },
+        )
+    }
+
+    // back to the digit string the image was parsed from
+    #[cfg(test)]
+    pub fn encode(&self) -> String {
+        self.layers
+            .iter()
+            .flat_map(|l| l.cells().iter())
+            .map(|c| (b'0' + c.to_digit()) as char)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sif_parse_errors() {
+        assert_eq!(Image::parse("0122", 0, 2), Err(SifError::EmptySize));
+        assert_eq!(
+            Image::parse("0122", usize::MAX, 2),
+            Err(SifError::SizeOverflow {
+                width: usize::MAX,
+                height: 2
+            })
+        );
+        assert_eq!(Image::parse(" \n", 2, 2), Err(SifError::NoLayer));
+        assert_eq!(
+            Image::parse("01x2", 2, 2),
+            Err(SifError::InvalidDigit {
+                position: 3,
+                c: 'x'
+            })
+        );
+        assert_eq!(
+            Image::parse("012201", 2, 2),
+            Err(SifError::IncompleteLayer {
+                width: 2,
+                height: 2,
+                pixels: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_sif_layers() {
+        // the example from the puzzle
+        let image = Image::parse("123456789012\n", 3, 2).unwrap();
+        let layers: Vec<&[Color]> = image.layers().map(|l| l.cells()).collect();
+        assert_eq!(layers.len(), 2);
+        assert_eq!(
+            layers[1],
+            &[
+                Color::Other(7),
+                Color::Other(8),
+                Color::Other(9),
+                Color::Black,
+                Color::White,
+                Color::Transparent
+            ]
+        );
+
+        let stats = image.layer_stats();
+        assert_eq!(stats[0].count(Color::White), 1);
+        assert_eq!(stats[0].count(Color::Black), 0);
+        assert_eq!(stats[1].count(Color::Black), 1);
+        assert_eq!(stats[1].count(Color::Other(8)), 1);
+    }
+
+    #[test]
+    fn test_sif_round_trip() {
+        let text = "0222112222120000";
+        let image = Image::parse(text, 2, 2).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 2));
+        assert_eq!(image.encode(), text);
+        assert_eq!(Image::parse(&image.encode(), 2, 2), Ok(image.clone()));
+        let wide = Image::parse(&image.encode(), 4, 1).unwrap();
+        assert_eq!((wide.width(), wide.height()), (4, 1));
+        assert_eq!(wide.encode(), text);
+    }
+
+    #[test]
+    fn test_sif_compositions() {
+        let image = Image::parse("0222112222120000", 2, 2).unwrap();
+        let frames: Vec<String> = image
+            .compositions()
+            .map(|f| f.render(|c| (b'0' + c.to_digit()) as char))
+            .collect();
+        assert_eq!(frames, vec!["02\n22\n", "01\n22\n", "01\n12\n", "01\n10\n"]);
+        assert_eq!(image.compositions().last(), Some(image.merge_image()));
+    }
+}
@@ -0,0 +1,54 @@
+// Watching the layers of an image pile up, to see where each pixel of the
+// final picture comes from.
+
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use super::{Color, Image};
+use grid::Grid;
+
+// a frame of the terminal animation: the pixels still transparent after
+// `layer` layers are shown as `_`
+pub fn frame_text(frame: &Grid<Color>, layer: usize, layers: usize) -> String {
+    let transparent = frame
+        .cells()
+        .iter()
+        .filter(|c| **c == Color::Transparent)
+        .count();
+    format!(
+        "layer {}/{}, {} transparent pixels\n{}",
+        layer,
+        layers,
+        transparent,
+        frame.render(|c| c.to_pretty_char())
+    )
+}
+
+// draw each composition over the previous one, waiting `delay` in between
+pub fn play(image: &Image, delay: Duration) {
+    let layers = image.layers().len();
+    let stdout = io::stdout();
+    for (i, frame) in image.compositions().enumerate() {
+        let mut out = stdout.lock();
+        // move to the top left corner and clear the screen
+        write!(out, "\x1b[H\x1b[2J{}", frame_text(&frame, i + 1, layers)).unwrap();
+        out.flush().unwrap();
+        thread::sleep(delay);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_frame_text() {
+        let image = Image::parse("0222112222120000", 2, 2).unwrap();
+        let frame = image.compositions().next().unwrap();
+        assert_eq!(
+            frame_text(&frame, 1, 4),
+            "layer 1/4, 3 transparent pixels\n _\n__\n"
+        );
+    }
+}
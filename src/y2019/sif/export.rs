@@ -0,0 +1,602 @@
+// Writing merged images to files.
+//
+// Netpbm files are plain text so that two pictures can be diffed, PNG is
+// written without compression: stored deflate blocks are enough for pictures
+// this small and don't need a compression library. Animations are written as
+// APNG or GIF, the latter with a LZW code table which is reset before it can
+// grow, so that every pixel is a code of the same width.
+
+use std::convert::TryFrom;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use super::Color;
+use grid::Grid;
+
+impl Color {
+    // transparent pixels are grey, so that they stand out from both black and
+    // white ones
+    pub fn rgb(self) -> [u8; 3] {
+        match self {
+            Color::Black => [0, 0, 0],
+            Color::White => [255, 255, 255],
+            Color::Transparent => [128, 128, 128],
+            Color::Other(d) => [d * 28; 3],
+        }
+    }
+
+    pub fn rgba(self) -> [u8; 4] {
+        let [r, g, b] = self.rgb();
+        match self {
+            Color::Transparent => [r, g, b, 0],
+            _ => [r, g, b, 255],
+        }
+    }
+}
+
+// png sizes are 4 bytes but must fit a signed integer
+const MAX_SIDE: u32 = i32::MAX as u32;
+
+// size of the picture once scaled, an error for a zero scale or when the
+// picture gets too large to be written
+fn scaled_size(image: &Grid<Color>, scale: usize) -> io::Result<(u32, u32)> {
+    if scale == 0 {
+        return Err(invalid_input("the scale must be at least 1".to_string()));
+    }
+    let side = |len: usize| {
+        len.checked_mul(scale)
+            .and_then(|l| u32::try_from(l).ok())
+            .filter(|&l| l <= MAX_SIDE)
+            .ok_or_else(|| invalid_input(format!("scale {} is too large", scale)))
+    };
+    Ok((side(image.width())?, side(image.height())?))
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+// each pixel becomes a `scale` by `scale` square
+fn scaled_rows<T, F>(image: &Grid<Color>, scale: usize, f: F) -> Vec<Vec<T>>
+where
+    T: Clone,
+    F: Fn(Color) -> T,
+{
+    let mut rows = Vec::with_capacity(image.height() * scale);
+    for row in image.rows() {
+        let scaled: Vec<T> = row
+            .iter()
+            .flat_map(|c| std::iter::repeat_n(f(*c), scale))
+            .collect();
+        for _ in 0..scale {
+            rows.push(scaled.clone());
+        }
+    }
+    rows
+}
+
+// plain bitmap: 1 for black, 0 for anything else
+pub fn pbm(image: &Grid<Color>, scale: usize) -> io::Result<String> {
+    let (width, height) = scaled_size(image, scale)?;
+    let rows = scaled_rows(image, scale, |c| if c == Color::Black { "1" } else { "0" });
+    let mut out = format!("P1\n{} {}\n", width, height);
+    for row in rows {
+        out.push_str(&row.join(" "));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+pub fn ppm(image: &Grid<Color>, scale: usize) -> io::Result<String> {
+    let (width, height) = scaled_size(image, scale)?;
+    let rows = scaled_rows(image, scale, |c| {
+        let [r, g, b] = c.rgb();
+        format!("{} {} {}", r, g, b)
+    });
+    let mut out = format!("P3\n{} {}\n255\n", width, height);
+    for row in rows {
+        out.push_str(&row.join("  "));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+// 8 bits RGBA, transparent pixels stay transparent
+pub fn png(image: &Grid<Color>, scale: usize) -> io::Result<Vec<u8>> {
+    let (width, height) = scaled_size(image, scale)?;
+    let mut out = png_header(width, height);
+    png_chunk(&mut out, b"IDAT", &zlib_stored(&scanlines(image, scale)));
+    png_chunk(&mut out, b"IEND", &[]);
+    Ok(out)
+}
+
+// an animated png showing the frames one after the other, forever. The
+// frames must all have the same size.
+pub fn apng(frames: &[Grid<Color>], scale: usize, delay: Duration) -> io::Result<Vec<u8>> {
+    let first = frames
+        .first()
+        .expect("an animation needs at least one frame");
+    let (width, height) = scaled_size(first, scale)?;
+    let delay_ms = delay.as_millis().min(u16::MAX as u128) as u16;
+
+    let mut out = png_header(width, height);
+    let mut control = Vec::with_capacity(8);
+    control.extend_from_slice(&(frames.len() as u32).to_be_bytes());
+    control.extend_from_slice(&0u32.to_be_bytes());
+    png_chunk(&mut out, b"acTL", &control);
+
+    // frame controls and frame data share the same sequence numbers
+    let mut sequence = 0u32;
+    for (i, frame) in frames.iter().enumerate() {
+        let mut control = Vec::with_capacity(26);
+        control.extend_from_slice(&sequence.to_be_bytes());
+        control.extend_from_slice(&width.to_be_bytes());
+        control.extend_from_slice(&height.to_be_bytes());
+        // x and y offsets
+        control.extend_from_slice(&[0; 8]);
+        control.extend_from_slice(&delay_ms.to_be_bytes());
+        control.extend_from_slice(&1000u16.to_be_bytes());
+        // no disposal, replace the whole canvas
+        control.extend_from_slice(&[0, 0]);
+        png_chunk(&mut out, b"fcTL", &control);
+        sequence += 1;
+
+        let data = zlib_stored(&scanlines(frame, scale));
+        if i == 0 {
+            png_chunk(&mut out, b"IDAT", &data);
+        } else {
+            let mut frame_data = Vec::with_capacity(4 + data.len());
+            frame_data.extend_from_slice(&sequence.to_be_bytes());
+            frame_data.extend_from_slice(&data);
+            png_chunk(&mut out, b"fdAT", &frame_data);
+            sequence += 1;
+        }
+    }
+    png_chunk(&mut out, b"IEND", &[]);
+    Ok(out)
+}
+
+// the ten digits index a palette of 16 colors
+const GIF_MIN_CODE_SIZE: u8 = 4;
+
+// an animated gif showing the frames one after the other, forever. The
+// frames must all have the same size and the delay is rounded down to
+// hundredths of a second.
+pub fn gif(frames: &[Grid<Color>], scale: usize, delay: Duration) -> io::Result<Vec<u8>> {
+    let first = frames
+        .first()
+        .expect("an animation needs at least one frame");
+    let (width, height) = scaled_size(first, scale)?;
+    let side = |l: u32| {
+        u16::try_from(l).map_err(|_| invalid_input(format!("scale {} is too large", scale)))
+    };
+    let (width, height) = (side(width)?, side(height)?);
+    let delay_cs = (delay.as_millis() / 10).min(u16::MAX as u128) as u16;
+    let transparent = Color::Transparent.to_digit();
+
+    let mut out = b"GIF89a".to_vec();
+    out.extend_from_slice(&width.to_le_bytes());
+    out.extend_from_slice(&height.to_le_bytes());
+    // global color table of 2^(3 + 1) colors, 8 bits per primary color
+    out.extend_from_slice(&[0xf3, transparent, 0]);
+    for digit in 0..16 {
+        let color = match Color::from_char((b'0' + digit) as char) {
+            Some(color) if digit < 10 => color.rgb(),
+            _ => [0, 0, 0],
+        };
+        out.extend_from_slice(&color);
+    }
+    // loop forever
+    out.extend_from_slice(&[0x21, 0xff, 11]);
+    out.extend_from_slice(b"NETSCAPE2.0");
+    out.extend_from_slice(&[3, 1, 0, 0, 0]);
+
+    for frame in frames {
+        // graphic control: clear the frame before the next one, with a
+        // transparent color
+        out.extend_from_slice(&[0x21, 0xf9, 4, (2 << 2) | 1]);
+        out.extend_from_slice(&delay_cs.to_le_bytes());
+        out.extend_from_slice(&[transparent, 0]);
+
+        // image descriptor covering the whole canvas, no local color table
+        out.push(0x2c);
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&width.to_le_bytes());
+        out.extend_from_slice(&height.to_le_bytes());
+        out.push(0);
+
+        let indices: Vec<u8> = scaled_rows(frame, scale, Color::to_digit)
+            .into_iter()
+            .flatten()
+            .collect();
+        out.push(GIF_MIN_CODE_SIZE);
+        for block in lzw_uncompressed(&indices).chunks(255) {
+            out.push(block.len() as u8);
+            out.extend_from_slice(block);
+        }
+        out.push(0);
+    }
+    out.push(0x3b);
+    Ok(out)
+}
+
+// LZW codes for `indices` which never use the code table: a clear code is
+// sent before the decoder would widen the codes
+fn lzw_uncompressed(indices: &[u8]) -> Vec<u8> {
+    let clear = 1u32 << GIF_MIN_CODE_SIZE;
+    let end = clear + 1;
+    let code_size = GIF_MIN_CODE_SIZE as u32 + 1;
+    // each code after the first one after a clear adds an entry to the
+    // decoder's table, which starts right after the end code
+    let run = (1 << code_size) - (end + 1) as usize;
+
+    let mut out = Vec::new();
+    let (mut bits, mut len) = (0u32, 0u32);
+    let mut emit = |code: u32| {
+        bits |= code << len;
+        len += code_size;
+        while len >= 8 {
+            out.push(bits as u8);
+            bits >>= 8;
+            len -= 8;
+        }
+    };
+    for chunk in indices.chunks(run) {
+        emit(clear);
+        for &index in chunk {
+            emit(index as u32);
+        }
+    }
+    emit(end);
+    if len > 0 {
+        out.push(bits as u8);
+    }
+    out
+}
+
+// the format is picked from the extension: png or gif
+pub fn write_animation(
+    frames: &[Grid<Color>],
+    scale: usize,
+    delay: Duration,
+    path: &Path,
+) -> io::Result<()> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let bytes = match extension.to_lowercase().as_ref() {
+        "png" => apng(frames, scale, delay)?,
+        "gif" => gif(frames, scale, delay)?,
+        _ => {
+            return Err(invalid_input(format!(
+                "unknown animation format: {:?}",
+                extension
+            )))
+        }
+    };
+    fs::write(path, bytes)
+}
+
+// signature and IHDR chunk
+fn png_header(width: u32, height: u32) -> Vec<u8> {
+    let mut out = PNG_SIGNATURE.to_vec();
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // bit depth, color type (RGBA), compression, filter, interlace
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+    png_chunk(&mut out, b"IHDR", &header);
+    out
+}
+
+fn scanlines(image: &Grid<Color>, scale: usize) -> Vec<u8> {
+    let rows = scaled_rows(image, scale, Color::rgba);
+    let mut raw = Vec::with_capacity(rows.len() * (1 + 4 * image.width() * scale));
+    for row in rows {
+        // no filter
+        raw.push(0);
+        for pixel in row {
+            raw.extend_from_slice(&pixel);
+        }
+    }
+    raw
+}
+
+// the format is picked from the extension: pbm, ppm or png
+pub fn write_image(image: &Grid<Color>, scale: usize, path: &Path) -> io::Result<()> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let bytes = match extension.to_lowercase().as_ref() {
+        "pbm" => pbm(image, scale)?.into_bytes(),
+        "ppm" => ppm(image, scale)?.into_bytes(),
+        "png" => png(image, scale)?,
+        _ => {
+            return Err(invalid_input(format!(
+                "unknown image format: {:?}",
+                extension
+            )))
+        }
+    };
+    fs::write(path, bytes)
+}
+
+const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+// zlib stream made of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xffff;
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none() as u8;
+        let len = block.len() as u16;
+        out.push(last);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::Image;
+    use super::*;
+
+    fn example() -> Grid<Color> {
+        Image::parse("0222112222120000", 2, 2)
+            .unwrap()
+            .merge_image()
+    }
+
+    #[test]
+    fn test_netpbm() {
+        assert_eq!(pbm(&example(), 1).unwrap(), "P1\n2 2\n1 0\n0 1\n");
+        assert_eq!(
+            pbm(&example(), 2).unwrap(),
+            "P1\n4 4\n1 1 0 0\n1 1 0 0\n0 0 1 1\n0 0 1 1\n"
+        );
+        assert_eq!(
+            ppm(&example(), 1).unwrap(),
+            "P3\n2 2\n255\n0 0 0  255 255 255\n255 255 255  0 0 0\n"
+        );
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn test_zlib_stored() {
+        let data: Vec<u8> = (0..70_000u32).map(|i| (i % 251) as u8).collect();
+        let z = zlib_stored(&data);
+        // two blocks, the second one being the last
+        assert_eq!(z[2], 0);
+        assert_eq!(&z[3..5], &[0xff, 0xff]);
+        let second = 2 + 5 + 0xffff;
+        assert_eq!(z[second], 1);
+        assert_eq!(z.len(), 2 + 5 + 0xffff + 5 + (70_000 - 0xffff) + 4);
+        assert_eq!(&z[z.len() - 4..], &adler32(&data).to_be_bytes());
+    }
+
+    #[test]
+    fn test_png() {
+        let png = png(&example(), 3).unwrap();
+        assert_eq!(&png[..8], &PNG_SIGNATURE);
+        // IHDR comes first: length, type, width and height
+        assert_eq!(&png[8..16], &[0, 0, 0, 13, b'I', b'H', b'D', b'R']);
+        assert_eq!(&png[16..24], &[0, 0, 0, 6, 0, 0, 0, 6]);
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+
+        // the pixels are stored as is: 6 rows of a filter byte and 6 pixels
+        let idat = &png[33..];
+        assert_eq!(&idat[4..8], b"IDAT");
+        let raw_len = 6 * (1 + 6 * 4);
+        let block = &idat[8 + 2 + 5..8 + 2 + 5 + raw_len];
+        assert_eq!(&block[..5], &[0, 0, 0, 0, 255]);
+        assert_eq!(&block[1 + 3 * 4..1 + 4 * 4], &[255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_invalid_scale() {
+        let kind = |r: io::Result<Vec<u8>>| r.unwrap_err().kind();
+        assert_eq!(kind(png(&example(), 0)), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            kind(png(&example(), usize::MAX)),
+            io::ErrorKind::InvalidInput
+        );
+        assert_eq!(kind(png(&example(), 1 << 30)), io::ErrorKind::InvalidInput);
+        assert!(pbm(&example(), 0).is_err());
+        assert!(apng(&[example()], 0, Duration::from_millis(1)).is_err());
+    }
+
+    // type and data of each chunk
+    fn chunks(png: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut result = Vec::new();
+        let mut i = 8;
+        while i < png.len() {
+            let len = u32::from_be_bytes([png[i], png[i + 1], png[i + 2], png[i + 3]]) as usize;
+            let kind = String::from_utf8(png[i + 4..i + 8].to_vec()).unwrap();
+            let data = png[i + 8..i + 8 + len].to_vec();
+            assert_eq!(
+                crc32(&png[i + 4..i + 8 + len]).to_be_bytes(),
+                png[i + 8 + len..i + 12 + len]
+            );
+            result.push((kind, data));
+            i += 12 + len;
+        }
+        result
+    }
+
+    #[test]
+    fn test_apng() {
+        let image = Image::parse("0222112222120000", 2, 2).unwrap();
+        let frames: Vec<Grid<Color>> = image.compositions().collect();
+        let apng = apng(&frames, 1, Duration::from_millis(250)).unwrap();
+        let chunks = chunks(&apng);
+        let kinds: Vec<&str> = chunks.iter().map(|(k, _)| k.as_ref()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                "IHDR", "acTL", "fcTL", "IDAT", "fcTL", "fdAT", "fcTL", "fdAT", "fcTL", "fdAT",
+                "IEND"
+            ]
+        );
+        assert_eq!(chunks[1].1, vec![0, 0, 0, 4, 0, 0, 0, 0]);
+
+        let sequence: Vec<u32> = chunks
+            .iter()
+            .filter(|(k, _)| k == "fcTL" || k == "fdAT")
+            .map(|(_, d)| u32::from_be_bytes([d[0], d[1], d[2], d[3]]))
+            .collect();
+        assert_eq!(sequence, (0..7).collect::<Vec<u32>>());
+        // 250/1000 of a second
+        assert_eq!(&chunks[2].1[20..24], &[0, 250, 3, 232]);
+    }
+
+    // a LZW decoder as described in the GIF specification, returning the
+    // color indices
+    fn lzw_decode(data: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let end = clear + 1;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let mut code_size = min_code_size as usize + 1;
+        let mut previous: Option<Vec<u8>> = None;
+        let mut out = Vec::new();
+        let (mut bits, mut len, mut i) = (0usize, 0usize, 0);
+        loop {
+            while len < code_size {
+                bits |= (data[i] as usize) << len;
+                len += 8;
+                i += 1;
+            }
+            let code = bits & ((1 << code_size) - 1);
+            bits >>= code_size;
+            len -= code_size;
+
+            if code == clear {
+                table = (0..clear).map(|c| vec![c as u8]).collect();
+                table.push(Vec::new());
+                table.push(Vec::new());
+                code_size = min_code_size as usize + 1;
+                previous = None;
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+            let entry = match (table.get(code), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(p)) => {
+                    let mut entry = p.clone();
+                    entry.push(p[0]);
+                    entry
+                }
+                (None, None) => panic!("invalid code {}", code),
+            };
+            if let Some(mut p) = previous.take() {
+                p.push(entry[0]);
+                table.push(p);
+                if table.len() == 1 << code_size && code_size < 12 {
+                    code_size += 1;
+                }
+            }
+            out.extend_from_slice(&entry);
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn test_lzw_uncompressed() {
+        for len in [0, 1, 13, 14, 15, 100, 1000].iter() {
+            let indices: Vec<u8> = (0..*len).map(|i| (i * 7 % 10) as u8).collect();
+            let data = lzw_uncompressed(&indices);
+            assert_eq!(lzw_decode(&data, GIF_MIN_CODE_SIZE), indices);
+        }
+    }
+
+    #[test]
+    fn test_gif() {
+        let image = Image::parse("0222112222120000", 2, 2).unwrap();
+        let frames: Vec<Grid<Color>> = image.compositions().collect();
+        let gif = gif(&frames, 3, Duration::from_millis(250)).unwrap();
+        assert_eq!(&gif[..6], b"GIF89a");
+        assert_eq!(&gif[6..13], &[6, 0, 6, 0, 0xf3, 2, 0]);
+        // black, white then grey for transparent
+        assert_eq!(&gif[13..22], &[0, 0, 0, 255, 255, 255, 128, 128, 128]);
+        assert_eq!(gif[gif.len() - 1], 0x3b);
+
+        // walk through the frames after the palette and the loop extension
+        let mut i = 13 + 48 + 19;
+        let mut decoded = Vec::new();
+        while gif[i] == 0x21 {
+            assert_eq!(&gif[i..i + 8], &[0x21, 0xf9, 4, 9, 25, 0, 2, 0]);
+            assert_eq!(&gif[i + 8..i + 18], &[0x2c, 0, 0, 0, 0, 6, 0, 6, 0, 0]);
+            assert_eq!(gif[i + 18], GIF_MIN_CODE_SIZE);
+            i += 19;
+            let mut data = Vec::new();
+            while gif[i] != 0 {
+                data.extend_from_slice(&gif[i + 1..i + 1 + gif[i] as usize]);
+                i += 1 + gif[i] as usize;
+            }
+            i += 1;
+            decoded.push(lzw_decode(&data, GIF_MIN_CODE_SIZE));
+        }
+        assert_eq!(i, gif.len() - 1);
+        assert_eq!(decoded.len(), 4);
+        let last: Vec<u8> = scaled_rows(&image.merge_image(), 3, Color::to_digit)
+            .into_iter()
+            .flatten()
+            .collect();
+        assert_eq!(decoded[3], last);
+        assert_eq!(&decoded[0][..6], &[0, 0, 0, 2, 2, 2]);
+    }
+
+    #[test]
+    fn test_write_animation_format() {
+        let frames = vec![example()];
+        let delay = Duration::from_millis(80);
+        let err = write_animation(&frames, 1, delay, Path::new("day08.ppm")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(gif(&frames, 1 << 15, delay).is_err());
+    }
+}